@@ -27,3 +27,8 @@ nnoremap <buffer><silent><leader>ec :EditConfig<CR>
 nnoremap <buffer><silent><leader>rc :ReloadConfig<CR>
 
 let g:conceal=50
+
+" Besides the history entries, the buffer receives
+"   {"Annotation":{"id":1,"note":"idor","tags":["auth"],"color":"red"}}
+" on every annotate and for all annotations on every attach. Replace the
+" annotation of the id, an annotation with only the id removes it.
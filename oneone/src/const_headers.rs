@@ -16,4 +16,6 @@ pub const CLOSE: &str = "close";
 
 pub const PROXY_CONNECTION: &str = "Proxy-Connection";
 
+pub const HOST: &str = "Host";
+
 pub const WS_EXT: &str = "Sec-WebSocket-Extensions";
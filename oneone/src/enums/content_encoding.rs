@@ -31,3 +31,17 @@ impl From<&str> for ContentEncoding {
         }
     }
 }
+
+impl ContentEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentEncoding::Brotli => BROTLI,
+            ContentEncoding::Compress => COMPRESS,
+            ContentEncoding::Deflate => DEFLATE,
+            ContentEncoding::Gzip => GZIP,
+            ContentEncoding::Identity => IDENTITY,
+            ContentEncoding::Zstd => ZSTD,
+            ContentEncoding::Chunked => CHUNKED,
+        }
+    }
+}
@@ -111,6 +111,15 @@ where
         self.body.as_mut()
     }
 
+    // Size of body as received, without chunk size and CRLF for chunked body
+    pub fn body_len(&self) -> usize {
+        match self.body.as_ref() {
+            Some(Body::Raw(data)) => data.len(),
+            Some(Body::Chunked(chunks)) => total_chunk_size(chunks),
+            None => 0,
+        }
+    }
+
     pub fn has_connection_keep_alive(&self) -> Option<usize> {
         self.header_struct
             .header_map()
@@ -3,6 +3,7 @@ use thiserror::Error;
 use crate::forward_info::ForwardInfo;
 use crate::io::inc_dir::DirError;
 use crate::io::unix_sock::error::UnixSockError;
+use crate::jar::JarError;
 use crate::run::boundary::IsUIError;
 
 #[derive(Debug, Error)]
@@ -19,6 +20,8 @@ pub enum AddonError {
     Serialize(#[from] serde_json::Error),
     #[error("dir error| {0:?}")]
     DirError(#[from] DirError),
+    #[error("jar| {0}")]
+    Jar(#[from] JarError),
 }
 
 impl IsUIError for AddonError {
@@ -17,6 +17,7 @@ use crate::file_types::EXT_REQ;
 use crate::forward_info::{ForwardInfo, Module};
 use crate::io::inc_dir::{DirError, incremental};
 use crate::io::unix_sock::error::UnixSockError;
+use crate::jar::{JarRequest, SharedJar, merge_file};
 use crate::run::boundary::{
     Buffer, CloseAction, FlushStorage, HandleCommander, NotifyCommander
 };
@@ -30,6 +31,7 @@ pub struct AddonHandler {
     from_commander: Receiver<ForwardInfo>,
     addons_map: HashMap<String, Addon>,
     buf: BytesMut,
+    jar: SharedJar,
 }
 
 impl AddonHandler {
@@ -37,11 +39,13 @@ impl AddonHandler {
     pub fn new(
         from_commander: Receiver<ForwardInfo>,
         addons_map: HashMap<String, Addon>,
+        jar: SharedJar,
     ) -> AddonHandler {
         AddonHandler {
             from_commander,
             addons_map,
             buf: BytesMut::with_capacity(CAPACITY_2MB),
+            jar,
         }
     }
 
@@ -51,15 +55,18 @@ impl AddonHandler {
      *      3. Build incremental destination file, by calling build_addon_dest
      *      4. Copy info.file to dest_file
      *      5. Get tls from server_info, if None, set it to true
-     *      6. Build addon command string by calling build_addon_cmd
+     *      6. If addon.use_jar and server_info is present, merge cookies in
+     *      the session jar into dest_file
+     *      7. Build addon command string by calling build_addon_cmd
      *      with addon_name, dest_file and is_tls
-     *      7. Build new AddonMsg and serialize to string and return
+     *      8. Build new AddonMsg and serialize to string and return
      *
      * Error:
      *      AddonError::WrongMsg        [1]
      *      AddonError::AddonNotFound   [2]
      *      AddonError::DirError        [3] [4]
-     *      AddonError::Serialize       [7]
+     *      AddonError::Jar             [6]
+     *      AddonError::Serialize       [8]
      */
 
     pub async fn build_cmd_string(
@@ -88,6 +95,17 @@ impl AddonHandler {
             .map(|info| info.is_tls())
             .unwrap_or(true);
 
+        if addon.use_jar
+            && let Some(server_info) = info.server_info.as_ref()
+        {
+            let jar = JarRequest {
+                jar: self.jar.clone(),
+                host: server_info.host.clone(),
+                tls: is_tls,
+            };
+            merge_file(&jar, &dest_file).await?;
+        }
+
         let cmd = addon.build_cmd(name, &dest_file.to_string_lossy(), is_tls);
 
         let addon_msg = AddonMsg::new(cmd, dest_file);
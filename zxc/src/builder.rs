@@ -1,8 +1,10 @@
 use std::fs::{File, remove_file};
 use std::io::{BufRead, BufReader, Error, Read};
+use std::path::{Path, PathBuf};
+use std::thread::spawn;
 
 use tokio::net::UnixListener;
-use tokio::sync::mpsc::{Receiver, Sender, channel};
+use tokio::sync::mpsc::{Receiver, Sender, channel, unbounded_channel};
 use tracing::{error, trace};
 
 use crate::addons::handler::AddonHandler;
@@ -15,12 +17,18 @@ use crate::config::global::parser::parse_global_config;
 use crate::config::local::proxy::ProxyArgs;
 use crate::config::{Config, GlobalConfig};
 use crate::forward_info::ForwardInfo;
+use crate::history::analyzer::{Analyzer, ToAnalyzer};
+use crate::history::annotation::{Annotations, HISTORY_ANNOTATIONS};
+use crate::history::db::HISTORY_DB;
+use crate::history::db::writer::{ToDb, run_db_writer};
 use crate::history::handler::HistoryHandler;
 use crate::history::message::from_commander::CommanderToHistory;
 use crate::history::message::from_ui::HistoryUIOps;
+use crate::history::passive::{HISTORY_FINDINGS, Passive};
 use crate::interceptor::InterceptorHandler;
 use crate::interceptor::message::from_ui::InterUIOps;
 use crate::interceptor::message::to_ui::InterToUI;
+use crate::jar::{CookieJar, SESSION_COOKIES, SharedJar};
 use crate::proxy::handler_state::transition::write_history::HistoryEnum;
 use crate::repeater::RepeaterHandler;
 
@@ -31,6 +39,7 @@ pub const COMMANDER: &str = "commander";
 
 pub const INTERCEPTOR: &str = "interceptor";
 pub const HISTORY: &str = "history";
+pub const ANALYZER: &str = "analyzer";
 pub const REPEATER: &str = "repeater";
 pub const ADDONS: &str = "addons";
 
@@ -44,12 +53,21 @@ pub struct Builder {
     comm_soldiers: Option<Receiver<CommanderRequest>>,
     global_config: Option<GlobalConfig>,
     index: usize,
+    jar: SharedJar,
     sname: String,
 }
 
 impl Builder {
+    // Cookie jar of the session is loaded, since cwd is session directory
     pub fn new(index: usize, sname: String) -> Builder {
         let attach = index != 1;
+        let path = PathBuf::from(SESSION_COOKIES);
+        let jar = CookieJar::load(path.clone())
+            .unwrap_or_else(|e| {
+                error!("load cookie jar| {}", e);
+                CookieJar::new(path)
+            })
+            .shared();
         Builder {
             attach,
             captain_crypto: CaptainCrypto::new().unwrap(),
@@ -60,6 +78,7 @@ impl Builder {
             comm_soldiers: None,
             global_config: None,
             index,
+            jar,
             sname,
         }
     }
@@ -79,9 +98,26 @@ impl Builder {
         UnixListener::bind(sock)
     }
 
-    pub fn build_history(&mut self) -> HistoryHandler {
+    /* Steps:
+     *      1. If attach, read history state.
+     *
+     *      2. If db or session already has history.db, start the db writer
+     *         thread [ run_db_writer() ], which opens it. Existing directory
+     *         based session is migrated on first open.
+     *
+     *      3. Load annotations of the session.
+     *
+     *      4. Load findings of the session for passive analysis and add
+     *         secret patterns from global config.
+     *
+     * Returns:
+     *      (HistoryHandler, Analyzer)
+     */
+
+    pub fn build_history(&mut self, db: bool) -> (HistoryHandler, Analyzer) {
         let (send_cth, recv_cth) = channel::<CommanderToHistory>(100); // Commander to History
         let (send_htc, recv_htc) = channel::<HistoryUIOps>(1); // History to Commander
+        let (send_ta, recv_ta) = unbounded_channel::<ToAnalyzer>(); // Proxy and Commander to Analyzer
 
         let storage = self
             .attach
@@ -95,9 +131,48 @@ impl Builder {
             })
             .unwrap_or_else(Vec::new);
 
-        self.comm_history =
-            Some(HistoryComm::new(self.index, recv_htc, send_cth));
-        HistoryHandler::new(recv_cth, send_htc, storage)
+        let db = (db || Path::new(HISTORY_DB).exists()).then(|| {
+            let (send_db, recv_db) = unbounded_channel::<ToDb>(); // History and Proxy to Db
+            spawn(move || run_db_writer(PathBuf::from("."), recv_db));
+            send_db
+        });
+
+        let annotations =
+            Annotations::load(PathBuf::from(HISTORY_ANNOTATIONS))
+                .unwrap_or_else(|e| {
+                    error!("load annotations| {}", e);
+                    Annotations::default()
+                });
+
+        let mut passive = Passive::load(PathBuf::from(HISTORY_FINDINGS))
+            .unwrap_or_else(|e| {
+                error!("load findings| {}", e);
+                Passive::default()
+            });
+        if let Some(patterns) = self
+            .global_config
+            .as_mut()
+            .and_then(|config| config.parse_secrets())
+        {
+            passive.set_secrets(patterns);
+        }
+
+        let analyzer = Analyzer::new(
+            recv_ta,
+            send_cth.clone(),
+            passive,
+            self.jar.clone(),
+        );
+        self.comm_history = Some(HistoryComm::new(
+            self.index,
+            recv_htc,
+            send_cth,
+            send_ta,
+            db.clone(),
+        ));
+        let history =
+            HistoryHandler::new(recv_cth, send_htc, storage, db, annotations);
+        (history, analyzer)
     }
 
     pub fn build_soldier_comm(&mut self) -> Sender<CommanderRequest> {
@@ -118,7 +193,7 @@ impl Builder {
         let (send_rtc, recv_rtc) = channel::<ForwardInfo>(1); // Repeater to Commander
         self.comm_repeater = Some(RepeaterComm::new(recv_rtc, send_ctr));
         let connector = self.captain_crypto.get_connector();
-        RepeaterHandler::new(connector, recv_ctr, send_rtc)
+        RepeaterHandler::new(connector, recv_ctr, send_rtc, self.jar.clone())
     }
 
     pub fn build_addons(&mut self) -> AddonHandler {
@@ -129,7 +204,7 @@ impl Builder {
             .as_mut()
             .and_then(|config| config.parse_addons())
             .unwrap_or_default();
-        AddonHandler::new(recv_cta, addons)
+        AddonHandler::new(recv_cta, addons, self.jar.clone())
     }
 
     pub fn build_commander(
@@ -143,10 +218,22 @@ impl Builder {
             trace!("config| None");
         }
 
+        // channel is empty before commander starts
+        let comm_history = self.comm_history.unwrap();
+        let scope = config
+            .as_ref()
+            .and_then(Config::domain_filter);
+        if let Err(e) = comm_history
+            .to_history
+            .try_send(CommanderToHistory::Scope(scope))
+        {
+            error!("send scope| {}", e);
+        }
+
         Commander::new(
             self.captain_crypto,
             self.comm_addons.unwrap(),
-            self.comm_history.unwrap(),
+            comm_history,
             self.comm_interceptor.unwrap(),
             self.comm_repeater.unwrap(),
             self.comm_soldiers.unwrap(),
@@ -1,12 +1,13 @@
 mod ca;
 pub mod error;
 mod verifier;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use ca::*;
 use error::*;
 use openssl::hash::DigestBytes;
-use rcgen::{CertificateParams, KeyPair};
+use rcgen::{CertificateParams, DnType, KeyPair};
 use rustls_pki_types::PrivateKeyDer;
 use tokio_rustls::TlsConnector;
 use tokio_rustls::rustls::client::WebPkiServerVerifier;
@@ -27,6 +28,8 @@ const ALPN_H1: &[u8] = b"http/1.1";
 pub struct CaptainCrypto {
     connector: Arc<TlsConnector>,
     key_pair: KeyPair,
+    // server name => config, for mocked hosts
+    mock_store: HashMap<String, Arc<ServerConfig>>,
     private_key: PrivateKeyDer<'static>,
     trusted_ca: CA,
     untrusted_ca: CA,
@@ -93,6 +96,7 @@ impl CaptainCrypto {
         Ok(CaptainCrypto {
             connector,
             key_pair,
+            mock_store: HashMap::new(),
             private_key,
             trusted_ca,
             untrusted_ca,
@@ -188,6 +192,53 @@ impl CaptainCrypto {
 
         Ok(tosend)
     }
+
+    /* Description:
+     *      Generate certificate for the server name of a mocked host, signed
+     *      by trusted CA. Server certificate is not available as the server
+     *      is not connected.
+     *
+     * Steps:
+     *      1. If config exists in mock_store, return it.
+     *      2. Build CertificateParams with server name as subject alt name
+     *         and common name.
+     *      3. Sign with trusted CA and generate Server Config.
+     *      4. Store in mock_store.
+     *
+     * Error:
+     *      CertError::Rcgen    [2] [3]
+     *      CertError::Rustls   [3]
+     */
+
+    pub fn generate_host_cert(
+        &mut self,
+        server_name: &str,
+    ) -> Result<Arc<ServerConfig>, CertError> {
+        // 1. Exists
+        if let Some(config) = self.mock_store.get(server_name) {
+            trace!("mock cert exists| Y");
+            return Ok(config.clone());
+        }
+        // 2. Params
+        let mut params =
+            CertificateParams::new(vec![server_name.to_string()])?;
+        params
+            .distinguished_name
+            .push(DnType::CommonName, server_name);
+        // 3. Sign
+        let cert: CertificateDer<'static> = params
+            .signed_by(&self.key_pair, self.trusted_ca.cert(), &self.key_pair)?
+            .into();
+        let config = Arc::new(generate_server_config(
+            cert,
+            self.private_key.clone_key(),
+        )?);
+        trace!("mock cert| Y");
+        // 4. Store
+        self.mock_store
+            .insert(server_name.to_string(), config.clone());
+        Ok(config)
+    }
 }
 
 /* Description:
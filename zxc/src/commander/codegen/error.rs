@@ -0,0 +1,15 @@
+use std::io;
+use std::path::PathBuf;
+
+use oneone::UpdateFrameError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CodeGenError {
+    #[error("read| {0}")]
+    Read(#[from] io::Error),
+    #[error("no server info| {0}")]
+    NoServerInfo(PathBuf),
+    #[error("parse| {0}")]
+    Parse(#[from] UpdateFrameError),
+}
@@ -0,0 +1,346 @@
+use std::fmt::Write;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use super::CodeRequest;
+
+const INDENT: &str = "    ";
+
+// Body as text if utf8 without nul, else base64 encoded
+enum CodeBody<'a> {
+    Text(&'a str),
+    Base64(String),
+}
+
+impl<'a> CodeBody<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        match std::str::from_utf8(data) {
+            Ok(text) if !text.contains('\0') => CodeBody::Text(text),
+            _ => CodeBody::Base64(STANDARD.encode(data)),
+        }
+    }
+}
+
+// Single quoted shell string, ' is written as '\''
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+// Double quoted string with json escapes, valid in python and javascript
+fn json_quote(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
+
+// Rust string literal
+fn rust_quote(s: &str) -> String {
+    format!("{:?}", s)
+}
+
+/* Description:
+ *      Generate curl command.
+ *
+ * Steps:
+ *      1. If body is binary, pipe the base64 decoded body to curl and read it
+ *         with --data-binary @-
+ *      2. Headers with -H, empty value is written as "key;" since "key:"
+ *         removes the header in curl.
+ *      3. Cookies with -b
+ *      4. Text body with --data-raw, which does not treat @ as file.
+ */
+
+pub fn curl(req: &CodeRequest) -> String {
+    let body = req.body.as_deref().map(CodeBody::new);
+    let mut code = String::new();
+    // 1. Binary body
+    if let Some(CodeBody::Base64(data)) = &body {
+        let _ = write!(code, "echo {} | base64 -d | ", shell_quote(data));
+    }
+    let _ = write!(
+        code,
+        "curl --path-as-is -X {} {}",
+        shell_quote(&req.method),
+        shell_quote(&req.url)
+    );
+    // 2. Headers
+    for (key, value) in req.headers.iter() {
+        let header = if value.is_empty() {
+            format!("{key};")
+        } else {
+            format!("{key}: {value}")
+        };
+        let _ = write!(code, " \\\n  -H {}", shell_quote(&header));
+    }
+    // 3. Cookies
+    if let Some(cookie) = req.cookie_header() {
+        let _ = write!(code, " \\\n  -b {}", shell_quote(&cookie));
+    }
+    // 4. Body
+    match body {
+        Some(CodeBody::Text(text)) => {
+            let _ = write!(code, " \\\n  --data-raw {}", shell_quote(text));
+        }
+        Some(CodeBody::Base64(_)) => code.push_str(" \\\n  --data-binary @-"),
+        None => (),
+    }
+    code
+}
+
+/* Description:
+ *      Generate python requests snippet.
+ *
+ * Steps:
+ *      1. Headers and cookies as dict.
+ *      2. Text body is encoded to utf8 bytes, as requests encodes str body as
+ *         latin-1. Binary body is base64 decoded.
+ */
+
+pub fn python(req: &CodeRequest) -> String {
+    let body = req.body.as_deref().map(CodeBody::new);
+    let mut code = String::new();
+    if matches!(body, Some(CodeBody::Base64(_))) {
+        code.push_str("import base64\n");
+    }
+    code.push_str("import requests\n\n");
+    let _ = writeln!(code, "url = {}", json_quote(&req.url));
+    let mut args = String::new();
+    // 1. Headers and cookies
+    for (name, pairs) in [("headers", &req.headers), ("cookies", &req.cookies)]
+    {
+        if pairs.is_empty() {
+            continue;
+        }
+        let _ = writeln!(code, "{name} = {{");
+        for (key, value) in pairs.iter() {
+            let _ = writeln!(
+                code,
+                "{INDENT}{}: {},",
+                json_quote(key),
+                json_quote(value)
+            );
+        }
+        code.push_str("}\n");
+        let _ = write!(args, ", {name}={name}");
+    }
+    // 2. Body
+    match &body {
+        Some(CodeBody::Text(text)) => {
+            let _ = writeln!(code, "data = {}.encode()", json_quote(text));
+        }
+        Some(CodeBody::Base64(data)) => {
+            let _ = writeln!(
+                code,
+                "data = base64.b64decode({})",
+                json_quote(data)
+            );
+        }
+        None => (),
+    }
+    if body.is_some() {
+        args.push_str(", data=data");
+    }
+    let _ = write!(
+        code,
+        "\nresponse = requests.request({}, url{})",
+        json_quote(&req.method),
+        args
+    );
+    code
+}
+
+/* Description:
+ *      Generate javascript fetch call. Cookies are sent in Cookie header.
+ *      Binary body is base64 decoded to Uint8Array.
+ */
+
+pub fn fetch(req: &CodeRequest) -> String {
+    let mut options = vec![format!("method: {}", json_quote(&req.method))];
+    let mut headers = req
+        .headers
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "{INDENT}{INDENT}{}: {}",
+                json_quote(key),
+                json_quote(value)
+            )
+        })
+        .collect::<Vec<String>>();
+    if let Some(cookie) = req.cookie_header() {
+        headers.push(format!(
+            "{INDENT}{INDENT}\"Cookie\": {}",
+            json_quote(&cookie)
+        ));
+    }
+    if !headers.is_empty() {
+        options
+            .push(format!("headers: {{\n{}\n{INDENT}}}", headers.join(",\n")));
+    }
+    match req.body.as_deref().map(CodeBody::new) {
+        Some(CodeBody::Text(text)) => {
+            options.push(format!("body: {}", json_quote(text)));
+        }
+        Some(CodeBody::Base64(data)) => options.push(format!(
+            "body: Uint8Array.from(atob({}), (c) => c.charCodeAt(0))",
+            json_quote(&data)
+        )),
+        None => (),
+    }
+    format!(
+        "fetch({}, {{\n{INDENT}{}\n}});",
+        json_quote(&req.url),
+        options.join(&format!(",\n{INDENT}"))
+    )
+}
+
+/* Description:
+ *      Generate rust reqwest snippet. Cookies are sent in Cookie header.
+ *      Binary body is base64 decoded with base64 crate.
+ */
+
+pub fn reqwest(req: &CodeRequest) -> String {
+    let body = req.body.as_deref().map(CodeBody::new);
+    let mut code = String::new();
+    if matches!(body, Some(CodeBody::Base64(_))) {
+        code.push_str(
+            "use base64::Engine;\nuse base64::engine::general_purpose::STANDARD;\n\n",
+        );
+    }
+    code.push_str("let client = reqwest::Client::new();\n");
+    code.push_str("let response = client\n");
+    let _ = writeln!(
+        code,
+        "{INDENT}.request({}.parse::<reqwest::Method>()?, {})",
+        rust_quote(&req.method),
+        rust_quote(&req.url)
+    );
+    for (key, value) in req.headers.iter() {
+        let _ = writeln!(
+            code,
+            "{INDENT}.header({}, {})",
+            rust_quote(key),
+            rust_quote(value)
+        );
+    }
+    if let Some(cookie) = req.cookie_header() {
+        let _ = writeln!(
+            code,
+            "{INDENT}.header(\"Cookie\", {})",
+            rust_quote(&cookie)
+        );
+    }
+    match body {
+        Some(CodeBody::Text(text)) => {
+            let _ = writeln!(code, "{INDENT}.body({})", rust_quote(text));
+        }
+        Some(CodeBody::Base64(data)) => {
+            let _ = writeln!(
+                code,
+                "{INDENT}.body(STANDARD.decode({})?)",
+                rust_quote(&data)
+            );
+        }
+        None => (),
+    }
+    let _ = write!(code, "{INDENT}.send()\n{INDENT}.await?;");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(body: Option<&[u8]>) -> CodeRequest {
+        CodeRequest {
+            method: "POST".to_string(),
+            url: "https://example.com/a?b=1".to_string(),
+            headers: vec![
+                ("X-Quote".to_string(), "it's \"q\"".to_string()),
+                ("X-Empty".to_string(), "".to_string()),
+            ],
+            cookies: vec![("a".to_string(), "1".to_string())],
+            body: body.map(|body| body.to_vec()),
+        }
+    }
+
+    #[test]
+    fn test_codegen_curl() {
+        let verify = r#"curl --path-as-is -X 'POST' 'https://example.com/a?b=1' \
+  -H 'X-Quote: it'\''s "q"' \
+  -H 'X-Empty;' \
+  -b 'a=1' \
+  --data-raw '@user=a'\''b'"#;
+        assert_eq!(curl(&request(Some(b"@user=a'b"))), verify);
+    }
+
+    #[test]
+    fn test_codegen_curl_binary() {
+        let code = curl(&request(Some(&[0xff, 0x00])));
+        assert!(code.starts_with("echo '/wA=' | base64 -d | curl "));
+        assert!(code.ends_with("--data-binary @-"));
+    }
+
+    #[test]
+    fn test_codegen_python() {
+        let verify = r#"import requests
+
+url = "https://example.com/a?b=1"
+headers = {
+    "X-Quote": "it's \"q\"",
+    "X-Empty": "",
+}
+cookies = {
+    "a": "1",
+}
+data = "line\r\n\"end\"".encode()
+
+response = requests.request("POST", url, headers=headers, cookies=cookies, data=data)"#;
+        assert_eq!(python(&request(Some(b"line\r\n\"end\""))), verify);
+    }
+
+    #[test]
+    fn test_codegen_python_binary() {
+        let code = python(&request(Some(b"a\0b")));
+        assert!(code.starts_with("import base64\nimport requests\n"));
+        assert!(code.contains("data = base64.b64decode(\"YQBi\")\n"));
+    }
+
+    #[test]
+    fn test_codegen_fetch() {
+        let verify = r#"fetch("https://example.com/a?b=1", {
+    method: "POST",
+    headers: {
+        "X-Quote": "it's \"q\"",
+        "X-Empty": "",
+        "Cookie": "a=1"
+    },
+    body: Uint8Array.from(atob("/wA="), (c) => c.charCodeAt(0))
+});"#;
+        assert_eq!(fetch(&request(Some(&[0xff, 0x00]))), verify);
+    }
+
+    #[test]
+    fn test_codegen_fetch_no_body() {
+        let mut req = request(None);
+        req.headers.clear();
+        req.cookies.clear();
+        let verify = r#"fetch("https://example.com/a?b=1", {
+    method: "POST"
+});"#;
+        assert_eq!(fetch(&req), verify);
+    }
+
+    #[test]
+    fn test_codegen_reqwest() {
+        let verify = r#"let client = reqwest::Client::new();
+let response = client
+    .request("POST".parse::<reqwest::Method>()?, "https://example.com/a?b=1")
+    .header("X-Quote", "it's \"q\"")
+    .header("X-Empty", "")
+    .header("Cookie", "a=1")
+    .body("tab\t\u{1b}")
+    .send()
+    .await?;"#;
+        assert_eq!(reqwest(&request(Some(b"tab\t\x1b"))), verify);
+    }
+}
@@ -0,0 +1,245 @@
+use std::path::PathBuf;
+
+use bytes::BytesMut;
+use oneone::{Request, update_one_one};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+pub mod error;
+mod generate;
+use error::CodeGenError;
+
+use crate::proxy::handler_state::handlers::get_attr;
+use crate::proxy::server_info::json::ServerInfoJson;
+use crate::proxy::server_info::scheme::Scheme;
+
+// Headers set by the generated client
+const SKIP_HEADERS: [&str; 2] = ["content-length", "transfer-encoding"];
+const COOKIE: &str = "cookie";
+const HOST: &str = "host";
+
+// Languages to generate code for
+#[cfg_attr(test, derive(Clone, Copy))]
+#[derive(Debug, Deserialize)]
+pub enum CodeLang {
+    Curl,
+    Python,
+    Fetch,
+    Reqwest,
+}
+
+/* Description:
+ *      Copy as info from history, interceptor and repeater ui.
+ *      If server_info is missing, it is read from the xattrs of the file.
+ *
+ * Format:
+ *      [1, {"operation": {"CopyAs": {
+ *              "file": "./history/1/1.req",
+ *              "lang": "Curl",
+ *              "server_info": {"host": "example.com", "http": true}
+ *      }}}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub struct CopyAsInfo {
+    pub file: PathBuf,
+    pub lang: CodeLang,
+    #[serde(default)]
+    pub server_info: Option<ServerInfoJson>,
+}
+
+// Parsed request used to generate code
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct CodeRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    cookies: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+}
+
+impl CodeRequest {
+    /* Steps:
+     *      1. Build OneOne<Request> by calling update_one_one()
+     *
+     *      2. Build url from scheme and host of server_info. If uri is in
+     *         absolute form, use it as is.
+     *
+     *      3. Collect headers except,
+     *          a. SKIP_HEADERS, which are set by the client.
+     *          b. Host, if same as server_info host.
+     *          c. Cookie, which is split into name value pairs.
+     *
+     *      4. If body is present, set body.
+     *
+     * Error:
+     *      CodeGenError::Parse     [1]
+     */
+
+    pub fn parse(
+        data: BytesMut,
+        server_info: &ServerInfoJson,
+    ) -> Result<Self, CodeGenError> {
+        // 1. Parse
+        let mut one = update_one_one::<Request>(data)?;
+
+        // 2. Url
+        let uri = one.uri_as_string().into_owned();
+        let url = if uri.starts_with('/') || uri == "*" {
+            let scheme = if server_info.is_tls() {
+                Scheme::Https
+            } else {
+                Scheme::Http
+            };
+            format!("{}://{}{}", scheme, server_info.host, uri)
+        } else {
+            uri
+        };
+
+        // 3. Headers
+        let mut headers = Vec::new();
+        let mut cookies = Vec::new();
+        for header in one
+            .header_struct()
+            .header_map()
+            .headers()
+        {
+            let (key, value) = (header.key_as_str(), header.value_as_str());
+            if SKIP_HEADERS
+                .iter()
+                .any(|skip| key.eq_ignore_ascii_case(skip))
+                || (key.eq_ignore_ascii_case(HOST)
+                    && value == server_info.host)
+            {
+                continue;
+            }
+            if key.eq_ignore_ascii_case(COOKIE) {
+                cookies.extend(value.split(';').filter_map(|pair| {
+                    let pair = pair.trim();
+                    if pair.is_empty() {
+                        return None;
+                    }
+                    let (name, value) = pair
+                        .split_once('=')
+                        .unwrap_or((pair, ""));
+                    Some((name.to_string(), value.to_string()))
+                }));
+                continue;
+            }
+            headers.push((key.to_string(), value.to_string()));
+        }
+
+        // 4. Body
+        let body = if one.body().is_some() {
+            one.get_body()
+                .into_data()
+                .filter(|data| !data.is_empty())
+                .map(|data| data.to_vec())
+        } else {
+            None
+        };
+
+        Ok(CodeRequest {
+            method: one.method_as_string().into_owned(),
+            url,
+            headers,
+            cookies,
+            body,
+        })
+    }
+
+    // Cookie pairs joined as a Cookie header value
+    fn cookie_header(&self) -> Option<String> {
+        (!self.cookies.is_empty()).then(|| {
+            self.cookies
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<String>>()
+                .join("; ")
+        })
+    }
+}
+
+/* Steps:
+ *      1. Read file.
+ *      2. Get server info from info, else from xattrs of file.
+ *      3. Parse request [ CodeRequest::parse() ]
+ *      4. Generate code for the language.
+ *
+ * Returns:
+ *      {"result": "curl ..."}
+ *
+ * Error:
+ *      CodeGenError::Read          [1]
+ *      CodeGenError::NoServerInfo  [2]
+ *      CodeGenError::Parse         [3]
+ */
+
+pub async fn perform_copy_as(
+    info: &CopyAsInfo,
+) -> Result<Value, CodeGenError> {
+    let data = tokio::fs::read(&info.file).await?;
+    let attr;
+    let server_info = match info.server_info.as_ref() {
+        Some(server_info) => server_info,
+        None => {
+            attr = get_attr(&info.file)
+                .map_err(|_| CodeGenError::NoServerInfo(info.file.clone()))?;
+            &attr
+        }
+    };
+    let req = CodeRequest::parse(BytesMut::from(&data[..]), server_info)?;
+    let code = match info.lang {
+        CodeLang::Curl => generate::curl(&req),
+        CodeLang::Python => generate::python(&req),
+        CodeLang::Fetch => generate::fetch(&req),
+        CodeLang::Reqwest => generate::reqwest(&req),
+    };
+    Ok(json!({"result" : code}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_info() -> ServerInfoJson {
+        ServerInfoJson {
+            host: "example.com".to_string(),
+            http: None,
+            sni: None,
+        }
+    }
+
+    #[test]
+    fn test_code_request_parse() {
+        let data = BytesMut::from(
+            "POST /login?a=1 HTTP/1.1\r\nHost: example.com\r\nCookie: a=1; b\r\nContent-Length: 10\r\nX-Test: it's\r\n\r\nuser=admin",
+        );
+        let req = CodeRequest::parse(data, &server_info()).unwrap();
+        let verify = CodeRequest {
+            method: "POST".to_string(),
+            url: "https://example.com/login?a=1".to_string(),
+            headers: vec![("X-Test".to_string(), "it's".to_string())],
+            cookies: vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "".to_string()),
+            ],
+            body: Some(b"user=admin".to_vec()),
+        };
+        assert_eq!(req, verify);
+    }
+
+    #[test]
+    fn test_code_request_parse_host_differs() {
+        let data = BytesMut::from(
+            "GET http://other.com/ HTTP/1.1\r\nHost: other.com\r\n\r\n",
+        );
+        let req = CodeRequest::parse(data, &server_info()).unwrap();
+        assert_eq!(req.url, "http://other.com/");
+        assert_eq!(
+            req.headers,
+            vec![("Host".to_string(), "other.com".to_string())]
+        );
+        assert!(req.body.is_none());
+    }
+}
@@ -1,13 +1,17 @@
 use std::path::PathBuf;
 
-use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender};
 
+use crate::history::analyzer::ToAnalyzer;
+use crate::history::db::writer::ToDb;
 use crate::history::message::from_commander::CommanderToHistory;
 use crate::history::message::from_ui::HistoryUIOps;
 
 pub struct HistoryComm {
     pub from_history: Receiver<HistoryUIOps>,
     pub to_history: Sender<CommanderToHistory>,
+    pub to_analyzer: UnboundedSender<ToAnalyzer>,
+    pub to_db: Option<UnboundedSender<ToDb>>,
     history_path: PathBuf,
     pub http_log_index: usize,
 }
@@ -18,10 +22,14 @@ impl HistoryComm {
         http_log_index: usize,
         from_history: Receiver<HistoryUIOps>,
         to_history: Sender<CommanderToHistory>,
+        to_analyzer: UnboundedSender<ToAnalyzer>,
+        to_db: Option<UnboundedSender<ToDb>>,
     ) -> Self {
         Self {
             from_history,
             to_history,
+            to_analyzer,
+            to_db,
             history_path: PathBuf::from("./history"),
             http_log_index,
         }
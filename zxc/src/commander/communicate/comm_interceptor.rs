@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use tokio::sync::mpsc::error::SendError;
 use tokio::sync::mpsc::{Receiver, Sender};
 
@@ -6,16 +8,23 @@ use crate::file_types::FileType;
 use crate::interceptor::message::from_ui::InterUIOps;
 use crate::interceptor::message::to_ui::InterToUI;
 
-// Type Alias
+// Intercepted message waiting for the ui to resume/drop
+pub struct Intercepted {
+    pub conn_id: usize,
+    pub log_id: usize,
+    pub ft: FileType,
+    since: Instant,
+}
 
 // Handles Commander to interceptor communication
 pub struct InterceptorComm {
     pub from_interceptor: Receiver<InterUIOps>,
     to_interceptor: Sender<InterToUI>,
-    http_queue: Vec<(usize, usize)>,
-    wreq_queue: Vec<(usize, usize)>,
-    wres_queue: Vec<(usize, usize)>,
+    http_queue: Vec<Intercepted>,
+    wreq_queue: Vec<Intercepted>,
+    wres_queue: Vec<Intercepted>,
     status: bool,
+    response_status: bool,
 }
 
 impl InterceptorComm {
@@ -31,6 +40,7 @@ impl InterceptorComm {
             wreq_queue: Vec::new(),
             wres_queue: Vec::new(),
             status: false,
+            response_status: false,
         }
     }
 
@@ -42,6 +52,15 @@ impl InterceptorComm {
         self.status = !self.status;
     }
 
+    // Whether all http responses should be intercepted
+    pub fn response_status(&self) -> bool {
+        self.response_status
+    }
+
+    pub fn toggle_response(&mut self) {
+        self.response_status = !self.response_status;
+    }
+
     pub async fn send_to_interceptor(
         &mut self,
         conn_id: usize,
@@ -54,20 +73,32 @@ impl InterceptorComm {
         } else {
             &mut self.wres_queue
         };
-        let log_id = msg.id();
-        queue.push((conn_id, log_id));
+        queue.push(Intercepted {
+            conn_id,
+            log_id: msg.id(),
+            ft: *msg.file_type(),
+            since: Instant::now(),
+        });
         self.to_interceptor.send(msg).await
     }
 
-    pub fn http_queue_as_mut(&mut self) -> &mut Vec<(usize, usize)> {
+    // Send msg to ui without queueing
+    pub async fn notify_interceptor(
+        &mut self,
+        msg: InterToUI,
+    ) -> Result<(), SendError<InterToUI>> {
+        self.to_interceptor.send(msg).await
+    }
+
+    pub fn http_queue_as_mut(&mut self) -> &mut Vec<Intercepted> {
         &mut self.http_queue
     }
 
-    pub fn wreq_queue_as_mut(&mut self) -> &mut Vec<(usize, usize)> {
+    pub fn wreq_queue_as_mut(&mut self) -> &mut Vec<Intercepted> {
         &mut self.wreq_queue
     }
 
-    pub fn wres_queue_as_mut(&mut self) -> &mut Vec<(usize, usize)> {
+    pub fn wres_queue_as_mut(&mut self) -> &mut Vec<Intercepted> {
         &mut self.wres_queue
     }
 
@@ -83,9 +114,41 @@ impl InterceptorComm {
         };
         let pos = queue
             .iter()
-            .position(|&(_, stored_log_id)| stored_log_id == log_id)
+            .position(|entry| entry.log_id == log_id && entry.ft == *ft)
             .ok_or(CommunicateError::NoId(log_id, "resume_intercept"))?;
-        let conn_id = queue.swap_remove(pos).0;
+        let conn_id = queue.swap_remove(pos).conn_id;
         Ok(conn_id)
     }
+
+    // Time at which the oldest queued message was intercepted
+    pub fn oldest(&self) -> Option<Instant> {
+        self.http_queue
+            .iter()
+            .chain(self.wreq_queue.iter())
+            .chain(self.wres_queue.iter())
+            .map(|entry| entry.since)
+            .min()
+    }
+
+    /* Steps:
+     *      For each queue, remove the messages that have been waiting for
+     *      longer than the timeout and return them.
+     */
+
+    pub fn pop_expired(&mut self, timeout: Duration) -> Vec<Intercepted> {
+        let mut expired = Vec::new();
+        for queue in
+            [&mut self.http_queue, &mut self.wreq_queue, &mut self.wres_queue]
+        {
+            let mut index = 0;
+            while index < queue.len() {
+                if queue[index].since.elapsed() >= timeout {
+                    expired.push(queue.swap_remove(index));
+                } else {
+                    index += 1;
+                }
+            }
+        }
+        expired
+    }
 }
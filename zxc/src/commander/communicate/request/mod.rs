@@ -6,6 +6,7 @@ use tokio_rustls::rustls::pki_types::CertificateDer;
 use super::response::CommanderResponse;
 use crate::interceptor::message::to_ui::InterToUI;
 use crate::proxy::handler_state::role::Role;
+use crate::proxy::server_info::scheme::Scheme;
 
 // Requests that soldiers sends to commander
 pub enum CommanderRequest {
@@ -22,6 +23,14 @@ pub enum CommanderRequest {
     CheckCertificate(usize, bool, DigestBytes),
     GenNewCert(usize, bool, DigestBytes, Vec<CertificateDer<'static>>),
 
+    // ----- Mock / Remote -----
+    // CONNECT, (id, host, server name)
+    MockCert(usize, String, String),
+    // http request, (id, method, host, path)
+    ShouldMock(usize, String, String, String),
+    // http request, (id, scheme, host, uri)
+    MapRemote(usize, Scheme, String, String),
+
     // ----- Should Log -----
     // http
     ShouldLogHttp(usize, String),
@@ -35,5 +44,7 @@ pub enum CommanderRequest {
     ShouldProxyWs(usize),
     WsRegister(usize),
     ShouldInterceptWsRespone(usize),
+    // http response, (id, host, status code, content type)
+    ShouldInterceptResponse(usize, String, String, Option<String>),
     Close(usize),
 }
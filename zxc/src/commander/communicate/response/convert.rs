@@ -52,9 +52,29 @@ impl TryFrom<CommanderResponse> for Result<Arc<ServerConfig>, CertError> {
     }
 }
 
-impl TryFrom<CommanderResponse>
-    for Option<(usize, PathBuf, Sender<CommanderToHistory>)>
-{
+impl TryFrom<CommanderResponse> for Option<Mock> {
+    type Error = WrongMessage;
+
+    fn try_from(value: CommanderResponse) -> Result<Self, Self::Error> {
+        match value {
+            CommanderResponse::Mock(mock) => Ok(mock),
+            _ => Err(WrongMessage(value.to_string())),
+        }
+    }
+}
+
+impl TryFrom<CommanderResponse> for Option<Remap> {
+    type Error = WrongMessage;
+
+    fn try_from(value: CommanderResponse) -> Result<Self, Self::Error> {
+        match value {
+            CommanderResponse::Remap(remap) => Ok(remap),
+            _ => Err(WrongMessage(value.to_string())),
+        }
+    }
+}
+
+impl TryFrom<CommanderResponse> for Option<HttpLogInfo> {
     type Error = WrongMessage;
 
     fn try_from(value: CommanderResponse) -> Result<Self, Self::Error> {
@@ -3,27 +3,46 @@ use std::path::PathBuf;
 use std::sync::Arc;
 pub mod convert;
 
-use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender};
 use tokio_rustls::TlsConnector;
 use tokio_rustls::rustls::ServerConfig;
 use tokio_rustls::rustls::client::WebPkiServerVerifier;
 
 use crate::commander::captain_crypto::error::CertError;
+use crate::config::{Mock, Remap};
+use crate::history::analyzer::ToAnalyzer;
+use crate::history::db::writer::ToDb;
 use crate::history::message::from_commander::CommanderToHistory;
 use crate::interceptor::message::from_ui::resume_info::ResumeInfo;
 
+// Log id, log path and the senders of the logged http messages
+pub struct HttpLogInfo {
+    pub index: usize,
+    pub path: PathBuf,
+    pub history: Sender<CommanderToHistory>,
+    pub analyzer: UnboundedSender<ToAnalyzer>,
+    pub db: Option<UnboundedSender<ToDb>>,
+    // http response interception is active i.e. toggle response or response
+    // rules
+    pub intercept_response: bool,
+}
+
 pub enum CommanderResponse {
     ClientConfig(Arc<TlsConnector>),
     Verifier(Arc<WebPkiServerVerifier>),
     ServerConfig(Option<Arc<ServerConfig>>),
     NewCertificate(Result<Arc<ServerConfig>, CertError>),
-    HttpLog(Option<(usize, PathBuf, Sender<CommanderToHistory>)>),
+    Mock(Option<Mock>),
+    Remap(Option<Remap>),
+    HttpLog(Option<HttpLogInfo>),
     Resume(Option<ResumeInfo>),
     WsProxyReply(bool),
     WsRegisterReply((Receiver<CommanderResponse>, Sender<CommanderToHistory>)),
     WsLog(usize),
     WsInterceptReply(bool),
+    ResponseInterceptReply(bool),
     Drop,
+    Respond,
 }
 
 impl CommanderResponse {
@@ -31,6 +50,10 @@ impl CommanderResponse {
         matches!(self, CommanderResponse::Drop)
     }
 
+    pub fn is_respond_msg(&self) -> bool {
+        matches!(self, CommanderResponse::Respond)
+    }
+
     pub fn wreq_need_response(&self) -> bool {
         if let CommanderResponse::Resume(Some(info)) = self {
             return info.is_wreq() && info.need_response();
@@ -48,6 +71,8 @@ impl Display for CommanderResponse {
             CommanderResponse::NewCertificate(_) => {
                 write!(f, "NewCertificate")
             }
+            CommanderResponse::Mock(_) => write!(f, "Mock"),
+            CommanderResponse::Remap(_) => write!(f, "Remap"),
             CommanderResponse::HttpLog(_) => write!(f, "HttpLog"),
             CommanderResponse::Resume(_) => write!(f, "Resume"),
             CommanderResponse::WsProxyReply(_) => write!(f, "WsProxyReply"),
@@ -56,7 +81,11 @@ impl Display for CommanderResponse {
             }
             CommanderResponse::WsLog(_) => write!(f, "WsLog"),
             CommanderResponse::WsInterceptReply(_) => write!(f, "WsIntercept"),
+            CommanderResponse::ResponseInterceptReply(_) => {
+                write!(f, "ResponseIntercept")
+            }
             CommanderResponse::Drop => write!(f, "Drop"),
+            CommanderResponse::Respond => write!(f, "Respond"),
         }
     }
 }
@@ -0,0 +1,201 @@
+use serde::Serialize;
+
+// Max cells in lcs table, larger inputs are diffed as delete and insert
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+// Diff operation on a line or word
+#[derive(Debug, PartialEq, Serialize)]
+pub enum DiffOp {
+    Equal(String),
+    Delete(String),
+    Insert(String),
+}
+
+impl DiffOp {
+    fn value_as_mut(&mut self) -> &mut String {
+        match self {
+            DiffOp::Equal(s) | DiffOp::Delete(s) | DiffOp::Insert(s) => s,
+        }
+    }
+
+    fn same_kind(&self, other: &DiffOp) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+// Split text into runs of whitespace and non whitespace
+pub fn words(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut prev = None;
+    for (index, c) in text.char_indices() {
+        let is_space = c.is_whitespace();
+        if prev.is_some_and(|p| p != is_space) {
+            tokens.push(&text[start..index]);
+            start = index;
+        }
+        prev = Some(is_space);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/* Description:
+ *      Diff two token lists.
+ *
+ * Steps:
+ *      1. Skip common prefix and suffix.
+ *      2. If remaining table size > MAX_LCS_CELLS, delete all left and
+ *         insert all right, else diff with lcs.
+ */
+
+pub fn diff(left: &[&str], right: &[&str]) -> Vec<DiffOp> {
+    // 1. Common prefix and suffix
+    let prefix = left
+        .iter()
+        .zip(right)
+        .take_while(|(l, r)| l == r)
+        .count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let l = &left[prefix..left.len() - suffix];
+    let r = &right[prefix..right.len() - suffix];
+
+    let mut ops = Vec::with_capacity(left.len().max(right.len()));
+    ops.extend(
+        left[..prefix]
+            .iter()
+            .map(|s| DiffOp::Equal(s.to_string())),
+    );
+    // 2. Middle
+    if (l.len() + 1) * (r.len() + 1) > MAX_LCS_CELLS {
+        ops.extend(
+            l.iter()
+                .map(|s| DiffOp::Delete(s.to_string())),
+        );
+        ops.extend(
+            r.iter()
+                .map(|s| DiffOp::Insert(s.to_string())),
+        );
+    } else {
+        lcs(l, r, &mut ops);
+    }
+    ops.extend(
+        left[left.len() - suffix..]
+            .iter()
+            .map(|s| DiffOp::Equal(s.to_string())),
+    );
+    ops
+}
+
+/* Steps:
+ *      1. Build table where table[i][j] is lcs length of l[i..] and r[j..]
+ *      2. Walk the table from start, preferring delete over insert.
+ */
+
+fn lcs(l: &[&str], r: &[&str], ops: &mut Vec<DiffOp>) {
+    let (n, m) = (l.len(), r.len());
+    let width = m + 1;
+    // 1. Table
+    let mut table = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i * width + j] = if l[i] == r[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+    // 2. Walk
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if l[i] == r[j] {
+            ops.push(DiffOp::Equal(l[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            ops.push(DiffOp::Delete(l[i].to_string()));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Insert(r[j].to_string()));
+            j += 1;
+        }
+    }
+    ops.extend(
+        l[i..]
+            .iter()
+            .map(|s| DiffOp::Delete(s.to_string())),
+    );
+    ops.extend(
+        r[j..]
+            .iter()
+            .map(|s| DiffOp::Insert(s.to_string())),
+    );
+}
+
+// Merge adjacent operations of the same kind, used for word diff
+pub fn merge(ops: Vec<DiffOp>) -> Vec<DiffOp> {
+    let mut merged: Vec<DiffOp> = Vec::with_capacity(ops.len());
+    for mut op in ops {
+        match merged.last_mut() {
+            Some(last) if last.same_kind(&op) => {
+                last.value_as_mut()
+                    .push_str(op.value_as_mut());
+            }
+            _ => merged.push(op),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let left = ["a", "b", "c", "d"];
+        let right = ["a", "x", "c", "d", "e"];
+        let verify = vec![
+            DiffOp::Equal("a".to_string()),
+            DiffOp::Delete("b".to_string()),
+            DiffOp::Insert("x".to_string()),
+            DiffOp::Equal("c".to_string()),
+            DiffOp::Equal("d".to_string()),
+            DiffOp::Insert("e".to_string()),
+        ];
+        assert_eq!(diff(&left, &right), verify);
+    }
+
+    #[test]
+    fn test_diff_empty() {
+        assert!(diff(&[], &[]).is_empty());
+        assert_eq!(diff(&["a"], &[]), vec![DiffOp::Delete("a".to_string())]);
+    }
+
+    #[test]
+    fn test_diff_words_merge() {
+        let left = words("role is user now");
+        let right = words("role is admin now");
+        let verify = vec![
+            DiffOp::Equal("role is ".to_string()),
+            DiffOp::Delete("user".to_string()),
+            DiffOp::Insert("admin".to_string()),
+            DiffOp::Equal(" now".to_string()),
+        ];
+        assert_eq!(merge(diff(&left, &right)), verify);
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(words(" a  bc\n"), vec![" ", "a", "  ", "bc", "\n"]);
+        assert!(words("").is_empty());
+    }
+}
@@ -0,0 +1,14 @@
+use std::io;
+
+use oneone::{DecompressError, HttpReadError};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CompareError {
+    #[error("read| {0}")]
+    Read(#[from] io::Error),
+    #[error("parse| {0}")]
+    Parse(#[from] HttpReadError),
+    #[error("decompress| {0}")]
+    Decompress(#[from] DecompressError),
+}
@@ -0,0 +1,374 @@
+use std::path::PathBuf;
+
+use buffer::{Cursor, Event};
+use bytes::BytesMut;
+use oneone::{HeaderStruct, InfoLine, OneOne, OneOneState, ParseBodyHeaders};
+use protocol_traits::Step;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+mod diff;
+pub mod error;
+use diff::{DiffOp, diff, merge, words};
+use error::CompareError;
+
+const RESPONSE_PREFIX: &[u8] = b"HTTP/";
+
+// Body diff granularity
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum DiffMode {
+    #[default]
+    Line,
+    Word,
+}
+
+/* Description:
+ *      Compare info from history, interceptor and repeater ui.
+ *      Files can be requests or responses.
+ *
+ * Format:
+ *      [1, {"operation": {"Compare": {
+ *              "left": "./history/1/1.res",
+ *              "right": "./history/2/2.res",
+ *              "ignore_header_order": true,
+ *              "mode": "Word"
+ *      }}}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub struct CompareInfo {
+    pub left: PathBuf,
+    pub right: PathBuf,
+    #[serde(default)]
+    pub ignore_header_order: bool,
+    #[serde(default)]
+    pub mode: DiffMode,
+}
+
+// Normalized http message used to compare
+#[cfg_attr(test, derive(Debug, PartialEq))]
+struct CompareMessage {
+    info_line: String,
+    headers: Vec<String>,
+    body: String,
+    status: Option<u16>,
+}
+
+/* Steps:
+ *      1. Read the data with Event::Read, followed by Event::End so that
+ *         body without length is read till end.
+ *      2. Convert the frame [ into_frame() ], which dechunks and
+ *         decompresses the body.
+ *
+ * Error:
+ *      CompareError::Parse         [1]
+ *      CompareError::Decompress    [2]
+ */
+
+fn normalize<T>(mut data: BytesMut) -> Result<OneOne<T>, CompareError>
+where
+    T: InfoLine,
+    HeaderStruct<T>: ParseBodyHeaders,
+{
+    let mut cbuf = Cursor::new(&mut data);
+    // 1. Read
+    let state = OneOneState::<T>::new()
+        .next(Event::Read(&mut cbuf))?
+        .next(Event::End(&mut cbuf))?;
+    // 2. Convert
+    Ok(state.into_frame()?)
+}
+
+impl CompareMessage {
+    /* Steps:
+     *      1. Info line is the first line of data.
+     *      2. If info line starts with "HTTP/", normalize as response and get
+     *         status code, else normalize as request.
+     */
+
+    fn parse(data: BytesMut) -> Result<Self, CompareError> {
+        let info_line = data[..]
+            .split(|b| *b == b'\n')
+            .next()
+            .map(|line| {
+                String::from_utf8_lossy(line)
+                    .trim_end()
+                    .to_string()
+            })
+            .unwrap_or_default();
+        if data.starts_with(RESPONSE_PREFIX) {
+            let one = normalize::<oneone::Response>(data)?;
+            let status = one.status_code().parse().ok();
+            Ok(Self::build(info_line, one, status))
+        } else {
+            let one = normalize::<oneone::Request>(data)?;
+            Ok(Self::build(info_line, one, None))
+        }
+    }
+
+    fn build<T>(
+        info_line: String,
+        mut one: OneOne<T>,
+        status: Option<u16>,
+    ) -> Self
+    where
+        T: InfoLine,
+        HeaderStruct<T>: ParseBodyHeaders,
+    {
+        let headers = one
+            .header_struct()
+            .header_map()
+            .headers()
+            .iter()
+            .map(|header| {
+                format!("{}: {}", header.key_as_str(), header.value_as_str())
+            })
+            .collect();
+        let body = if one.body().is_some() {
+            one.get_body()
+                .into_data()
+                .map(|data| String::from_utf8_lossy(&data).into_owned())
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        CompareMessage {
+            info_line,
+            headers,
+            body,
+            status,
+        }
+    }
+
+    // Length of the normalized message, CRLF line endings
+    fn len(&self) -> usize {
+        self.info_line.len()
+            + self
+                .headers
+                .iter()
+                .map(|header| header.len() + 2)
+                .sum::<usize>()
+            + 4
+            + self.body.len()
+    }
+
+    fn summary(&self) -> MessageSummary {
+        MessageSummary {
+            status: self.status,
+            length: self.len(),
+            body_length: self.body.len(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct MessageSummary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    length: usize,
+    body_length: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct CompareSummary {
+    left: MessageSummary,
+    right: MessageSummary,
+    identical: bool,
+}
+
+/* Description:
+ *      Structured diff of two messages.
+ *
+ * Format:
+ *      {
+ *          "summary": {
+ *              "left": {"status": 200, "length": 120, "body_length": 20},
+ *              "right": {"status": 403, "length": 98, "body_length": 0},
+ *              "identical": false
+ *          },
+ *          "info_line": [{"Delete": "HTTP/1.1 200 OK"}, {"Insert": ..}],
+ *          "headers": [{"Equal": "Server: nginx"}, ..],
+ *          "body": [{"Equal": "{"}, {"Delete": "\"role\": \"admin\""}, ..]
+ *      }
+ */
+
+#[derive(Debug, Serialize)]
+struct CompareResult {
+    summary: CompareSummary,
+    info_line: Vec<DiffOp>,
+    headers: Vec<DiffOp>,
+    body: Vec<DiffOp>,
+}
+
+/* Steps:
+ *      1. Diff info line.
+ *      2. If ignore_header_order, sort headers by lowercase key and value
+ *         before diff.
+ *      3. Diff body by line or word based on mode. Word diff is merged into
+ *         runs.
+ *      4. Messages are identical if all operations are Equal.
+ */
+
+fn compare(
+    mut left: CompareMessage,
+    mut right: CompareMessage,
+    info: &CompareInfo,
+) -> CompareResult {
+    // 1. Info line
+    let info_line = diff(&[&left.info_line], &[&right.info_line]);
+
+    // 2. Headers
+    if info.ignore_header_order {
+        for msg in [&mut left, &mut right] {
+            msg.headers
+                .sort_by_cached_key(|header| header.to_ascii_lowercase());
+        }
+    }
+    let left_headers: Vec<&str> = left
+        .headers
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let right_headers: Vec<&str> = right
+        .headers
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let headers = diff(&left_headers, &right_headers);
+
+    // 3. Body
+    let body = match info.mode {
+        DiffMode::Line => {
+            let l: Vec<&str> = left.body.lines().collect();
+            let r: Vec<&str> = right.body.lines().collect();
+            diff(&l, &r)
+        }
+        DiffMode::Word => merge(diff(&words(&left.body), &words(&right.body))),
+    };
+
+    // 4. Summary
+    let identical = info_line
+        .iter()
+        .chain(&headers)
+        .chain(&body)
+        .all(|op| matches!(op, DiffOp::Equal(_)));
+    CompareResult {
+        summary: CompareSummary {
+            left: left.summary(),
+            right: right.summary(),
+            identical,
+        },
+        info_line,
+        headers,
+        body,
+    }
+}
+
+/* Steps:
+ *      1. Read and parse both files [ CompareMessage::parse() ]
+ *      2. Compare [ compare() ]
+ *
+ * Returns:
+ *      {"result": CompareResult}
+ *
+ * Error:
+ *      CompareError::Read          [1]
+ *      CompareError::Parse         [1]
+ *      CompareError::Decompress    [1]
+ */
+
+pub async fn perform_compare(
+    info: &CompareInfo,
+) -> Result<Value, CompareError> {
+    let left = tokio::fs::read(&info.left).await?;
+    let right = tokio::fs::read(&info.right).await?;
+    let left = CompareMessage::parse(BytesMut::from(&left[..]))?;
+    let right = CompareMessage::parse(BytesMut::from(&right[..]))?;
+    let result = compare(left, right, info);
+    Ok(json!({"result": result}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(ignore_header_order: bool, mode: DiffMode) -> CompareInfo {
+        CompareInfo {
+            left: PathBuf::new(),
+            right: PathBuf::new(),
+            ignore_header_order,
+            mode,
+        }
+    }
+
+    #[test]
+    fn test_compare_message_parse_chunked() {
+        let data = BytesMut::from(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\n",
+        );
+        let msg = CompareMessage::parse(data).unwrap();
+        assert_eq!(msg.info_line, "HTTP/1.1 200 OK");
+        assert_eq!(msg.status, Some(200));
+        assert_eq!(msg.headers, vec!["Content-Length: 9"]);
+        assert_eq!(msg.body, "Wikipedia");
+    }
+
+    #[test]
+    fn test_compare_message_parse_short_body() {
+        let data = BytesMut::from(
+            "POST / HTTP/1.1\r\nHost: a.com\r\nContent-Length: 20\r\n\r\nuser=admin",
+        );
+        let msg = CompareMessage::parse(data).unwrap();
+        assert_eq!(msg.status, None);
+        assert_eq!(msg.body, "user=admin");
+    }
+
+    #[test]
+    fn test_compare_header_order() {
+        let left = CompareMessage::parse(BytesMut::from(
+            "HTTP/1.1 200 OK\r\nA: 1\r\nB: 2\r\n\r\n",
+        ))
+        .unwrap();
+        let right = CompareMessage::parse(BytesMut::from(
+            "HTTP/1.1 403 Forbidden\r\nB: 2\r\nA: 1\r\n\r\n",
+        ))
+        .unwrap();
+        let result = compare(left, right, &info(true, DiffMode::Line));
+        assert!(
+            result
+                .headers
+                .iter()
+                .all(|op| matches!(op, DiffOp::Equal(_)))
+        );
+        assert_eq!(
+            result.info_line,
+            vec![
+                DiffOp::Delete("HTTP/1.1 200 OK".to_string()),
+                DiffOp::Insert("HTTP/1.1 403 Forbidden".to_string())
+            ]
+        );
+        assert!(!result.summary.identical);
+        assert_eq!(
+            result.summary.right,
+            MessageSummary {
+                status: Some(403),
+                length: 38,
+                body_length: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_compare_body_word() {
+        let data =
+            "HTTP/1.1 200 OK\r\nContent-Length: 13\r\n\r\n{\"role\": \"a\"}";
+        let left = CompareMessage::parse(BytesMut::from(data)).unwrap();
+        let right = CompareMessage::parse(BytesMut::from(data)).unwrap();
+        let result = compare(left, right, &info(false, DiffMode::Word));
+        assert!(result.summary.identical);
+        assert_eq!(
+            result.body,
+            vec![DiffOp::Equal("{\"role\": \"a\"}".to_string())]
+        );
+    }
+}
@@ -1,9 +1,12 @@
 use thiserror::Error;
 use tokio::sync::mpsc::error::SendError;
+use tokio::task::JoinError;
 
 use super::CommanderResponse;
 use crate::config::error::ConfigError;
 use crate::forward_info::ForwardInfo;
+use crate::history::analyzer::ToAnalyzer;
+use crate::history::import::ImportError;
 use crate::history::message::from_commander::CommanderToHistory;
 
 // Commander Error
@@ -32,7 +35,14 @@ pub enum CommunicateError {
     // History
     #[error("History to repeater| {0}")]
     ToRepeater(#[from] SendError<ForwardInfo>),
+    #[error("analyzer| {0}")]
+    ToAnalyzer(#[from] SendError<ToAnalyzer>),
 
     #[error("Config| {0}")]
     Config(#[from] ConfigError),
+
+    #[error("Import| {0}")]
+    Import(#[from] ImportError),
+    #[error("Join| {0}")]
+    Join(#[from] JoinError),
 }
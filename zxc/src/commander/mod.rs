@@ -1,6 +1,8 @@
 pub mod captain_crypto;
 pub mod codec;
+pub mod codegen;
 pub mod communicate;
+pub mod compare;
 pub mod error;
 use communicate::comm_history::HistoryComm;
 use communicate::comm_interceptor::InterceptorComm;
@@ -12,22 +14,29 @@ use std::path::PathBuf;
 use captain_crypto::*;
 pub use communicate::request::CommanderRequest;
 pub use communicate::response::CommanderResponse;
+use communicate::response::HttpLogInfo;
 use error::*;
 pub use protocol::*;
+use serde_json::json;
 use soldiers::Soldiers;
 use tokio::fs::create_dir;
 use tokio::sync::mpsc::{Receiver, Sender, channel};
+use tokio::task::spawn_blocking;
+use tokio::time::{Instant, sleep_until};
 use tracing::{debug, error, trace};
 pub mod soldiers;
 
-use crate::config::Config;
 use crate::config::global::parser::parse_global_config;
 use crate::config::local::io::parse_local_config;
+use crate::config::{Config, GlobalConfig};
 use crate::file_types::FileType;
 use crate::forward_info::{ForwardInfo, Module};
+use crate::history::analyzer::ToAnalyzer;
+use crate::history::import::{ImportInfo, read_import, write_entry};
 use crate::history::message::from_commander::CommanderToHistory;
 use crate::history::message::from_ui::HistoryUIOps;
 use crate::interceptor::message::from_ui::InterUIOps;
+use crate::interceptor::message::to_ui::{AutoResolve, InterToUI};
 
 const WS_REGISTER: &str = "ws_register";
 
@@ -128,7 +137,10 @@ impl Commander {
              *      info    : InterToUI
              *
              * Steps:
-             *      1. If interception is off, send none to respective conn
+             *      1. If interception is off, send none to respective conn.
+             *         Http response is already selected by need_response,
+             *         toggle response or response rules, so it is not
+             *         affected by the interception status.
              *
              *      2. else send msg to ui through interceptor_handle by calling
              *         interceptor_handle.send_to_interceptor()
@@ -139,7 +151,9 @@ impl Commander {
              *      CommunicateError::InterceptorSend
              */
             CommanderRequest::Intercept(id, info) => {
-                if !self.comm_interceptor.status() {
+                if !self.comm_interceptor.status()
+                    && !matches!(info.file_type(), FileType::Res)
+                {
                     trace!("interceptor off");
                     let response = CommanderResponse::Resume(None);
                     let ft = info.file_type();
@@ -253,6 +267,70 @@ impl Commander {
                         (id, response)
                     }
 
+                    /* Associated Values:
+                     *      host        : String
+                     *      server_name : String
+                     *
+                     * Steps:
+                     *      1. If config is_some, call config.mock_host() to
+                     *         check if any mock rule matches the host.
+                     *
+                     *      2. If matched, generate certificate for the server
+                     *         name, so that the tunnel is served without
+                     *         connecting to the server. Error is logged and
+                     *         None is sent, i.e. connect to the server.
+                     */
+                    CommanderRequest::MockCert(id, host, server_name) => {
+                        let mock = self
+                            .config
+                            .as_ref()
+                            .is_some_and(|config| config.mock_host(&host));
+                        trace!("mock host| {}| {}| {}", id, host, mock);
+                        let config = if mock {
+                            self.captain_crypto
+                                .generate_host_cert(&server_name)
+                                .inspect_err(|e| error!("mock cert| {}", e))
+                                .ok()
+                        } else {
+                            None
+                        };
+                        (id, CommanderResponse::ServerConfig(config))
+                    }
+
+                    /* Associated Values:
+                     *      method  : String
+                     *      host    : String
+                     *      path    : String
+                     *
+                     * Steps:
+                     *      If config is_some, call config.mock() to get the
+                     *      first matching mock rule.
+                     */
+                    CommanderRequest::ShouldMock(id, method, host, path) => {
+                        let mock = self.config.as_ref().and_then(|config| {
+                            config.mock(&method, &host, &path)
+                        });
+                        trace!("http mock| {}| {:?}", id, mock);
+                        (id, CommanderResponse::Mock(mock))
+                    }
+
+                    /* Associated Values:
+                     *      scheme  : Scheme
+                     *      host    : String
+                     *      uri     : String
+                     *
+                     * Steps:
+                     *      If config is_some, call config.remap() to get the
+                     *      new target from the first matching remote rule.
+                     */
+                    CommanderRequest::MapRemote(id, scheme, host, uri) => {
+                        let remap = self.config.as_ref().and_then(|config| {
+                            config.remap(scheme, &host, &uri)
+                        });
+                        trace!("http remap| {}| {:?}", id, remap);
+                        (id, CommanderResponse::Remap(remap))
+                    }
+
                     /* Associated Values:
                      *      ext : String
                      *
@@ -261,6 +339,11 @@ impl Commander {
                      *         extension, else true
                      *      2. if true, call self.get_http_log_path() with id
                      *         to get new path and Sender<CommanderToHistory>
+                     *
+                     *      3. Add Sender<ToAnalyzer>, Sender<ToDb> and
+                     *         response_intercept_active() so that the proxy
+                     *         skips the ShouldInterceptResponse query when it
+                     *         is false.
                      */
                     CommanderRequest::ShouldLogHttp(id, ext) => {
                         let result = self
@@ -273,6 +356,17 @@ impl Commander {
                         } else {
                             None
                         };
+                        let active = self.response_intercept_active(); // 3
+                        let comm = &self.comm_history;
+                        let tosend =
+                            tosend.map(|(index, path, history)| HttpLogInfo {
+                                index,
+                                path,
+                                history,
+                                analyzer: comm.to_analyzer.clone(),
+                                db: comm.to_db.clone(),
+                                intercept_response: active,
+                            });
 
                         let response = CommanderResponse::HttpLog(tosend);
                         (id, response)
@@ -288,6 +382,9 @@ impl Commander {
                      *
                      *      2. if true, call self.get_http_log_path() with id
                      *         to get new path and Sender<CommanderToHistory>
+                     *
+                     *      3. Add Sender<ToAnalyzer>, Sender<ToDb> and
+                     *         response_intercept_active()
                      */
                     CommanderRequest::ShouldLogHttpCt(id, ct) => {
                         let result =
@@ -305,10 +402,55 @@ impl Commander {
                         } else {
                             None
                         };
+                        let active = self.response_intercept_active(); // 3
+                        let comm = &self.comm_history;
+                        let tosend =
+                            tosend.map(|(index, path, history)| HttpLogInfo {
+                                index,
+                                path,
+                                history,
+                                analyzer: comm.to_analyzer.clone(),
+                                db: comm.to_db.clone(),
+                                intercept_response: active,
+                            });
                         let response = CommanderResponse::HttpLog(tosend);
                         (id, response)
                     }
 
+                    /* Associated Values:
+                     *      host    : String
+                     *      status  : String
+                     *      ct      : Option<String>
+                     *
+                     * Steps:
+                     *      Intercept if all responses should be intercepted
+                     *      (toggle response) or if the response matches any
+                     *      of the config response rules. Independent of the
+                     *      request interception status.
+                     */
+                    CommanderRequest::ShouldInterceptResponse(
+                        id,
+                        host,
+                        status,
+                        ct,
+                    ) => {
+                        let result = self.comm_interceptor.response_status()
+                            || self
+                                .config
+                                .as_ref()
+                                .is_some_and(|config| {
+                                    config.should_intercept_response(
+                                        &host,
+                                        &status,
+                                        ct.as_deref(),
+                                    )
+                                });
+                        trace!("http intercept response| {}| {}", id, result);
+                        let response =
+                            CommanderResponse::ResponseInterceptReply(result);
+                        (id, response)
+                    }
+
                     CommanderRequest::ShouldProxyWs(id) => {
                         let result = self
                             .config
@@ -338,6 +480,19 @@ impl Commander {
      *          b. else return None
      */
 
+    /* Description:
+     *      Http response interception is active if all responses should be
+     *      intercepted (toggle response) or any response rule exists.
+     */
+
+    fn response_intercept_active(&self) -> bool {
+        self.comm_interceptor.response_status()
+            || self
+                .config
+                .as_ref()
+                .is_some_and(|config| config.has_response_rules())
+    }
+
     pub async fn get_http_log_path(
         &mut self,
         id: usize,
@@ -405,6 +560,80 @@ impl Commander {
         Ok(())
     }
 
+    /* Description:
+     *      Time at which the oldest intercepted message expires.
+     *
+     * Steps:
+     *      1. If config has no intercept_timeout, return None
+     *      2. Else, add timeout to the time the oldest message in the
+     *         interceptor queue was intercepted
+     */
+
+    pub fn intercept_deadline(&self) -> Option<Instant> {
+        let timeout = self
+            .config
+            .as_ref()?
+            .intercept_timeout()?;
+        self.comm_interceptor
+            .oldest()
+            .map(|since| Instant::from_std(since + timeout))
+    }
+
+    /* Description:
+     *      Resolve the intercepted messages whose timeout has expired.
+     *
+     * Steps:
+     *      1. Get intercept_timeout and drop_on_timeout from config
+     *
+     *      2. Pop expired messages from interceptor queues
+     *
+     *      3. For each message,
+     *          a. If drop_on_timeout, send CommanderResponse::Drop
+     *          b. Else, send CommanderResponse::Resume(None) i.e. forward
+     *             unmodified
+     *          c. Notify interceptor ui that the message was auto resolved
+     *
+     * Error:
+     *      CommunicateError::ResponseNoId
+     *      CommunicateError::Send
+     *      CommunicateError::InterceptorSend
+     */
+
+    pub async fn auto_resolve(&mut self) -> Result<(), CommunicateError> {
+        let Some(config) = self.config.as_ref() else {
+            return Ok(());
+        };
+        let Some(timeout) = config.intercept_timeout() else {
+            return Ok(());
+        };
+        let action = if config.drop_on_timeout() {
+            AutoResolve::Drop
+        } else {
+            AutoResolve::Resume
+        };
+
+        for entry in self
+            .comm_interceptor
+            .pop_expired(timeout)
+        {
+            trace!("auto resolve| {}| {:?}", entry.log_id, action);
+            let response = match action {
+                AutoResolve::Drop => CommanderResponse::Drop,
+                AutoResolve::Resume => CommanderResponse::Resume(None),
+            };
+            self.soldiers
+                .send_response_ft(entry.conn_id, &entry.ft, response)
+                .await?;
+            let msg =
+                InterToUI::build_auto_resolved(entry.log_id, entry.ft, action);
+            self.comm_interceptor
+                .notify_interceptor(msg)
+                .await
+                .map_err(|_| CommunicateError::InterceptorSend)?;
+        }
+        Ok(())
+    }
+
     /* Steps:
      *      Match request
      *      1. If toggle, call interceptor_handle.toggle().
@@ -412,12 +641,15 @@ impl Commander {
      *      2. If intercept status is false, empty the interceptor queue
      *      (msgs which have been intercepted).
      *
+     *      If toggle response, call interceptor_handle.toggle_response().
+     *
      *      3. If Forward, call interceptor_handle.forward()
      *
      *      4. if resume,
      *          a. if wreq, and need_response, set_ws_need_response(id)
      *
-     *      5. For resume and drop msgs, get log_id, file_type, response
+     *      5. For resume, respond and drop msgs, get log_id, file_type,
+     *         response. respond is only applicable for http requests.
      *
      *      6. Get connection id from log_id, by calling conn_id_from_log_id
      *
@@ -438,6 +670,11 @@ impl Commander {
                 }
                 Ok(())
             }
+            InterUIOps::ToggleResponse => {
+                trace!("toggle response");
+                self.comm_interceptor.toggle_response();
+                Ok(())
+            }
             InterUIOps::Forward(finfo) => {
                 trace!("interceptor forward");
                 self.forward(finfo).await
@@ -456,6 +693,10 @@ impl Commander {
                         trace!("drop");
                         (id, ft, CommanderResponse::Drop)
                     }
+                    InterUIOps::Respond(id) => {
+                        trace!("respond");
+                        (id, FileType::Req, CommanderResponse::Respond)
+                    }
                     _ => unreachable!(),
                 };
                 let conn_id = self
@@ -480,21 +721,121 @@ impl Commander {
         match msg {
             HistoryUIOps::ReloadConfig => {
                 trace!("reloading config");
-                let global_config = parse_global_config()?;
+                let mut global_config = parse_global_config()?;
+                let secrets = global_config
+                    .as_mut()
+                    .and_then(GlobalConfig::parse_secrets)
+                    .unwrap_or_default();
                 let local_config = parse_local_config()
                     .map_err(|e| error!("parse local conf| {}", e))
                     .ok();
                 self.config = Config::build(local_config, global_config);
+                let scope = self
+                    .config
+                    .as_ref()
+                    .and_then(Config::domain_filter);
+                self.comm_history
+                    .to_history
+                    .send(CommanderToHistory::Scope(scope))
+                    .await?;
+                self.comm_history
+                    .to_analyzer
+                    .send(ToAnalyzer::Secrets(secrets))?;
             }
             HistoryUIOps::Forward(finfo) => {
                 trace!("history forward");
                 self.forward(finfo).await?
             }
+            HistoryUIOps::Import(info) => {
+                trace!("import| {}", info.path.display());
+                self.import(info).await?
+            }
             msg => error!("Should not be reached| {:?}", msg),
         }
         Ok(())
     }
 
+    /* Description:
+     *      Import HAR or raw request into history and reply to history ui
+     *
+     * Steps:
+     *      1. Import the entries [ import_entries() ]
+     *
+     *      2. Send reply to history,
+     *              {"Import": {"entries": 2}}
+     *         On error,
+     *              {"Import": {"error": ".."}}
+     *
+     * Error:
+     *      CommunicateError::WriteHistory     [2]
+     */
+
+    pub async fn import(
+        &mut self,
+        info: ImportInfo,
+    ) -> Result<(), CommunicateError> {
+        // 1. Import
+        let reply = match self.import_entries(info).await {
+            Ok(count) => json!({"Import": {"entries": count}}),
+            Err(e) => {
+                error!("import| {}", e);
+                json!({"Import": {"error": e.to_string()}})
+            }
+        };
+        // 2. Reply
+        self.comm_history
+            .to_history
+            .send(CommanderToHistory::Reply(reply.to_string()))
+            .await?;
+        Ok(())
+    }
+
+    /* Steps:
+     *      1. Read the entries from file in a blocking task.
+     *
+     *      2. For each entry, get log path and id [ get_http_log_path() ],
+     *         write the entry in a blocking task and send the history json
+     *         to history.
+     *
+     * Returns:
+     *      Number of imported entries
+     *
+     * Error:
+     *      CommunicateError::Import           [1] [2]
+     *      CommunicateError::Join             [1] [2]
+     *      CommunicateError::WriteHistory     [2]
+     */
+
+    async fn import_entries(
+        &mut self,
+        info: ImportInfo,
+    ) -> Result<usize, CommunicateError> {
+        // 1. Read
+        let entries = spawn_blocking(move || {
+            read_import(&info.path, info.target.as_deref())
+        })
+        .await??;
+        // 2. Write
+        let mut count = 0;
+        for entry in entries {
+            let Some((index, path, to_history)) =
+                self.get_http_log_path(0).await
+            else {
+                continue;
+            };
+            let lines =
+                spawn_blocking(move || write_entry(&path, index, entry))
+                    .await??;
+            for line in lines {
+                to_history
+                    .send(CommanderToHistory::Http(line))
+                    .await?;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
     pub async fn forward(
         &mut self,
         finfo: ForwardInfo,
@@ -527,12 +868,15 @@ impl Commander {
  *              Interceptor  => handle_interceptor
  *              History      => handle_history
  *              Repeater     => forward
+ *              Deadline     => auto_resolve, if intercept_timeout is set
+ *                              and interceptor queue is not empty
  *              Token        => return
  */
 
 pub async fn run_commander(mut comm: Commander, token: CancellationToken) {
     debug!("[+] commander started");
     loop {
+        let deadline = comm.intercept_deadline();
         tokio::select! {
             soldier_msg = comm.comm_soldiers.recv() => {
                 if let Some(smsg) = soldier_msg {
@@ -562,6 +906,11 @@ pub async fn run_commander(mut comm: Commander, token: CancellationToken) {
                     }
                 }
             }
+            _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                if let Err(e) = comm.auto_resolve().await {
+                    error!("auto resolve| {:?}", e);
+                }
+            }
             _ = token.cancelled() => {
                 trace!("cancelled");
                 return
@@ -2,6 +2,7 @@ use http::http_comm_storage::HttpCommStorage;
 use tokio::sync::mpsc::{Receiver, Sender};
 use ws::ws_comm_storage::WsCommStorage;
 
+use super::communicate::comm_interceptor::Intercepted;
 use super::{CommanderResponse, CommunicateError, WS_REGISTER};
 use crate::file_types::FileType;
 use crate::id::Id;
@@ -98,21 +99,21 @@ impl Soldiers {
 
     pub async fn broadcast_none_http(
         &mut self,
-        list: &mut Vec<(usize, usize)>,
+        list: &mut Vec<Intercepted>,
     ) -> Result<(), CommunicateError> {
         broadcast_none(list, self.http.iter()).await
     }
 
     pub async fn broadcast_none_wreq(
         &mut self,
-        list: &mut Vec<(usize, usize)>,
+        list: &mut Vec<Intercepted>,
     ) -> Result<(), CommunicateError> {
         broadcast_none(list, self.ws.iter(Role::Server)).await
     }
 
     pub async fn broadcast_none_wres(
         &mut self,
-        list: &mut Vec<(usize, usize)>,
+        list: &mut Vec<Intercepted>,
     ) -> Result<(), CommunicateError> {
         broadcast_none(list, self.ws.iter(Role::Client)).await
     }
@@ -133,17 +134,17 @@ where
 }
 
 pub async fn broadcast_none<'a, T>(
-    list: &mut Vec<(usize, usize)>,
+    list: &mut Vec<Intercepted>,
     mut senders: T,
 ) -> Result<(), CommunicateError>
 where
     T: Iterator<Item = (usize, &'a Sender<CommanderResponse>)>,
 {
     if !list.is_empty() {
-        for (id, _) in list.iter() {
+        for entry in list.iter() {
             senders
-                .find(|(sid, _)| sid == id)
-                .ok_or(CommunicateError::NoId(*id, BROADCAST_NONE))?
+                .find(|(sid, _)| *sid == entry.conn_id)
+                .ok_or(CommunicateError::NoId(entry.conn_id, BROADCAST_NONE))?
                 .1
                 .send(CommanderResponse::Resume(None))
                 .await?;
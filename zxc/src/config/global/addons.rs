@@ -8,6 +8,9 @@ pub struct Addon {
     pub http_flag: Option<String>,
     pub https_flag: Option<String>,
     pub add_flag: Option<String>,
+    // merge cookies in session jar into the request
+    #[serde(default)]
+    pub use_jar: bool,
 }
 
 // Format: addon_name request_flag $file http/https_flag add_flag
@@ -48,6 +51,7 @@ pub mod tests {
             http_flag: http_flag.map(|s| s.to_string()),
             https_flag: https_flag.map(|s| s.to_string()),
             add_flag: add_flag.map(|s| s.to_string()),
+            use_jar: false,
         }
     }
 
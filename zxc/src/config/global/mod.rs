@@ -19,6 +19,8 @@ pub struct GlobalConfig {
     pub excluded_extensions: Option<Vec<String>>,
     pub with_ws: Option<bool>,
     pub addons: Option<HashMap<String, Addon>>,
+    // name to regex of secrets detected in responses
+    pub secrets: Option<HashMap<String, String>>,
 }
 
 impl GlobalConfig {
@@ -54,6 +56,7 @@ impl GlobalConfig {
             || self.excluded_extensions.is_some()
             || self.with_ws.is_some()
             || self.addons.is_some()
+            || self.secrets.is_some()
         {
             return Some(self);
         }
@@ -63,6 +66,10 @@ impl GlobalConfig {
     pub fn parse_addons(&mut self) -> Option<HashMap<String, Addon>> {
         self.addons.take()
     }
+
+    pub fn parse_secrets(&mut self) -> Option<HashMap<String, String>> {
+        self.secrets.take()
+    }
 }
 
 #[cfg(test)]
@@ -89,6 +96,9 @@ mod tests {
             request_flag = "-r"
             https_flag = "--force-ssl"
             add_flag = "--batch --dbs"
+
+            [secrets]
+            stripe = "sk_live_[0-9a-zA-Z]{24}"
             "#;
 
         let ffuf = addons::tests::build_ffuf();
@@ -115,6 +125,10 @@ mod tests {
                 ("ffuf".to_string(), ffuf),
                 ("sqlmap".to_string(), sqlmap),
             ])),
+            secrets: Some(HashMap::from([(
+                "stripe".to_string(),
+                "sk_live_[0-9a-zA-Z]{24}".to_string(),
+            )])),
         };
 
         assert_eq!(gc, verify);
@@ -0,0 +1,70 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Subcommand};
+
+// Commands run without starting a session
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Export session history
+    #[command(subcommand)]
+    Export(ExportFormat),
+    /// Import HAR or raw request files to session history
+    Import(ImportArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExportFormat {
+    /// HTTP Archive
+    Har(ExportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Session directory
+    #[arg(default_value = ".")]
+    pub session: PathBuf,
+    /// Output file, defaults to $session_name.har
+    #[arg(short, long = "output")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// Files to import, .har files are read as HTTP Archive, others as raw
+    /// http request
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    /// Session directory
+    #[arg(short, long = "session", default_value = ".")]
+    pub session: PathBuf,
+    /// Target of raw requests, scheme://host[:port]. Defaults to Host header
+    /// with https
+    #[arg(short, long = "target")]
+    pub target: Option<String>,
+}
+
+impl ExportArgs {
+    // Output file or $session_name.har in current directory
+    pub fn output(&self) -> PathBuf {
+        self.output.clone().unwrap_or_else(|| {
+            let name = session_name(&self.session)
+                .unwrap_or_else(|| "session".to_string());
+            PathBuf::from(format!("{name}.har"))
+        })
+    }
+}
+
+impl ImportArgs {
+    pub fn session_name(&self) -> Option<String> {
+        session_name(&self.session)
+    }
+}
+
+// Name of the session directory
+fn session_name(session: &Path) -> Option<String> {
+    session
+        .canonicalize()
+        .ok()?
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
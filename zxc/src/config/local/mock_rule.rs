@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/* Description:
+ *      Rule to mock http request. Set in local config only.
+ *      All the given match fields should match for the rule to match.
+ *      Either response or local should be set. If both are set, response
+ *      is used.
+ *
+ * Format:
+ *      [[mock_rules]]
+ *      method = "GET"                  // method, case insensitive
+ *      host = "api\\.example\\.com"    // host regex
+ *      path = "^/v1/users"             // path regex
+ *      response = "/tmp/users.http"    // file with raw http response
+ *
+ *      [[mock_rules]]
+ *      host = "static\\.example\\.com"
+ *      local = "/srv/static"           // serve files from directory
+ */
+
+#[cfg_attr(test, derive(PartialEq, Clone))]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MockRule {
+    pub method: Option<String>,
+    pub host: Option<String>,
+    pub path: Option<String>,
+    pub response: Option<PathBuf>,
+    pub local: Option<PathBuf>,
+}
+
+impl MockRule {
+    /* Steps:
+     *      1. Remove empty values
+     *      2. If both response and local are empty, return None
+     */
+
+    pub fn sanitize(mut self) -> Option<MockRule> {
+        for field in [&mut self.method, &mut self.host, &mut self.path] {
+            if field
+                .as_ref()
+                .is_some_and(|val| val.is_empty())
+            {
+                field.take();
+            }
+        }
+        for field in [&mut self.response, &mut self.local] {
+            if field
+                .as_ref()
+                .is_some_and(|val| val.as_os_str().is_empty())
+            {
+                field.take();
+            }
+        }
+        if self.response.is_some() || self.local.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_rule_sanitize_no_target() {
+        let rule = MockRule {
+            host: Some("example.com".to_string()),
+            local: Some(PathBuf::new()),
+            ..Default::default()
+        };
+        assert!(rule.sanitize().is_none());
+    }
+
+    #[test]
+    fn test_mock_rule_sanitize() {
+        let rule = MockRule {
+            method: Some("".to_string()),
+            path: Some("^/api".to_string()),
+            response: Some(PathBuf::from("/tmp/res.http")),
+            ..Default::default()
+        };
+        let verify = MockRule {
+            path: Some("^/api".to_string()),
+            response: Some(PathBuf::from("/tmp/res.http")),
+            ..Default::default()
+        };
+        assert_eq!(rule.sanitize(), Some(verify));
+    }
+}
@@ -1,9 +1,14 @@
 use clap::Parser;
+use command::Command;
 use proxy::ProxyArgs;
 use session::SessionArgs;
 
+pub mod command;
 pub mod io;
+pub mod mock_rule;
 pub mod proxy;
+pub mod remote_rule;
+pub mod response_rule;
 mod session;
 
 // Struct for cli args
@@ -16,6 +21,8 @@ pub struct CliArgs {
     /// Debug mode
     #[arg(short, long = "debug")]
     pub debug: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
 }
 
 impl CliArgs {
@@ -31,6 +38,7 @@ impl CliArgs {
             .and_then(|proxy_args| proxy_args.sanitize());
 
         if self.debug
+            || self.command.is_some()
             || self.session_args.is_some()
             || self.proxy_args.is_some()
         {
@@ -4,6 +4,9 @@ use clap::Args;
 use clap::builder::NonEmptyStringValueParser;
 use serde::{Deserialize, Serialize};
 
+use super::mock_rule::MockRule;
+use super::remote_rule::RemoteRule;
+use super::response_rule::ResponseRule;
 use crate::config::misc::{add_option_vec, sanitize_option_vec_string};
 
 // Struct for command line arguments + Local Config
@@ -33,6 +36,28 @@ pub struct ProxyArgs {
     /// Relay ws connections
     #[arg(long = "no-ws", action = clap::ArgAction::SetTrue)]
     pub no_ws: Option<bool>,
+    /// Seconds after which an intercepted message is auto resolved
+    #[arg(long = "intercept-timeout")]
+    pub intercept_timeout: Option<u64>,
+    /// Drop intercepted messages on timeout instead of forwarding
+    #[arg(
+        long = "timeout-drop",
+        requires = "intercept_timeout",
+        action = clap::ArgAction::SetTrue
+    )]
+    pub timeout_drop: Option<bool>,
+    /// Mirror history to embedded database, migrates existing session
+    #[arg(long = "db", action = clap::ArgAction::SetTrue)]
+    pub db: Option<bool>,
+    /// Rules to intercept http responses, local config only
+    #[arg(skip)]
+    pub response_rules: Option<Vec<ResponseRule>>,
+    /// Rules to mock http requests, local config only
+    #[arg(skip)]
+    pub mock_rules: Option<Vec<MockRule>>,
+    /// Rules to redirect http requests, local config only
+    #[arg(skip)]
+    pub remote_rules: Option<Vec<RemoteRule>>,
 }
 
 impl ProxyArgs {
@@ -40,7 +65,9 @@ impl ProxyArgs {
      *      1. If port is 8080, remove it
      *      2. Remove empty and duplicate values from included_domains and
      *         excluded_domains
-     *      3. If all fields are empty and no_ws is false, return None
+     *      3. If intercept_timeout is 0, remove it
+     *      4. If all fields are empty and no_ws, timeout_drop, db are false,
+     *         return None
      */
 
     pub fn sanitize(mut self) -> Option<ProxyArgs> {
@@ -51,12 +78,57 @@ impl ProxyArgs {
         if self.no_ws == Some(false) {
             self.no_ws.take();
         }
+        if self.intercept_timeout == Some(0) {
+            self.intercept_timeout = None;
+        }
+        if self.timeout_drop == Some(false) {
+            self.timeout_drop.take();
+        }
+        if self.db == Some(false) {
+            self.db.take();
+        }
         sanitize_option_vec_string(&mut self.included_domains);
         sanitize_option_vec_string(&mut self.excluded_domains);
+        self.response_rules = self
+            .response_rules
+            .take()
+            .map(|rules| {
+                rules
+                    .into_iter()
+                    .filter_map(ResponseRule::sanitize)
+                    .collect::<Vec<ResponseRule>>()
+            })
+            .filter(|rules| !rules.is_empty());
+        self.mock_rules = self
+            .mock_rules
+            .take()
+            .map(|rules| {
+                rules
+                    .into_iter()
+                    .filter_map(MockRule::sanitize)
+                    .collect::<Vec<MockRule>>()
+            })
+            .filter(|rules| !rules.is_empty());
+        self.remote_rules = self
+            .remote_rules
+            .take()
+            .map(|rules| {
+                rules
+                    .into_iter()
+                    .filter_map(RemoteRule::sanitize)
+                    .collect::<Vec<RemoteRule>>()
+            })
+            .filter(|rules| !rules.is_empty());
         if self.port.is_some()
             || self.included_domains.is_some()
             || self.excluded_domains.is_some()
             || self.no_ws.is_some()
+            || self.intercept_timeout.is_some()
+            || self.timeout_drop.is_some()
+            || self.db.is_some()
+            || self.response_rules.is_some()
+            || self.mock_rules.is_some()
+            || self.remote_rules.is_some()
         {
             Some(self)
         } else {
@@ -76,6 +148,16 @@ impl Add for ProxyArgs {
             (Some(a), None) | (None, Some(a)) => Some(a),
             (None, None) => None,
         };
+        let intercept_timeout = self
+            .intercept_timeout
+            .or(rhs.intercept_timeout);
+        let timeout_drop = self.timeout_drop.or(rhs.timeout_drop);
+        let db = self.db.or(rhs.db);
+        let response_rules = self
+            .response_rules
+            .or(rhs.response_rules);
+        let mock_rules = self.mock_rules.or(rhs.mock_rules);
+        let remote_rules = self.remote_rules.or(rhs.remote_rules);
         // if both include and exclude are present include is given prefrence
         let included_domains: Option<Vec<String>> =
             if self.included_domains.is_some()
@@ -100,6 +182,12 @@ impl Add for ProxyArgs {
             included_domains,
             excluded_domains,
             no_ws,
+            intercept_timeout,
+            timeout_drop,
+            db,
+            response_rules,
+            mock_rules,
+            remote_rules,
         }
     }
 }
@@ -107,6 +195,7 @@ impl Add for ProxyArgs {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::proxy::server_info::scheme::Scheme;
 
     #[test]
     fn test_proxyargs_sanitize_none() {
@@ -164,6 +253,31 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_proxyargs_sanitize_intercept_timeout_zero() {
+        let proxy = ProxyArgs {
+            intercept_timeout: Some(0),
+            timeout_drop: Some(false),
+            ..Default::default()
+        };
+        assert!(proxy.sanitize().is_none());
+    }
+
+    #[test]
+    fn test_proxyargs_sanitize_intercept_timeout() {
+        let proxy = ProxyArgs {
+            intercept_timeout: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(
+            proxy
+                .sanitize()
+                .unwrap()
+                .intercept_timeout,
+            Some(30)
+        );
+    }
+
     // ---- Add
 
     // Port
@@ -187,6 +301,114 @@ pub mod tests {
         assert_eq!(new + old.clone(), old);
     }
 
+    // Intercept timeout
+    #[test]
+    fn test_proxyargs_add_new_intercept_timeout_old_intercept_timeout() {
+        let new = ProxyArgs {
+            intercept_timeout: Some(10),
+            ..Default::default()
+        };
+        let old = ProxyArgs {
+            intercept_timeout: Some(60),
+            timeout_drop: Some(true),
+            ..Default::default()
+        };
+        let verify = ProxyArgs {
+            intercept_timeout: Some(10),
+            timeout_drop: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(new + old, verify);
+    }
+
+    #[test]
+    fn test_proxyargs_sanitize_response_rules_empty() {
+        let proxy = ProxyArgs {
+            response_rules: Some(vec![ResponseRule::default()]),
+            ..Default::default()
+        };
+        assert!(proxy.sanitize().is_none());
+    }
+
+    #[test]
+    fn test_proxyargs_toml_response_rules() {
+        let config_toml = r#"
+            port = 8081
+
+            [[response_rules]]
+            status = "5xx"
+
+            [[response_rules]]
+            content_type = "json"
+            host = "*.example.com"
+            "#;
+        let proxy = toml::from_str::<ProxyArgs>(config_toml).unwrap();
+        let verify = ProxyArgs {
+            port: Some(8081),
+            response_rules: Some(vec![
+                ResponseRule {
+                    status: Some("5xx".to_string()),
+                    ..Default::default()
+                },
+                ResponseRule {
+                    content_type: Some("json".to_string()),
+                    host: Some("*.example.com".to_string()),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(proxy, verify);
+        assert!(toml::to_string(&proxy).is_ok());
+    }
+
+    #[test]
+    fn test_proxyargs_toml_mock_rules() {
+        let config_toml = r#"
+            [[mock_rules]]
+            method = "GET"
+            host = "api\\.example\\.com"
+            response = "/tmp/users.http"
+
+            [[mock_rules]]
+            path = "^/static/"
+            "#;
+        let proxy = toml::from_str::<ProxyArgs>(config_toml).unwrap();
+        let verify = ProxyArgs {
+            mock_rules: Some(vec![MockRule {
+                method: Some("GET".to_string()),
+                host: Some(r"api\.example\.com".to_string()),
+                response: Some("/tmp/users.http".into()),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        assert_eq!(proxy.sanitize(), Some(verify));
+    }
+
+    #[test]
+    fn test_proxyargs_toml_remote_rules() {
+        let config_toml = r#"
+            [[remote_rules]]
+            host = "api.prod.example"
+            to_host = "localhost:8443"
+            to_scheme = "http"
+            rewrite_host = true
+            "#;
+        let proxy = toml::from_str::<ProxyArgs>(config_toml).unwrap();
+        let verify = ProxyArgs {
+            remote_rules: Some(vec![RemoteRule {
+                host: Some("api.prod.example".to_string()),
+                to_host: Some("localhost:8443".to_string()),
+                to_scheme: Some(Scheme::Http),
+                rewrite_host: Some(true),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        assert_eq!(proxy, verify);
+    }
+
     // Include domains
     #[test]
     fn test_proxyargs_add_new_included_domains_old_no_included_domains() {
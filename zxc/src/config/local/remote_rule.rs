@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use crate::proxy::server_info::scheme::Scheme;
+
+/* Description:
+ *      Rule to redirect http request to another server. Set in local config
+ *      only. All the given match fields should match for the rule to match.
+ *
+ * Format:
+ *      [[remote_rules]]
+ *      host = "api.prod.example"       // host or wildcard
+ *      path = "/v2/"                   // path prefix
+ *      to_host = "localhost:8443"      // new host[:port]
+ *      to_scheme = "https"             // new scheme, http | https
+ *      to_path = "/v3/"                // replaces the matched path prefix
+ *      rewrite_host = true             // rewrite Host header and sni
+ */
+
+#[cfg_attr(test, derive(PartialEq, Clone))]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RemoteRule {
+    pub host: Option<String>,
+    pub path: Option<String>,
+    pub to_host: Option<String>,
+    pub to_scheme: Option<Scheme>,
+    pub to_path: Option<String>,
+    pub rewrite_host: Option<bool>,
+}
+
+impl RemoteRule {
+    /* Steps:
+     *      1. Remove empty values and false rewrite_host
+     *      2. If to_host, to_scheme and to_path are empty, return None
+     */
+
+    pub fn sanitize(mut self) -> Option<RemoteRule> {
+        for field in [
+            &mut self.host,
+            &mut self.path,
+            &mut self.to_host,
+            &mut self.to_path,
+        ] {
+            if field
+                .as_ref()
+                .is_some_and(|val| val.is_empty())
+            {
+                field.take();
+            }
+        }
+        if self.rewrite_host == Some(false) {
+            self.rewrite_host.take();
+        }
+        if self.to_host.is_some()
+            || self.to_scheme.is_some()
+            || self.to_path.is_some()
+        {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_rule_sanitize_no_target() {
+        let rule = RemoteRule {
+            host: Some("example.com".to_string()),
+            to_host: Some("".to_string()),
+            rewrite_host: Some(true),
+            ..Default::default()
+        };
+        assert!(rule.sanitize().is_none());
+    }
+
+    #[test]
+    fn test_remote_rule_sanitize() {
+        let rule = RemoteRule {
+            host: Some("".to_string()),
+            path: Some("/v2/".to_string()),
+            to_path: Some("/v3/".to_string()),
+            rewrite_host: Some(false),
+            ..Default::default()
+        };
+        let verify = RemoteRule {
+            path: Some("/v2/".to_string()),
+            to_path: Some("/v3/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(rule.sanitize(), Some(verify));
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+/* Description:
+ *      Rule to intercept http response. Set in local config only.
+ *      All the given fields should match for the rule to match.
+ *
+ * Format:
+ *      [[response_rules]]
+ *      status = "5xx"                  // status code, x matches any digit
+ *      content_type = "json"           // part of Content-Type header value
+ *      host = "*.example.com"          // host or wildcard
+ */
+
+#[cfg_attr(test, derive(PartialEq, Clone))]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ResponseRule {
+    pub status: Option<String>,
+    pub content_type: Option<String>,
+    pub host: Option<String>,
+}
+
+impl ResponseRule {
+    /* Steps:
+     *      1. Remove empty values
+     *      2. If all fields are empty, return None
+     */
+
+    pub fn sanitize(mut self) -> Option<ResponseRule> {
+        for field in [&mut self.status, &mut self.content_type, &mut self.host]
+        {
+            if field
+                .as_ref()
+                .is_some_and(|val| val.is_empty())
+            {
+                field.take();
+            }
+        }
+        if self.status.is_some()
+            || self.content_type.is_some()
+            || self.host.is_some()
+        {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_rule_sanitize_empty() {
+        let rule = ResponseRule {
+            status: Some("".to_string()),
+            ..Default::default()
+        };
+        assert!(rule.sanitize().is_none());
+    }
+
+    #[test]
+    fn test_response_rule_sanitize() {
+        let rule = ResponseRule {
+            status: Some("5xx".to_string()),
+            host: Some("".to_string()),
+            ..Default::default()
+        };
+        let verify = ResponseRule {
+            status: Some("5xx".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(rule.sanitize(), Some(verify));
+    }
+}
@@ -8,7 +8,7 @@ mod windows;
 pub use global::GlobalConfig;
 pub use global::addons::Addon;
 pub use local::CliArgs;
-pub use parsed_config::Config;
+pub use parsed_config::{Config, DomainFilter, Mock, Remap};
 pub use windows::*;
 
 const CONFIG_FILE_NAME: &str = "config.toml";
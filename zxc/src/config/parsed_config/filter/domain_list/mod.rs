@@ -4,6 +4,7 @@ mod from_vec_string;
 use std::fmt::Debug;
 
 // Struct which contains domain names as list of string and wildcards
+#[derive(Clone)]
 pub struct DomainList {
     str: Option<Vec<String>>,
     wildcard: Option<Vec<Wildcard<'static>>>,
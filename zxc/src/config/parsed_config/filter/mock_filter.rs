@@ -0,0 +1,263 @@
+use std::path::{Component, Path, PathBuf};
+
+use regex::Regex;
+use tracing::error;
+
+use crate::config::local::mock_rule::MockRule;
+
+// What to respond with for a mocked request
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub enum Mock {
+    // File containing raw http response
+    Response(PathBuf),
+    // File to be served from local directory
+    Local(PathBuf),
+}
+
+#[cfg_attr(any(test, debug_assertions), derive(PartialEq))]
+#[derive(Debug)]
+enum MockTarget {
+    Response(PathBuf),
+    Local(PathBuf),
+}
+
+// Parsed MockRule
+#[derive(Debug)]
+struct MockFilterRule {
+    method: Option<String>,
+    host: Option<Regex>,
+    path: Option<Regex>,
+    target: MockTarget,
+}
+
+// Compile regex of a rule field
+fn compile(field: Option<String>) -> Result<Option<Regex>, regex::Error> {
+    field
+        .map(|val| Regex::new(&val))
+        .transpose()
+}
+
+impl TryFrom<MockRule> for MockFilterRule {
+    type Error = regex::Error;
+
+    fn try_from(rule: MockRule) -> Result<Self, Self::Error> {
+        let target = match (rule.response, rule.local) {
+            (Some(response), _) => MockTarget::Response(response),
+            (None, Some(local)) => MockTarget::Local(local),
+            (None, None) => unreachable!("sanitized mock rule"),
+        };
+        Ok(Self {
+            method: rule.method,
+            host: compile(rule.host)?,
+            path: compile(rule.path)?,
+            target,
+        })
+    }
+}
+
+/* Description:
+ *      Resolve the request path relative to the local directory.
+ *
+ * Steps:
+ *      1. Remove query and fragment from path
+ *      2. Push only normal components to the directory, so that the
+ *         resolved path never leaves the directory.
+ *      3. If path ends with '/' or is empty, use index.html
+ */
+
+fn local_path(dir: &Path, path: &str) -> PathBuf {
+    let path = path
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default();
+    let mut result = dir.to_path_buf();
+    for component in Path::new(path).components() {
+        if let Component::Normal(val) = component {
+            result.push(val);
+        }
+    }
+    if path.ends_with('/') || result == dir {
+        result.push("index.html");
+    }
+    result
+}
+
+impl MockFilterRule {
+    fn matches(&self, method: &str, host: &str, path: &str) -> bool {
+        self.method
+            .as_ref()
+            .is_none_or(|val| val.eq_ignore_ascii_case(method))
+            && self
+                .host
+                .as_ref()
+                .is_none_or(|re| re.is_match(host))
+            && self
+                .path
+                .as_ref()
+                .is_none_or(|re| re.is_match(path))
+    }
+
+    fn mock(&self, path: &str) -> Mock {
+        match &self.target {
+            MockTarget::Response(file) => Mock::Response(file.clone()),
+            MockTarget::Local(dir) => Mock::Local(local_path(dir, path)),
+        }
+    }
+}
+
+// List of rules to mock http requests
+#[cfg_attr(any(test, debug_assertions), derive(PartialEq))]
+#[derive(Debug)]
+pub struct MockFilter(Vec<MockFilterRule>);
+
+impl From<Vec<MockRule>> for MockFilter {
+    fn from(rules: Vec<MockRule>) -> Self {
+        Self(
+            rules
+                .into_iter()
+                .filter_map(|rule| {
+                    MockFilterRule::try_from(rule)
+                        .inspect_err(|e| error!("mock rule| {}", e))
+                        .ok()
+                })
+                .collect(),
+        )
+    }
+}
+
+impl MockFilter {
+    // First rule that matches
+    pub fn mock(&self, method: &str, host: &str, path: &str) -> Option<Mock> {
+        self.0
+            .iter()
+            .find(|rule| rule.matches(method, host, path))
+            .map(|rule| rule.mock(path))
+    }
+
+    /* Description:
+     *      Check if any rule with a host regex matches the host. Used to
+     *      serve CONNECT tunnels without connecting to the server. Rules
+     *      without host are not considered, so that every tunnel is not
+     *      served without the server.
+     */
+
+    pub fn matches_host(&self, host: &str) -> bool {
+        self.0.iter().any(|rule| {
+            rule.host
+                .as_ref()
+                .is_some_and(|re| re.is_match(host))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// Regex does not implement PartialEq, compare the source
+#[cfg(any(test, debug_assertions))]
+impl PartialEq for MockFilterRule {
+    fn eq(&self, other: &Self) -> bool {
+        fn source(re: &Option<Regex>) -> Option<&str> {
+            re.as_ref().map(Regex::as_str)
+        }
+        self.method == other.method
+            && source(&self.host) == source(&other.host)
+            && source(&self.path) == source(&other.path)
+            && self.target == other.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_filter() -> MockFilter {
+        MockFilter::from(vec![
+            MockRule {
+                method: Some("post".to_string()),
+                host: Some(r"^api\.example\.com$".to_string()),
+                path: Some("^/v1/".to_string()),
+                response: Some(PathBuf::from("/tmp/mock.http")),
+                ..Default::default()
+            },
+            MockRule {
+                host: Some(r"static\.example\.com".to_string()),
+                local: Some(PathBuf::from("/srv/static")),
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn test_mock_filter_matches_host() {
+        let filter = build_filter();
+        assert!(filter.matches_host("static.example.com"));
+        assert!(filter.matches_host("api.example.com"));
+        assert!(!filter.matches_host("example.com"));
+        let filter = MockFilter::from(vec![MockRule {
+            path: Some("^/".to_string()),
+            local: Some(PathBuf::from("/srv")),
+            ..Default::default()
+        }]);
+        assert!(!filter.matches_host("example.com"));
+    }
+
+    #[test]
+    fn test_mock_filter_invalid_regex() {
+        let filter = MockFilter::from(vec![MockRule {
+            host: Some("(".to_string()),
+            local: Some(PathBuf::from("/srv")),
+            ..Default::default()
+        }]);
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_mock_filter_response() {
+        let filter = build_filter();
+        assert_eq!(
+            filter.mock("POST", "api.example.com", "/v1/users"),
+            Some(Mock::Response(PathBuf::from("/tmp/mock.http")))
+        );
+        assert!(
+            filter
+                .mock("GET", "api.example.com", "/v1/users")
+                .is_none()
+        );
+        assert!(
+            filter
+                .mock("POST", "api.example.com", "/v2/users")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_mock_filter_local() {
+        let filter = build_filter();
+        assert_eq!(
+            filter.mock("GET", "static.example.com", "/js/app.js?v=1"),
+            Some(Mock::Local(PathBuf::from("/srv/static/js/app.js")))
+        );
+    }
+
+    #[test]
+    fn test_local_path_index() {
+        let dir = Path::new("/srv");
+        assert_eq!(local_path(dir, "/"), PathBuf::from("/srv/index.html"));
+        assert_eq!(
+            local_path(dir, "/docs/"),
+            PathBuf::from("/srv/docs/index.html")
+        );
+    }
+
+    #[test]
+    fn test_local_path_traversal() {
+        let dir = Path::new("/srv");
+        assert_eq!(
+            local_path(dir, "/../../etc/passwd"),
+            PathBuf::from("/srv/etc/passwd")
+        );
+    }
+}
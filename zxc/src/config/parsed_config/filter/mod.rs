@@ -1,9 +1,12 @@
 pub mod domain_list;
+pub mod mock_filter;
+pub mod remote_filter;
+pub mod response_filter;
 use domain_list::DomainList;
 
 // Enum which contains the included/exluded DomainList
 #[cfg_attr(any(test, debug_assertions), derive(PartialEq))]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum DomainFilter {
     Include(DomainList),
     Exclude(DomainList),
@@ -0,0 +1,170 @@
+use super::domain_list::DomainList;
+use crate::config::local::remote_rule::RemoteRule;
+use crate::proxy::server_info::scheme::Scheme;
+
+// New target for a request matching a remote rule
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug)]
+pub struct Remap {
+    pub scheme: Scheme,
+    pub host: String,
+    pub uri: Option<String>,
+    pub rewrite_host: bool,
+}
+
+// Parsed RemoteRule
+#[cfg_attr(any(test, debug_assertions), derive(PartialEq))]
+#[derive(Debug)]
+struct RemoteFilterRule {
+    host: Option<DomainList>,
+    path: Option<String>,
+    to_host: Option<String>,
+    to_scheme: Option<Scheme>,
+    to_path: Option<String>,
+    rewrite_host: bool,
+}
+
+impl From<RemoteRule> for RemoteFilterRule {
+    fn from(rule: RemoteRule) -> Self {
+        Self {
+            host: rule
+                .host
+                .map(|host| DomainList::from(vec![host])),
+            path: rule.path,
+            to_host: rule.to_host,
+            to_scheme: rule.to_scheme,
+            to_path: rule.to_path,
+            rewrite_host: rule.rewrite_host.unwrap_or(false),
+        }
+    }
+}
+
+impl RemoteFilterRule {
+    /* Steps:
+     *      All the given fields should match
+     *      1. host => DomainList contains host
+     *      2. path => uri starts with path
+     */
+
+    fn matches(&self, host: &String, uri: &str) -> bool {
+        self.host
+            .as_ref()
+            .is_none_or(|list| list.contains(host))
+            && self
+                .path
+                .as_ref()
+                .is_none_or(|prefix| uri.starts_with(prefix.as_str()))
+    }
+
+    /* Steps:
+     *      1. Use to_scheme and to_host if present, else the original.
+     *      2. If to_path is present, replace the matched path prefix with
+     *         to_path.
+     */
+
+    fn remap(&self, scheme: Scheme, host: &str, uri: &str) -> Remap {
+        let uri = self.to_path.as_ref().map(|to_path| {
+            let rest = self
+                .path
+                .as_ref()
+                .map_or(uri, |prefix| &uri[prefix.len()..]);
+            format!("{}{}", to_path, rest)
+        });
+        Remap {
+            scheme: self.to_scheme.unwrap_or(scheme),
+            host: self
+                .to_host
+                .clone()
+                .unwrap_or_else(|| host.to_string()),
+            uri,
+            rewrite_host: self.rewrite_host,
+        }
+    }
+}
+
+// List of rules to redirect http requests
+#[cfg_attr(any(test, debug_assertions), derive(PartialEq))]
+#[derive(Debug)]
+pub struct RemoteFilter(Vec<RemoteFilterRule>);
+
+impl From<Vec<RemoteRule>> for RemoteFilter {
+    fn from(rules: Vec<RemoteRule>) -> Self {
+        Self(
+            rules
+                .into_iter()
+                .map(RemoteFilterRule::from)
+                .collect(),
+        )
+    }
+}
+
+impl RemoteFilter {
+    // First rule that matches
+    pub fn remap(
+        &self,
+        scheme: Scheme,
+        host: &String,
+        uri: &str,
+    ) -> Option<Remap> {
+        self.0
+            .iter()
+            .find(|rule| rule.matches(host, uri))
+            .map(|rule| rule.remap(scheme, host, uri))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_filter() -> RemoteFilter {
+        RemoteFilter::from(vec![
+            RemoteRule {
+                host: Some("api.prod.example".to_string()),
+                to_host: Some("localhost:8443".to_string()),
+                rewrite_host: Some(true),
+                ..Default::default()
+            },
+            RemoteRule {
+                path: Some("/v2/".to_string()),
+                to_scheme: Some(Scheme::Http),
+                to_path: Some("/v3/".to_string()),
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn test_remote_filter_host() {
+        let filter = build_filter();
+        let host = "api.prod.example".to_string();
+        let verify = Remap {
+            scheme: Scheme::Https,
+            host: "localhost:8443".to_string(),
+            uri: None,
+            rewrite_host: true,
+        };
+        assert_eq!(filter.remap(Scheme::Https, &host, "/users"), Some(verify));
+    }
+
+    #[test]
+    fn test_remote_filter_path() {
+        let filter = build_filter();
+        let host = "www.example.com".to_string();
+        let verify = Remap {
+            scheme: Scheme::Http,
+            host: "www.example.com".to_string(),
+            uri: Some("/v3/users?id=1".to_string()),
+            rewrite_host: false,
+        };
+        assert_eq!(
+            filter.remap(Scheme::Https, &host, "/v2/users?id=1"),
+            Some(verify)
+        );
+        assert!(
+            filter
+                .remap(Scheme::Https, &host, "/v1/users")
+                .is_none()
+        );
+    }
+}
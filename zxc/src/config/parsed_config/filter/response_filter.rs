@@ -0,0 +1,150 @@
+use super::domain_list::DomainList;
+use crate::config::local::response_rule::ResponseRule;
+
+// Parsed ResponseRule
+#[cfg_attr(any(test, debug_assertions), derive(PartialEq))]
+#[derive(Debug)]
+struct ResponseFilterRule {
+    status: Option<String>,
+    content_type: Option<String>,
+    host: Option<DomainList>,
+}
+
+impl From<ResponseRule> for ResponseFilterRule {
+    fn from(rule: ResponseRule) -> Self {
+        Self {
+            status: rule.status,
+            content_type: rule
+                .content_type
+                .map(|ct| ct.to_ascii_lowercase()),
+            host: rule
+                .host
+                .map(|host| DomainList::from(vec![host])),
+        }
+    }
+}
+
+/* Description:
+ *      Check if status code matches the pattern. x in pattern matches any
+ *      digit.
+ *
+ *      5xx matches 500, 503
+ *      404 matches 404
+ */
+
+fn status_matches(pattern: &str, status: &str) -> bool {
+    pattern.len() == status.len()
+        && pattern
+            .bytes()
+            .zip(status.bytes())
+            .all(|(p, s)| p.eq_ignore_ascii_case(&b'x') || p == s)
+}
+
+impl ResponseFilterRule {
+    /* Steps:
+     *      All the given fields should match
+     *      1. status       => status_matches()
+     *      2. content_type => Content-Type header value contains content_type
+     *      3. host         => DomainList contains host
+     */
+
+    fn matches(
+        &self,
+        host: &str,
+        status: &str,
+        content_type: Option<&str>,
+    ) -> bool {
+        self.status
+            .as_ref()
+            .is_none_or(|pattern| status_matches(pattern, status))
+            && self
+                .content_type
+                .as_ref()
+                .is_none_or(|ct| {
+                    content_type.is_some_and(|value| {
+                        value
+                            .to_ascii_lowercase()
+                            .contains(ct.as_str())
+                    })
+                })
+            && self
+                .host
+                .as_ref()
+                .is_none_or(|list| list.contains(&host.to_string()))
+    }
+}
+
+// List of rules to intercept http response
+#[cfg_attr(any(test, debug_assertions), derive(PartialEq))]
+#[derive(Debug)]
+pub struct ResponseFilter(Vec<ResponseFilterRule>);
+
+impl From<Vec<ResponseRule>> for ResponseFilter {
+    fn from(rules: Vec<ResponseRule>) -> Self {
+        Self(
+            rules
+                .into_iter()
+                .map(ResponseFilterRule::from)
+                .collect(),
+        )
+    }
+}
+
+impl ResponseFilter {
+    // Any of the rule matches
+    pub fn matches(
+        &self,
+        host: &str,
+        status: &str,
+        content_type: Option<&str>,
+    ) -> bool {
+        self.0
+            .iter()
+            .any(|rule| rule.matches(host, status, content_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_filter() -> ResponseFilter {
+        ResponseFilter::from(vec![
+            ResponseRule {
+                status: Some("5xx".to_string()),
+                ..Default::default()
+            },
+            ResponseRule {
+                content_type: Some("JSON".to_string()),
+                host: Some("*.example.com".to_string()),
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn test_status_matches() {
+        assert!(status_matches("5xx", "503"));
+        assert!(status_matches("404", "404"));
+        assert!(status_matches("4X4", "404"));
+        assert!(!status_matches("5xx", "404"));
+        assert!(!status_matches("5x", "500"));
+    }
+
+    #[test]
+    fn test_response_filter_status() {
+        let filter = build_filter();
+        let host = "www.google.com".to_string();
+        assert!(filter.matches(&host, "500", None));
+        assert!(!filter.matches(&host, "200", Some("application/json")));
+    }
+
+    #[test]
+    fn test_response_filter_content_type_host() {
+        let filter = build_filter();
+        let host = "api.example.com".to_string();
+        assert!(filter.matches(&host, "200", Some("application/json")));
+        assert!(!filter.matches(&host, "200", Some("text/html")));
+        assert!(!filter.matches(&host, "200", None));
+    }
+}
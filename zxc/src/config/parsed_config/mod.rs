@@ -1,6 +1,13 @@
 mod filter;
-use filter::DomainFilter;
+use std::time::Duration;
+
+pub use filter::DomainFilter;
 use filter::domain_list::DomainList;
+pub use filter::mock_filter::Mock;
+use filter::mock_filter::MockFilter;
+pub use filter::remote_filter::Remap;
+use filter::remote_filter::RemoteFilter;
+use filter::response_filter::ResponseFilter;
 use mime::ContentType;
 use mime::from_extension::EXTENSION_MAP;
 use tracing::trace;
@@ -8,6 +15,7 @@ use tracing::trace;
 use super::GlobalConfig;
 use super::local::proxy::ProxyArgs;
 use crate::config::misc::{add_option_vec, sanitize_option_vec_string};
+use crate::proxy::server_info::scheme::Scheme;
 
 // Configuration Struct which holds the config informartion
 #[cfg_attr(any(test, debug_assertions), derive(PartialEq))]
@@ -17,6 +25,11 @@ pub struct Config {
     excluded_content_types: Option<Vec<ContentType>>,
     excluded_extensions: Option<Vec<String>>,
     with_ws: bool,
+    intercept_timeout: Option<Duration>,
+    drop_on_timeout: bool,
+    response_filter: Option<ResponseFilter>,
+    mock_filter: Option<MockFilter>,
+    remote_filter: Option<RemoteFilter>,
 }
 
 impl Config {
@@ -33,15 +46,18 @@ impl Config {
      *            0 1 0
      *            1 1 0
      *
-     *      3. Get filter_domains from local config and global config
+     *      3. Get intercept_timeout, timeout_drop, response_rules,
+     *         mock_rules and remote_rules from local config
+     *
+     *      4. Get filter_domains from local config and global config
      *      by calling combine_filter
      *
-     *      4. Sanitize, if any field is Some and with_ws is false
+     *      5. Sanitize, if any field is Some and with_ws is false
      *      return Some(Config) else return None
      */
 
     pub fn build(
-        local_config: Option<ProxyArgs>,
+        mut local_config: Option<ProxyArgs>,
         mut global_config: Option<GlobalConfig>,
     ) -> Option<Config> {
         // 1. Exclude content types and extensions
@@ -63,7 +79,33 @@ impl Config {
 
         let with_ws = global_ws && !local_ws;
 
-        // 5. Combine local and global config
+        // 3. Intercept timeout
+        let intercept_timeout = local_config
+            .as_ref()
+            .and_then(|lc| lc.intercept_timeout)
+            .map(Duration::from_secs);
+
+        let drop_on_timeout = local_config
+            .as_ref()
+            .is_some_and(|lc| lc.timeout_drop.unwrap_or(false));
+
+        let response_filter = local_config
+            .as_mut()
+            .and_then(|lc| lc.response_rules.take())
+            .map(ResponseFilter::from);
+
+        let mock_filter = local_config
+            .as_mut()
+            .and_then(|lc| lc.mock_rules.take())
+            .map(MockFilter::from)
+            .filter(|filter| !filter.is_empty());
+
+        let remote_filter = local_config
+            .as_mut()
+            .and_then(|lc| lc.remote_rules.take())
+            .map(RemoteFilter::from);
+
+        // 4. Combine local and global config
         let filter_domains = Self::combine_filter(local_config, global_config);
 
         // sanitize
@@ -71,12 +113,21 @@ impl Config {
             || filter_domains.is_some()
             || excluded_content_types.is_some()
             || excluded_extensions.is_some()
+            || intercept_timeout.is_some()
+            || response_filter.is_some()
+            || mock_filter.is_some()
+            || remote_filter.is_some()
         {
             Some(Config {
                 filter_domains,
                 excluded_content_types,
                 excluded_extensions,
                 with_ws,
+                intercept_timeout,
+                drop_on_timeout,
+                response_filter,
+                mock_filter,
+                remote_filter,
             })
         } else {
             None
@@ -170,6 +221,10 @@ impl Config {
             .is_none_or(|filter| filter.contains(host))
     }
 
+    pub fn domain_filter(&self) -> Option<DomainFilter> {
+        self.filter_domains.clone()
+    }
+
     /* Description:
      *      Checks if given extension should be logged.
      *
@@ -220,6 +275,56 @@ impl Config {
     pub fn with_ws(&self) -> bool {
         self.with_ws
     }
+
+    pub fn intercept_timeout(&self) -> Option<Duration> {
+        self.intercept_timeout
+    }
+
+    pub fn drop_on_timeout(&self) -> bool {
+        self.drop_on_timeout
+    }
+
+    pub fn has_response_rules(&self) -> bool {
+        self.response_filter.is_some()
+    }
+
+    // Check if http response matches any of the response rules
+    pub fn should_intercept_response(
+        &self,
+        host: &str,
+        status: &str,
+        content_type: Option<&str>,
+    ) -> bool {
+        self.response_filter
+            .as_ref()
+            .is_some_and(|filter| filter.matches(host, status, content_type))
+    }
+
+    // Check if http request matches any of the mock rules
+    pub fn mock(&self, method: &str, host: &str, path: &str) -> Option<Mock> {
+        self.mock_filter
+            .as_ref()
+            .and_then(|filter| filter.mock(method, host, path))
+    }
+
+    // Check if host matches the host of any mock rule
+    pub fn mock_host(&self, host: &str) -> bool {
+        self.mock_filter
+            .as_ref()
+            .is_some_and(|filter| filter.matches_host(host))
+    }
+
+    // Check if http request matches any of the remote rules
+    pub fn remap(
+        &self,
+        scheme: Scheme,
+        host: &String,
+        uri: &str,
+    ) -> Option<Remap> {
+        self.remote_filter
+            .as_ref()
+            .and_then(|filter| filter.remap(scheme, host, uri))
+    }
 }
 
 #[cfg(test)]
@@ -241,6 +346,7 @@ mod tests {
             ]),
             with_ws: None,
             addons: None,
+            secrets: None,
         });
 
         let config = Config::build(None, global_config);
@@ -258,6 +364,11 @@ mod tests {
                 "png".to_string(),
             ]),
             with_ws: false,
+            intercept_timeout: None,
+            drop_on_timeout: false,
+            response_filter: None,
+            mock_filter: None,
+            remote_filter: None,
         };
 
         assert_eq!(config.unwrap(), verify);
@@ -270,6 +381,7 @@ mod tests {
             included_domains: Some(vec!["*.google.com".to_string()]),
             excluded_domains: None,
             no_ws: None,
+            ..Default::default()
         });
 
         let config = Config::build(local_config, None);
@@ -280,6 +392,11 @@ mod tests {
             excluded_content_types: None,
             excluded_extensions: None,
             with_ws: false,
+            intercept_timeout: None,
+            drop_on_timeout: false,
+            response_filter: None,
+            mock_filter: None,
+            remote_filter: None,
         };
 
         assert_eq!(config.unwrap(), verify);
@@ -303,6 +420,7 @@ mod tests {
             excluded_extensions: None,
             with_ws: None,
             addons: None,
+            secrets: None,
         });
         let filter = Config::combine_filter(local_config, global_config);
         assert_eq!(
@@ -319,6 +437,7 @@ mod tests {
             excluded_domains: Some(elist.clone()),
             port: Some(8080),
             no_ws: None,
+            ..Default::default()
         });
         let global_config: Option<GlobalConfig> = None;
         let filter = Config::combine_filter(local_config, global_config);
@@ -336,6 +455,7 @@ mod tests {
             excluded_domains: None,
             port: Some(8080),
             no_ws: None,
+            ..Default::default()
         });
         let global_config: Option<GlobalConfig> = None;
         let filter = Config::combine_filter(local_config, global_config);
@@ -354,6 +474,7 @@ mod tests {
             excluded_domains: Some(elist.clone()),
             port: Some(8080),
             no_ws: None,
+            ..Default::default()
         });
         let global_config: Option<GlobalConfig> = None;
         let filter = Config::combine_filter(local_config, global_config);
@@ -372,6 +493,7 @@ mod tests {
             excluded_domains: Some(elist.clone()),
             port: Some(8080),
             no_ws: None,
+            ..Default::default()
         });
         let global_config: Option<GlobalConfig> = Some(GlobalConfig {
             excluded_domains: Some(elist.clone()),
@@ -379,6 +501,7 @@ mod tests {
             excluded_extensions: None,
             with_ws: None,
             addons: None,
+            secrets: None,
         });
         let filter = Config::combine_filter(local_config, global_config);
         assert_eq!(
@@ -395,6 +518,7 @@ mod tests {
             excluded_domains: Some(elist.clone()),
             port: Some(8080),
             no_ws: None,
+            ..Default::default()
         });
         let gelist =
             vec!["*.youtube.com".to_string(), "reddit.com".to_string()];
@@ -404,6 +528,7 @@ mod tests {
             excluded_extensions: None,
             with_ws: None,
             addons: None,
+            secrets: None,
         });
         let filter = Config::combine_filter(local_config, global_config);
         assert_eq!(
@@ -427,6 +552,11 @@ mod tests {
             excluded_content_types: Some(excluded_content_types),
             excluded_extensions: Some(excluded_extensions),
             with_ws: false,
+            intercept_timeout: None,
+            drop_on_timeout: false,
+            response_filter: None,
+            mock_filter: None,
+            remote_filter: None,
         };
 
         assert!(config.should_log("html".to_string()));
@@ -6,7 +6,7 @@ pub const EXT_RES: &str = "res";
 pub const EXT_WREQ: &str = "wreq";
 pub const EXT_WRES: &str = "wres";
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum FileType {
     Req,
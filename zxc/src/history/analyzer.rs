@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::mpsc::{Sender, UnboundedReceiver};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, trace};
+
+use super::http::{HttpMessage, header_value};
+use super::message::from_commander::CommanderToHistory;
+use super::passive::{Passive, findings_line};
+use super::sitemap::extract_links;
+use super::sitemap::links::Links;
+use crate::builder::HISTORY;
+use crate::jar::{SharedJar, update_from_parsed};
+use crate::proxy::handler_state::handlers::get_attr;
+use crate::proxy::server_info::scheme::Scheme;
+
+// Messages to the analyzer
+#[derive(Debug)]
+pub enum ToAnalyzer {
+    // From Proxy - logged response of id
+    Response(usize),
+    // From Commander - secret patterns of config, on reload
+    Secrets(HashMap<String, String>),
+}
+
+#[derive(Debug, Error)]
+pub enum AnalyzerError {
+    #[error("io| {0}")]
+    Io(#[from] io::Error),
+    #[error("history send| {0}")]
+    HistorySend(#[from] SendError<CommanderToHistory>),
+}
+
+/* Description:
+ *      Analysis of the logged http responses in its own task, off the proxy
+ *      flow and the history task.
+ *
+ *      Responses are queued in an unbounded channel, so that the proxy never
+ *      waits and no response is skipped. Request and response files are
+ *      read and parsed once, to update the cookie jar, to extract the links
+ *      for the sitemap and to run the passive checks [ Passive ]. Only the
+ *      discovered links and the findings line are sent to the history task.
+ */
+
+pub struct Analyzer {
+    root: PathBuf,
+    from_proxy: UnboundedReceiver<ToAnalyzer>,
+    to_history: Sender<CommanderToHistory>,
+    links: Links,
+    passive: Passive,
+    jar: SharedJar,
+}
+
+impl Analyzer {
+    pub fn new(
+        from_proxy: UnboundedReceiver<ToAnalyzer>,
+        to_history: Sender<CommanderToHistory>,
+        passive: Passive,
+        jar: SharedJar,
+    ) -> Self {
+        Self {
+            root: PathBuf::from(HISTORY),
+            from_proxy,
+            to_history,
+            links: Links::default(),
+            passive,
+            jar,
+        }
+    }
+
+    /* Steps:
+     *      1. Read and parse the request and response files of id.
+     *
+     *      2. Get scheme, host and tls from xattrs of request, else http and
+     *         host from Host header.
+     *
+     *      3. Update cookie jar from Set-Cookie of the response
+     *         [ update_from_parsed() ].
+     *
+     *      4. Extract links [ extract_links() ], if any send
+     *         CommanderToHistory::Links to history.
+     *
+     *      5. Run passive analysis [ Passive::analyze() ], if new findings
+     *         send CommanderToHistory::Findings to history.
+     *
+     * Error:
+     *      AnalyzerError::Io           [1] [5]
+     *      AnalyzerError::HistorySend  [4] [5]
+     */
+
+    async fn analyze(&mut self, id: usize) -> Result<(), AnalyzerError> {
+        // 1. Read
+        let dir = self.root.join(id.to_string());
+        let req_path = dir.join(format!("{id}.req"));
+        let req = HttpMessage::parse(&tokio::fs::read(&req_path).await?);
+        let res = HttpMessage::parse(
+            &tokio::fs::read(dir.join(format!("{id}.res"))).await?,
+        );
+
+        // 2. Server info
+        let (scheme, host, tls) = match get_attr(&req_path) {
+            Ok(info) if info.is_tls() => (Scheme::Https, info.host, true),
+            Ok(info) => (Scheme::Http, info.host, false),
+            Err(_) => (
+                Scheme::Http,
+                header_value(&req.headers, "host")
+                    .unwrap_or_default()
+                    .to_string(),
+                false,
+            ),
+        };
+
+        // 3. Jar
+        update_from_parsed(&self.jar, &host, &req, &res).await;
+
+        // 4. Links
+        let links =
+            extract_links(&self.links, &scheme.to_string(), &host, &req, &res);
+        if !links.is_empty() {
+            self.to_history
+                .send(CommanderToHistory::Links(host.clone(), links))
+                .await?;
+        }
+
+        // 5. Passive
+        let findings = self
+            .passive
+            .analyze(id, (&host, tls), &req, &res)
+            .await?;
+        if !findings.is_empty() {
+            self.to_history
+                .send(CommanderToHistory::Findings(findings_line(&findings)))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/* Steps:
+ *      Select
+ *          from_proxy  => ToAnalyzer::Response, analyze the response
+ *                         ToAnalyzer::Secrets, replace secret patterns
+ *                         None, return
+ *          Token       => return
+ */
+
+pub async fn run_analyzer(mut analyzer: Analyzer, token: CancellationToken) {
+    debug!("[+] analyzer started");
+    loop {
+        tokio::select! {
+            msg = analyzer.from_proxy.recv() => {
+                match msg {
+                    Some(ToAnalyzer::Response(id)) => {
+                        if let Err(e) = analyzer.analyze(id).await {
+                            error!("analyze| {}| {}", id, e);
+                        }
+                    }
+                    Some(ToAnalyzer::Secrets(patterns)) => {
+                        analyzer.passive.set_secrets(patterns)
+                    }
+                    None => return,
+                }
+            }
+            _ = token.cancelled() => {
+                trace!("cancelled");
+                return
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tokio::sync::mpsc::{channel, unbounded_channel};
+
+    use super::*;
+    use crate::jar::CookieJar;
+
+    const REQ: &str = "GET /app.js HTTP/1.1\r\nHost: a.com\r\n\r\n";
+    const RES: &str = "HTTP/1.1 200 OK\r\nServer: Apache/2.4.1\r\nSet-Cookie: sid=1\r\nContent-Type: application/javascript\r\n\r\nfetch('/api/admin')";
+
+    #[tokio::test]
+    async fn test_analyzer_analyze() {
+        let root = PathBuf::from("/tmp/zxc_analyzer");
+        let _ = fs::remove_dir_all(&root);
+        let dir = root.join("1");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("1.req"), REQ).unwrap();
+        fs::write(dir.join("1.res"), RES).unwrap();
+        let (_, from_proxy) = unbounded_channel();
+        let (to_history, mut from_analyzer) = channel(10);
+        let passive = Passive::new(root.join("findings.jsonl"));
+        let jar = CookieJar::new(root.join("cookies.json")).shared();
+        let mut analyzer =
+            Analyzer::new(from_proxy, to_history, passive, jar.clone());
+        analyzer.root = root.clone();
+        analyzer.analyze(1).await.unwrap();
+
+        let Some(CommanderToHistory::Links(host, links)) =
+            from_analyzer.recv().await
+        else {
+            panic!("links");
+        };
+        assert_eq!(host, "a.com");
+        assert_eq!(links[0].uri, "/api/admin");
+        assert!(matches!(
+            from_analyzer.recv().await,
+            Some(CommanderToHistory::Findings(line)) if line.contains("server_banner")
+        ));
+        assert_eq!(jar.lock().unwrap().cookies()[0].name, "sid");
+        // missing files
+        assert!(analyzer.analyze(2).await.is_err());
+        fs::remove_dir_all(root).unwrap();
+    }
+}
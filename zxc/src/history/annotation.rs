@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+pub const HISTORY_ANNOTATIONS: &str = "./annotations.json";
+
+// Highlight colours supported by history ui
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Cyan,
+    Blue,
+    Magenta,
+    Gray,
+}
+
+// Note, tags and colour of a log id
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Annotation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+}
+
+impl Annotation {
+    fn is_empty(&self) -> bool {
+        self.note.is_none() && self.tags.is_empty() && self.color.is_none()
+    }
+}
+
+/* Description:
+ *      Annotate info from history ui. Empty note and null colour clear the
+ *      value.
+ *
+ * Format:
+ *      [1, {"operation": {"Annotate": {"id": 3, "action": {"Note": "idor"}}}}]
+ *      [1, {"operation": {"Annotate": {"id": 3, "action": {"AddTag": "auth"}}}}]
+ *      [1, {"operation": {"Annotate": {"id": 3, "action": {"Color": "red"}}}}]
+ *      [1, {"operation": {"Annotate": {"id": 3, "action": "Clear"}}}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub struct AnnotateInfo {
+    pub id: usize,
+    pub action: AnnotateAction,
+}
+
+#[derive(Debug, Deserialize)]
+pub enum AnnotateAction {
+    Note(String),
+    AddTag(String),
+    RemoveTag(String),
+    Color(Option<Color>),
+    Clear,
+}
+
+/* Description:
+ *      Annotations of the session, stored as json object of id to annotation
+ *      in ./annotations.json. Ids without annotation are not stored.
+ */
+
+#[derive(Debug, Default)]
+pub struct Annotations {
+    path: PathBuf,
+    map: BTreeMap<usize, Annotation>,
+}
+
+impl Annotations {
+    // Read annotations from path, empty if file is missing
+    pub fn load(path: PathBuf) -> Result<Self, io::Error> {
+        let map = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            path,
+            map,
+        })
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Annotation> {
+        self.map.get(&id)
+    }
+
+    /* Steps:
+     *      1. Apply the action to annotation of id.
+     *          Note        => set note, clear if empty.
+     *          AddTag      => add trimmed tag, if not present.
+     *          RemoveTag   => remove tag.
+     *          Color       => set colour.
+     *          Clear       => remove annotation.
+     *
+     *      2. If annotation is empty, remove it.
+     *
+     * Returns:
+     *      History json line of the annotation
+     */
+
+    pub fn apply(&mut self, info: AnnotateInfo) -> String {
+        let annotation = self.map.entry(info.id).or_default();
+        match info.action {
+            AnnotateAction::Note(note) => {
+                annotation.note = (!note.is_empty()).then_some(note);
+            }
+            AnnotateAction::AddTag(tag) => {
+                let tag = tag.trim();
+                if !tag.is_empty() && !annotation.tags.iter().any(|t| t == tag)
+                {
+                    annotation.tags.push(tag.to_string());
+                }
+            }
+            AnnotateAction::RemoveTag(tag) => {
+                annotation
+                    .tags
+                    .retain(|t| t != tag.trim());
+            }
+            AnnotateAction::Color(color) => annotation.color = color,
+            AnnotateAction::Clear => *annotation = Annotation::default(),
+        }
+        let line = history_line(info.id, annotation);
+        if annotation.is_empty() {
+            self.map.remove(&info.id);
+        }
+        line
+    }
+
+    // Write annotations to file
+    pub async fn save(&self) -> Result<(), io::Error> {
+        let data = serde_json::to_vec(&self.map)?;
+        tokio::fs::write(&self.path, data).await
+    }
+
+    // History json lines of all annotations
+    pub fn lines(&self) -> Vec<String> {
+        self.map
+            .iter()
+            .map(|(id, annotation)| history_line(*id, annotation))
+            .collect()
+    }
+}
+
+// Annotation with id, flattened in history json line
+#[derive(Serialize)]
+struct AnnotationHistory<'a> {
+    id: usize,
+    #[serde(flatten)]
+    annotation: &'a Annotation,
+}
+
+#[derive(Serialize)]
+enum AnnotationLine<'a> {
+    Annotation(AnnotationHistory<'a>),
+}
+
+/* Description:
+ *      History json line sent to ui, separate from the Request line of the
+ *      id. Sent on every change and for all annotations on every connect, so
+ *      ui should replace the annotation of the id instead of appending to
+ *      it. Empty annotation is sent so that ui can remove it.
+ *
+ * Format:
+ *      {"Annotation":{"id":1,"note":"idor","tags":["auth"],"color":"red"}}
+ *      {"Annotation":{"id":1}}
+ */
+
+fn history_line(id: usize, annotation: &Annotation) -> String {
+    let line = AnnotationLine::Annotation(AnnotationHistory {
+        id,
+        annotation,
+    });
+    // safe to unwrap, only strings and enums
+    serde_json::to_string(&line).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(id: usize, action: AnnotateAction) -> AnnotateInfo {
+        AnnotateInfo {
+            id,
+            action,
+        }
+    }
+
+    #[test]
+    fn test_annotate_apply() {
+        let mut annotations = Annotations::default();
+        annotations.apply(info(1, AnnotateAction::Note("idor".to_string())));
+        annotations
+            .apply(info(1, AnnotateAction::AddTag(" auth ".to_string())));
+        annotations.apply(info(1, AnnotateAction::AddTag("auth".to_string())));
+        let line = annotations
+            .apply(info(1, AnnotateAction::Color(Some(Color::Red))));
+        assert_eq!(
+            line,
+            r#"{"Annotation":{"id":1,"note":"idor","tags":["auth"],"color":"red"}}"#
+        );
+        let verify = Annotation {
+            note: Some("idor".to_string()),
+            tags: vec!["auth".to_string()],
+            color: Some(Color::Red),
+        };
+        assert_eq!(annotations.get(1), Some(&verify));
+    }
+
+    #[test]
+    fn test_annotate_clear() {
+        let mut annotations = Annotations::default();
+        annotations.apply(info(2, AnnotateAction::AddTag("a".to_string())));
+        let line = annotations
+            .apply(info(2, AnnotateAction::RemoveTag("a".to_string())));
+        assert_eq!(line, r#"{"Annotation":{"id":2}}"#);
+        assert!(annotations.get(2).is_none());
+        annotations.apply(info(2, AnnotateAction::Note("x".to_string())));
+        annotations.apply(info(2, AnnotateAction::Clear));
+        assert!(annotations.lines().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_annotate_save_load() {
+        let path = PathBuf::from("/tmp/zxc_annotations.json");
+        let _ = std::fs::remove_file(&path);
+        let mut annotations = Annotations::load(path.clone()).unwrap();
+        annotations.apply(info(3, AnnotateAction::Color(Some(Color::Blue))));
+        annotations.save().await.unwrap();
+        let annotations = Annotations::load(path.clone()).unwrap();
+        assert_eq!(
+            annotations.lines(),
+            vec![r#"{"Annotation":{"id":3,"color":"blue"}}"#]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
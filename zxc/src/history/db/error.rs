@@ -0,0 +1,15 @@
+use std::io;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DbError {
+    #[error("sqlite| {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io| {0}")]
+    Io(#[from] io::Error),
+    #[error("history json| {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("no entry| {0}")]
+    NoEntry(usize),
+}
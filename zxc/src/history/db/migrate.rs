@@ -0,0 +1,137 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{Transaction, params};
+use tracing::error;
+
+use super::{DbError, HISTORY_DIR, WS_DIR};
+use crate::history::http::{HttpMessage, header_value, read_optional};
+use crate::history::wshistory::{HISTORY_WS_HIS, HISTORY_WS_WSESS};
+use crate::proxy::handler_state::handlers::get_attr;
+use crate::proxy::handler_state::transition::write_history::*;
+use crate::proxy::server_info::scheme::Scheme;
+
+/* Description:
+ *      Migrate a directory based session to the db.
+ *
+ * Steps:
+ *      1. For each numeric directory in ./history
+ *          a. Read $id.req, build the request history json from the request
+ *             line and the xattrs (Host header, if no xattrs) and store it
+ *             with the raw request and server info.
+ *
+ *          b. Read $id.res, build the response history json from the status
+ *             line and body length and store it with the raw response.
+ *
+ *          c. Read websocket/history.wsess and store it as a ws message.
+ *
+ *      2. For each line in ws.whis, http_id | scheme | address, store the ws.
+ *
+ * Returns:
+ *      Number of migrated http entries
+ */
+
+pub fn migrate(tx: &Transaction, session: &Path) -> Result<usize, DbError> {
+    let root = session.join(HISTORY_DIR);
+    let mut ids = match fs::read_dir(&root) {
+        Ok(dir) => dir
+            .filter_map(|entry| {
+                entry
+                    .ok()?
+                    .file_name()
+                    .to_str()?
+                    .parse::<usize>()
+                    .ok()
+            })
+            .collect::<Vec<usize>>(),
+        Err(e) => {
+            error!("migrate| read history| {}", e);
+            Vec::new()
+        }
+    };
+    ids.sort_unstable();
+
+    // 1. Http
+    let mut count = 0;
+    for id in ids {
+        let dir = root.join(id.to_string());
+        let req_path = dir.join(format!("{id}.req"));
+        let Some(req) = read_optional(&req_path)? else {
+            continue;
+        };
+        let info = get_attr(&req_path).ok();
+        let msg = HttpMessage::parse(&req);
+        let (host, scheme) = match info.as_ref() {
+            Some(info) if info.is_tls() => (info.host.clone(), Scheme::Https),
+            Some(info) => (info.host.clone(), Scheme::Http),
+            None => (
+                header_value(&msg.headers, "host")
+                    .unwrap_or_default()
+                    .to_string(),
+                Scheme::Https,
+            ),
+        };
+        let request = HistoryEnum::Request(RequestHistory::new(
+            id,
+            Cow::Borrowed(&msg.first),
+            scheme,
+            host,
+            Cow::Borrowed(&msg.second),
+        ));
+        let server_info = info
+            .map(|info| serde_json::to_string(&info))
+            .transpose()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO http (id, request, server_info, req)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![id, serde_json::to_string(&request)?, server_info, req],
+        )?;
+
+        // 1.b. Response
+        let res_path = dir.join(format!("{id}.res"));
+        if let Some(res) = read_optional(&res_path)? {
+            let msg = HttpMessage::parse(&res);
+            let response = HistoryEnum::Response(ResponseHistory::new(
+                id,
+                Cow::Borrowed(&msg.second),
+                msg.body.len(),
+            ));
+            tx.execute(
+                "UPDATE http SET response = ?2, res = ?3 WHERE id = ?1",
+                params![id, serde_json::to_string(&response)?, res],
+            )?;
+        }
+
+        // 1.c. Ws messages
+        let wsess = dir.join(WS_DIR).join(HISTORY_WS_WSESS);
+        if let Some(data) = read_optional(&wsess)? {
+            tx.execute(
+                "INSERT INTO ws_message (http_id, data) VALUES (?1, ?2)",
+                params![id, String::from_utf8_lossy(&data)],
+            )?;
+        }
+        count += 1;
+    }
+
+    // 2. Ws
+    if let Some(data) = read_optional(&session.join(HISTORY_WS_HIS))? {
+        for line in String::from_utf8_lossy(&data).lines() {
+            let mut fields = line.split(" | ");
+            let (Some(http_id), Some(scheme), Some(address)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Ok(http_id) = http_id.trim().parse::<usize>() else {
+                continue;
+            };
+            tx.execute(
+                "INSERT OR REPLACE INTO ws (http_id, scheme, address)
+                 VALUES (?1, ?2, ?3)",
+                params![http_id, scheme.trim(), address.trim()],
+            )?;
+        }
+    }
+    Ok(count)
+}
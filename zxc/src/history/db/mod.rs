@@ -0,0 +1,429 @@
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+
+use rusqlite::{Connection, OptionalExtension, params};
+use tracing::{error, trace};
+
+mod error;
+mod migrate;
+pub mod writer;
+pub use error::DbError;
+
+use crate::history::http::read_optional;
+use crate::history::wshistory::HISTORY_WS_WSESS;
+use crate::proxy::handler_state::handlers::{get_attr, set_attr};
+use crate::proxy::handler_state::transition::write_history::HistoryEnum;
+use crate::proxy::server_info::json::ServerInfoJson;
+
+pub const HISTORY_DB: &str = "history.db";
+const HISTORY_DIR: &str = "history";
+const WS_DIR: &str = "websocket";
+const SCHEMA_VERSION: i32 = 1;
+
+/* http
+ *      request     => history json of request
+ *      response    => history json of response
+ *      server_info => ServerInfoJson, same as the xattrs of .req file
+ *      req / res   => raw request and response
+ *
+ * ws
+ *      http_id     => log id of the http upgrade request
+ *
+ * ws_message
+ *      data        => ws history line
+ */
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS http (
+    id          INTEGER PRIMARY KEY,
+    request     TEXT,
+    response    TEXT,
+    server_info TEXT,
+    req         BLOB,
+    res         BLOB
+);
+CREATE TABLE IF NOT EXISTS ws (
+    http_id     INTEGER PRIMARY KEY,
+    scheme      TEXT NOT NULL,
+    address     TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS ws_message (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    http_id     INTEGER NOT NULL,
+    data        TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS ws_message_http_id ON ws_message (http_id);
+";
+
+const UPSERT_REQUEST: &str = "INSERT INTO http (id, request) VALUES (?1, ?2)
+    ON CONFLICT(id) DO UPDATE SET request = excluded.request";
+const UPSERT_RESPONSE: &str = "INSERT INTO http (id, response) VALUES (?1, ?2)
+    ON CONFLICT(id) DO UPDATE SET response = excluded.response";
+const UPSERT_REQ: &str =
+    "INSERT INTO http (id, req, server_info) VALUES (?1, ?2, ?3)
+    ON CONFLICT(id) DO UPDATE SET req = excluded.req,
+    server_info = excluded.server_info";
+const UPSERT_RES: &str = "INSERT INTO http (id, res) VALUES (?1, ?2)
+    ON CONFLICT(id) DO UPDATE SET res = excluded.res";
+
+/* Description:
+ *      Embedded database of session history, ./history.db
+ *
+ *      Every entry is written to the db as it is logged. The proxy upserts
+ *      the raw request when it is sent, with the server info, and the raw
+ *      response when it is received. History json and ws messages are
+ *      written by the history task. sync() on open copies the files written
+ *      while the db was not open.
+ *
+ *      Files in ./history are still written by the proxy, since intercept
+ *      edits them in place, and the ui, search, sitemap, export and
+ *      repeater read the files. Missing files are materialized from the db
+ *      on demand, so a session can be restored from history.db alone.
+ *
+ *      The connection is synchronous and owned by the db writer thread
+ *      [ writer::run_db_writer() ], never by an async task.
+ */
+
+pub struct HistoryDb {
+    conn: Connection,
+    session: PathBuf,
+}
+
+impl HistoryDb {
+    /* Steps:
+     *      1. Open session/history.db and create the tables.
+     *
+     *      2. If user_version is less than SCHEMA_VERSION, migrate the
+     *         directory based session in a transaction and set user_version.
+     *         A failed migration is retried on next open.
+     *
+     *      3. Sync the raw request and response written after the last close.
+     */
+
+    pub fn open(session: PathBuf) -> Result<Self, DbError> {
+        let mut conn = Connection::open(session.join(HISTORY_DB))?;
+        conn.execute_batch(SCHEMA)?;
+        let version: i32 =
+            conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version < SCHEMA_VERSION {
+            let tx = conn.transaction()?;
+            let count = migrate::migrate(&tx, &session)?;
+            tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            tx.commit()?;
+            trace!("db migrated| {}", count);
+        }
+        let db = Self {
+            conn,
+            session,
+        };
+        db.sync()?;
+        Ok(db)
+    }
+
+    fn history_dir(&self, id: usize) -> PathBuf {
+        self.session
+            .join(HISTORY_DIR)
+            .join(id.to_string())
+    }
+
+    // Store request or response history json
+    pub fn record(&self, data: &str) -> Result<(), DbError> {
+        let his = serde_json::from_str::<HistoryEnum>(data)?;
+        let query = match his {
+            HistoryEnum::Request(_) => UPSERT_REQUEST,
+            HistoryEnum::Response(_) => UPSERT_RESPONSE,
+            HistoryEnum::WebSocket(..) => return Ok(()),
+        };
+        // safe to unwrap, http history
+        self.conn
+            .execute(query, params![his.id().unwrap(), data])?;
+        Ok(())
+    }
+
+    // Store raw request as sent and server info json
+    pub fn store_request(
+        &self,
+        id: usize,
+        req: &[u8],
+        server_info: &str,
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(UPSERT_REQ, params![id, req, server_info])?;
+        Ok(())
+    }
+
+    // Store raw response as received
+    pub fn store_response(
+        &self,
+        id: usize,
+        res: &[u8],
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(UPSERT_RES, params![id, res])?;
+        Ok(())
+    }
+
+    pub fn register_ws(
+        &self,
+        http_id: usize,
+        scheme: &str,
+        address: &str,
+    ) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO ws (http_id, scheme, address) VALUES (?1, ?2, ?3)",
+            params![http_id, scheme, address],
+        )?;
+        Ok(())
+    }
+
+    pub fn ws_message(
+        &self,
+        http_id: usize,
+        data: &str,
+    ) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT INTO ws_message (http_id, data) VALUES (?1, ?2)",
+            params![http_id, data],
+        )?;
+        Ok(())
+    }
+
+    /* Description:
+     *      Store the raw request, response and server info of entries whose
+     *      files were written while the db was not open.
+     *
+     * Steps:
+     *      1. Select ids with missing req or res.
+     *      2. If missing req and $id.req exists, store it with the xattrs.
+     *      3. If missing res and $id.res exists, store it.
+     *
+     * Returns:
+     *      Number of files stored
+     */
+
+    pub fn sync(&self) -> Result<usize, DbError> {
+        let pending = self
+            .conn
+            .prepare(
+                "SELECT id, req IS NULL, res IS NULL FROM http
+                 WHERE req IS NULL OR res IS NULL",
+            )?
+            .query_map([], |row| {
+                Ok((row.get::<_, usize>(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<(usize, bool, bool)>, _>>()?;
+
+        let mut count = 0;
+        for (id, no_req, no_res) in pending {
+            let dir = self.history_dir(id);
+            if no_req {
+                let path = dir.join(format!("{id}.req"));
+                if let Some(req) = read_optional(&path)? {
+                    let server_info = get_attr(&path)
+                        .ok()
+                        .map(|info| serde_json::to_string(&info))
+                        .transpose()?;
+                    self.conn.execute(
+                        "UPDATE http SET req = ?2, server_info = ?3 WHERE id = ?1",
+                        params![id, req, server_info],
+                    )?;
+                    count += 1;
+                }
+            }
+            if no_res {
+                let path = dir.join(format!("{id}.res"));
+                if let Some(res) = read_optional(&path)? {
+                    self.conn.execute(
+                        "UPDATE http SET res = ?2 WHERE id = ?1",
+                        params![id, res],
+                    )?;
+                    count += 1;
+                }
+            }
+        }
+        trace!("db synced| {}", count);
+        Ok(count)
+    }
+
+    /* Description:
+     *      Write the files of a log id from the db, so that they can be
+     *      edited in the ui. Existing files are not overwritten.
+     *
+     * Steps:
+     *      1. Get req, res and server_info of id, if no row return
+     *         DbError::NoEntry
+     *
+     *      2. Create ./history/$id, write $id.req and $id.res if missing and
+     *         set the xattrs from server_info.
+     *
+     *      3. If ws messages exist, write websocket/history.wsess if missing.
+     *
+     * Returns:
+     *      Path of the .req file
+     */
+
+    pub fn materialize(&self, id: usize) -> Result<PathBuf, DbError> {
+        // 1. Get entry
+        let (req, res, server_info) = self
+            .conn
+            .query_row(
+                "SELECT req, res, server_info FROM http WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<Vec<u8>>>(0)?,
+                        row.get::<_, Option<Vec<u8>>>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                    ))
+                },
+            )
+            .optional()?
+            .ok_or(DbError::NoEntry(id))?;
+
+        // 2. Write files
+        let dir = self.history_dir(id);
+        fs::create_dir_all(&dir)?;
+        let req_path = dir.join(format!("{id}.req"));
+        if let Some(req) = req
+            && write_new(&req_path, &req)?
+            && let Some(info) = server_info
+        {
+            let info = serde_json::from_str::<ServerInfoJson>(&info)?;
+            if let Err(e) = set_attr(&req_path, info) {
+                error!("materialize| set attr| {}", e);
+            }
+        }
+        if let Some(res) = res {
+            write_new(&dir.join(format!("{id}.res")), &res)?;
+        }
+
+        // 3. Ws messages
+        let messages = self
+            .conn
+            .prepare(
+                "SELECT data FROM ws_message WHERE http_id = ?1 ORDER BY id",
+            )?
+            .query_map(params![id], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        if !messages.is_empty() {
+            let ws_dir = dir.join(WS_DIR);
+            fs::create_dir_all(&ws_dir)?;
+            write_new(
+                &ws_dir.join(HISTORY_WS_WSESS),
+                messages.concat().as_bytes(),
+            )?;
+        }
+        trace!("materialized| {}", id);
+        Ok(req_path)
+    }
+}
+
+// Write file if it does not exist, returns true if written
+fn write_new(path: &PathBuf, data: &[u8]) -> Result<bool, DbError> {
+    match File::create_new(path) {
+        Ok(mut file) => {
+            file.write_all(data)?;
+            Ok(true)
+        }
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::wshistory::HISTORY_WS_HIS;
+
+    const REQ: &str =
+        "GET /robots.txt HTTP/1.1\r\nHost: www.example.com\r\n\r\n";
+    const RES: &str = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+
+    fn build_session(name: &str) -> PathBuf {
+        let session = PathBuf::from(format!("/tmp/zxc_db_{name}"));
+        let _ = fs::remove_dir_all(&session);
+        let dir = session.join("history/1");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("1.req"), REQ).unwrap();
+        fs::write(dir.join("1.res"), RES).unwrap();
+        fs::write(
+            session.join(HISTORY_WS_HIS),
+            "1 | https | www.example.com:443\n",
+        )
+        .unwrap();
+        session
+    }
+
+    fn count(db: &HistoryDb, query: &str) -> usize {
+        db.conn
+            .query_row(query, [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_history_db_migrate() {
+        let session = build_session("migrate");
+        let db = HistoryDb::open(session.clone()).unwrap();
+        let (request, response): (String, String) = db
+            .conn
+            .query_row(
+                "SELECT request, response FROM http WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            request,
+            r#"{"Request":{"id":1,"method":"GET","host":"www.example.com","uri":"/robots.txt"}}"#
+        );
+        assert_eq!(
+            response,
+            r#"{"Response":{"id":1,"status":"200","length":2}}"#
+        );
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM ws"), 1);
+
+        // reopen, not migrated again
+        drop(db);
+        let db = HistoryDb::open(session.clone()).unwrap();
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM http"), 1);
+        fs::remove_dir_all(&session).unwrap();
+    }
+
+    #[test]
+    fn test_history_db_record_sync_materialize() {
+        let session = build_session("record");
+        let db = HistoryDb::open(session.clone()).unwrap();
+        db.record(
+            r#"{"Request":{"id":2,"method":"GET","host":"a.com","uri":"/"}}"#,
+        )
+        .unwrap();
+        db.record(r#"{"Response":{"id":2,"status":"404","length":0}}"#)
+            .unwrap();
+        db.ws_message(1, "1 | client -> server | 5\n")
+            .unwrap();
+
+        // files written after history json
+        let dir = session.join("history/2");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("2.req"), REQ).unwrap();
+        assert_eq!(db.sync().unwrap(), 1);
+        fs::write(dir.join("2.res"), RES).unwrap();
+        assert_eq!(db.sync().unwrap(), 1);
+        assert_eq!(db.sync().unwrap(), 0);
+
+        // materialize from db
+        fs::remove_dir_all(session.join("history")).unwrap();
+        let path = db.materialize(2).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), REQ);
+        assert_eq!(fs::read_to_string(dir.join("2.res")).unwrap(), RES);
+        db.materialize(1).unwrap();
+        let wsess = session.join("history/1/websocket/history.wsess");
+        assert_eq!(
+            fs::read_to_string(wsess).unwrap(),
+            "1 | client -> server | 5\n"
+        );
+        assert!(matches!(db.materialize(3), Err(DbError::NoEntry(3))));
+        fs::remove_dir_all(&session).unwrap();
+    }
+}
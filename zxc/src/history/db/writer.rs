@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+use bytes::Bytes;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::oneshot;
+use tracing::{debug, error, trace};
+
+use super::{DbError, HistoryDb};
+
+// Messages to the db writer
+#[derive(Debug)]
+pub enum ToDb {
+    // From History - request or response history json
+    Record(String),
+    // From Proxy - sent request and server info json
+    Request(usize, Bytes, String),
+    // From Proxy - received response
+    Response(usize, Bytes),
+    // From History - http id, scheme and address of ws
+    RegisterWs(usize, String, String),
+    // From History - http id and ws history line
+    WsMessage(usize, String),
+    // From History - write the files of id
+    Materialize(usize, oneshot::Sender<Result<PathBuf, DbError>>),
+    // From History - on close, reply after the queued messages are stored
+    Close(oneshot::Sender<()>),
+}
+
+/* Description:
+ *      Db writer, runs in its own thread since the rusqlite connection is
+ *      synchronous. Messages are queued in an unbounded channel and stored
+ *      in order, so that neither the proxy nor the history task wait for the
+ *      db.
+ *
+ * Steps:
+ *      1. Open the db [ HistoryDb::open() ], migrating and syncing the
+ *         session. If failed, return.
+ *
+ *      2. Receive messages till Close or all senders are dropped.
+ *
+ *      3. On error, log and continue.
+ */
+
+pub fn run_db_writer(session: PathBuf, mut from: UnboundedReceiver<ToDb>) {
+    // 1. Open
+    let db = match HistoryDb::open(session) {
+        Ok(db) => db,
+        Err(e) => {
+            error!("open history db| {}", e);
+            return;
+        }
+    };
+    debug!("[+] db writer started");
+    // 2. Receive
+    while let Some(msg) = from.blocking_recv() {
+        let result = match msg {
+            ToDb::Record(data) => db.record(&data),
+            ToDb::Request(id, req, server_info) => {
+                db.store_request(id, &req, &server_info)
+            }
+            ToDb::Response(id, res) => db.store_response(id, &res),
+            ToDb::RegisterWs(http_id, scheme, address) => {
+                db.register_ws(http_id, &scheme, &address)
+            }
+            ToDb::WsMessage(http_id, data) => db.ws_message(http_id, &data),
+            ToDb::Materialize(id, reply) => {
+                let _ = reply.send(db.materialize(id));
+                Ok(())
+            }
+            ToDb::Close(reply) => {
+                let _ = reply.send(());
+                break;
+            }
+        };
+        // 3. Error
+        if let Err(e) = result {
+            error!("db| {}", e);
+        }
+    }
+    trace!("db writer closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::thread::spawn;
+
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::*;
+
+    const REQ: &str = "GET / HTTP/1.1\r\nHost: a.com\r\n\r\n";
+    const RES: &str = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+
+    #[tokio::test]
+    async fn test_db_writer_store_materialize() {
+        let session = PathBuf::from("/tmp/zxc_db_writer");
+        let _ = fs::remove_dir_all(&session);
+        fs::create_dir_all(&session).unwrap();
+        let (to_db, from) = unbounded_channel();
+        let root = session.clone();
+        let writer = spawn(move || run_db_writer(root, from));
+
+        let info = r#"{"host":"a.com","http":true}"#;
+        to_db
+            .send(ToDb::Request(1, Bytes::from(REQ), info.to_string()))
+            .unwrap();
+        to_db
+            .send(ToDb::Response(1, Bytes::from(RES)))
+            .unwrap();
+        let (send, recv) = oneshot::channel();
+        to_db
+            .send(ToDb::Materialize(1, send))
+            .unwrap();
+        let path = recv.await.unwrap().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), REQ);
+        assert_eq!(
+            fs::read_to_string(session.join("history/1/1.res")).unwrap(),
+            RES
+        );
+
+        // close after queued
+        let (send, recv) = oneshot::channel();
+        to_db.send(ToDb::Close(send)).unwrap();
+        recv.await.unwrap();
+        writer.join().unwrap();
+        assert!(
+            to_db
+                .send(ToDb::Record(String::new()))
+                .is_err()
+        );
+        fs::remove_dir_all(&session).unwrap();
+    }
+}
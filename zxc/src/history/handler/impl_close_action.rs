@@ -3,9 +3,12 @@ use std::io::{Error, IoSlice};
 use oneone::abnf::LF;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::oneshot;
+use tracing::error;
 
 use super::HistoryHandler;
 use crate::builder::HISTORY_STATE_FILE;
+use crate::history::db::writer::ToDb;
 use crate::run::boundary::CloseAction;
 
 /* Steps:
@@ -14,6 +17,8 @@ use crate::run::boundary::CloseAction;
  *
  *      2. Convert storage to IoSlice and write to state file
  *
+ *      3. If db, close the db writer after the queued messages are stored.
+ *
  *  TODO:
  *      Handle amount the data written
  */
@@ -34,6 +39,13 @@ impl CloseAction for HistoryHandler {
             let _ = file.write_vectored(&io_slices).await?;
             file.flush().await?;
         }
+        if let Some(db) = self.db.as_ref() {
+            let (send, recv) = oneshot::channel();
+            let _ = db.send(ToDb::Close(send));
+            if recv.await.is_err() {
+                error!("db writer| closed");
+            }
+        }
         Ok(())
     }
 }
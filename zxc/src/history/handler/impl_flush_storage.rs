@@ -12,11 +12,16 @@ use crate::run::boundary::FlushStorage;
 /* Steps:
  *      1. Build Vec<IoSlice> with capacity of storage length * 3
  *
- *      2. For each entry in storage push
+ *      2. For each entry in storage followed by annotations, push
  *          - DEFAULT_ID
  *          - entry
  *          - ']'
  *
+ *         Annotations are sent on every connect, after the stored entries, so
+ *         that ui has them after restart and attach. Ui receives the same
+ *         annotation more than once and should replace the existing one of
+ *         the id.
+ *
  *      3. write to socket
  *
  * Errors:
@@ -27,14 +32,19 @@ impl FlushStorage for HistoryHandler {
         &mut self,
         stream: &mut UnixStream,
     ) -> Result<(), UnixSockError> {
-        let mut send: Vec<IoSlice<'_>> =
-            Vec::with_capacity(self.ui_storage.len() * 3);
+        let annotations = self.annotations.lines();
+        let mut send: Vec<IoSlice<'_>> = Vec::with_capacity(
+            (self.ui_storage.len() + annotations.len()) * 3,
+        );
 
-        self.ui_storage.iter().for_each(|data| {
-            send.push(IoSlice::new(DEFAULT_ID.as_bytes()));
-            send.push(IoSlice::new(data.as_bytes()));
-            send.push(IoSlice::new(CLOSE_SQUARE_BRACKET.as_bytes()));
-        });
+        self.ui_storage
+            .iter()
+            .chain(annotations.iter())
+            .for_each(|data| {
+                send.push(IoSlice::new(DEFAULT_ID.as_bytes()));
+                send.push(IoSlice::new(data.as_bytes()));
+                send.push(IoSlice::new(CLOSE_SQUARE_BRACKET.as_bytes()));
+            });
 
         let _ = unix_write_vec_and_flush(stream, &mut send)
             .await
@@ -1,18 +1,64 @@
+use std::path::Path;
+
+use serde_json::{Value, json};
+use tokio::sync::oneshot;
+use tracing::error;
+
 use super::HistoryHandler;
+use crate::commander::codegen::perform_copy_as;
+use crate::commander::compare::perform_compare;
+use crate::history::db::writer::ToDb;
 use crate::history::error::HistoryError;
+use crate::history::har::write_har;
 use crate::history::message::from_ui::{HistoryUIOps, HistoryUImsg};
 use crate::io::unix_sock::error::UnixSockError;
 use crate::run::boundary::HandleUI;
 
+const NO_DB: &str = "db not enabled";
+const NO_DB_WRITER: &str = "db writer closed";
+
 /* Steps:
  *      1. Deserialize the buffer to HistoryUImsg
  *      2. If operation is close, return HistoryError::UI
- *      3. Else send operation to commander
+ *      3. If operation is search,
+ *          a. Build regex from query, on error reply with error json.
+ *          b. Update the search index.
+ *          c. Reply with matching ids and snippets
+ *              {"Search": [{"id": 1, "matches": [{"part": .., "snippet": ..}]}]}
+ *      4. If operation is materialize, request the db writer to write the
+ *         files of the id and reply with the path of .req file
+ *              {"path": "history/1/1.req"}
+ *         If no db or on error, reply with error json.
+ *      5. If operation is export har, export the session to the given path in
+ *         a blocking task and reply with the number of entries
+ *              {"entries": 10}
+ *         On error, reply with error json.
+ *      6. If operation is copy as, generate code for the request file and
+ *         reply with
+ *              {"result": "curl ..."}
+ *         On error, reply with error json.
+ *      7. If operation is annotate, apply the action, save the annotations
+ *         and reply with the annotation history json
+ *              {"Annotation": {"id": 1, "tags": ["auth"]}}
+ *      8. If operation is compare, diff the two files and reply with
+ *              {"result": {"summary": .., "headers": .., ..}}
+ *         On error, reply with error json.
+ *      9. If operation is sitemap, scan the session on first call and
+ *         render the sitemap as json or text.
+ *          a. If path, write to the file and reply with
+ *              {"path": "site.json"}
+ *          b. Else reply with
+ *              {"result": ..}
+ *         On error, reply with error json.
+ *     10. If operation is inventory, update the parameter inventory and
+ *         reply with the params of matching hosts
+ *              {"Inventory": {"example.com": [{"name": "id", ..}]}}
+ *     11. Else send operation to commander
  *
  * Error:
  *      HistoryError::MsgDecode         [1]
  *      HistoryError::UI                [2]
- *      HistoryError::CommanderSend     [3]
+ *      HistoryError::CommanderSend     [11]
  */
 
 impl HandleUI for HistoryHandler {
@@ -20,12 +66,121 @@ impl HandleUI for HistoryHandler {
         &mut self,
     ) -> Result<Option<(usize, String)>, HistoryError> {
         let msg = serde_json::from_slice::<HistoryUImsg>(&self.buf)?;
-        if matches!(msg.operation, HistoryUIOps::Close) {
-            return Err(UnixSockError::Closed.into());
+        match msg.operation {
+            HistoryUIOps::Close => Err(UnixSockError::Closed.into()),
+            HistoryUIOps::Search(query) => {
+                let re = match query.build_regex() {
+                    Ok(re) => re,
+                    Err(e) => {
+                        error!("search| {}", e);
+                        let err = json!({"error" : e.to_string()});
+                        return Ok(Some((msg.id, err.to_string())));
+                    }
+                };
+                self.search_index.update().await;
+                let result = self
+                    .search_index
+                    .search(&re, &query)
+                    .await;
+                let data = json!({"Search": result}).to_string();
+                Ok(Some((msg.id, data)))
+            }
+            HistoryUIOps::Materialize(id) => {
+                let result = match self.db.as_ref() {
+                    Some(db) => {
+                        let (send, recv) = oneshot::channel();
+                        let _ = db.send(ToDb::Materialize(id, send));
+                        match recv.await {
+                            Ok(Ok(path)) => json!({"path": path}),
+                            Ok(Err(e)) => {
+                                error!("materialize| {}", e);
+                                json!({"error" : e.to_string()})
+                            }
+                            Err(_) => json!({"error" : NO_DB_WRITER}),
+                        }
+                    }
+                    None => json!({"error" : NO_DB}),
+                };
+                Ok(Some((msg.id, result.to_string())))
+            }
+            HistoryUIOps::ExportHar(path) => {
+                let result = tokio::task::spawn_blocking(move || {
+                    write_har(Path::new("."), &path)
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|res| res.map_err(|e| e.to_string()));
+                let data = match result {
+                    Ok(count) => json!({"entries": count}),
+                    Err(e) => {
+                        error!("export har| {}", e);
+                        json!({"error" : e})
+                    }
+                };
+                Ok(Some((msg.id, data.to_string())))
+            }
+            HistoryUIOps::CopyAs(info) => {
+                let result = perform_copy_as(&info)
+                    .await
+                    .unwrap_or_else(|e| {
+                        error!("copy as| {}", e);
+                        json!({"error" : e.to_string()})
+                    });
+                Ok(Some((msg.id, result.to_string())))
+            }
+            HistoryUIOps::Annotate(info) => {
+                let line = self.annotations.apply(info);
+                if let Err(e) = self.annotations.save().await {
+                    error!("save annotations| {}", e);
+                }
+                Ok(Some((msg.id, line)))
+            }
+            HistoryUIOps::Sitemap(info) => {
+                self.sitemap.update().await;
+                let result =
+                    match (self.sitemap.render(info.format), info.path) {
+                        (Ok(value), Some(path)) => {
+                            let data = match value {
+                                Value::String(text) => text,
+                                value => value.to_string(),
+                            };
+                            match tokio::fs::write(&path, data).await {
+                                Ok(()) => json!({"path": path}),
+                                Err(e) => {
+                                    error!("sitemap write| {}", e);
+                                    json!({"error" : e.to_string()})
+                                }
+                            }
+                        }
+                        (Ok(value), None) => json!({"result": value}),
+                        (Err(e), _) => {
+                            error!("sitemap| {}", e);
+                            json!({"error" : e.to_string()})
+                        }
+                    };
+                Ok(Some((msg.id, result.to_string())))
+            }
+            HistoryUIOps::Inventory(query) => {
+                self.inventory.update().await;
+                let result = self.inventory.query(&query);
+                let data = json!({"Inventory": result}).to_string();
+                Ok(Some((msg.id, data)))
+            }
+            HistoryUIOps::Compare(info) => {
+                let result = perform_compare(&info)
+                    .await
+                    .unwrap_or_else(|e| {
+                        error!("compare| {}", e);
+                        json!({"error" : e.to_string()})
+                    });
+                Ok(Some((msg.id, result.to_string())))
+            }
+            operation => {
+                self.to_commander
+                    .send(operation)
+                    .await?;
+                Ok(None)
+            }
         }
-        self.to_commander
-            .send(msg.into())
-            .await?;
-        Ok(None)
     }
 }
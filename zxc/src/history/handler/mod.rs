@@ -8,13 +8,18 @@ use std::fmt::Debug;
 
 use bytes::BytesMut;
 use tokio::fs::{File, OpenOptions};
-use tokio::sync::mpsc::{Receiver, Sender};
-use tracing::trace;
+use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender};
+use tracing::{error, trace};
 use zxc_derive::{Buffer, NotifyCommander};
 
+use super::annotation::Annotations;
+use super::db::writer::ToDb;
 use super::error::HistoryError;
+use super::inventory::ParamInventory;
 use super::message::from_commander::CommanderToHistory;
 use super::message::from_ui::HistoryUIOps;
+use super::search::SearchIndex;
+use super::sitemap::SiteMap;
 use super::wshistory::{HISTORY_WS_HIS, WsHistory};
 use crate::CAPACITY_2MB;
 use crate::id::Id;
@@ -27,10 +32,15 @@ const WS_REMOVE: &str = "ws_remove";
 // History Manager - handles history side of proxy
 #[derive(Buffer, NotifyCommander)]
 pub struct HistoryHandler {
+    annotations: Annotations,
     buf: BytesMut,
+    db: Option<UnboundedSender<ToDb>>,
     from_commander: Receiver<CommanderToHistory>,
+    inventory: ParamInventory,
     msg_storage: Vec<CommanderToHistory>,
     to_commander: Sender<HistoryUIOps>,
+    search_index: SearchIndex,
+    sitemap: SiteMap,
     ui_storage: Vec<String>, // Stores the history in case the channel is disconnected
     ws_his: Option<File>,
     ws_storage: Vec<WsHistory>, // Stores the Ws History
@@ -42,18 +52,32 @@ impl HistoryHandler {
         from_commander: Receiver<CommanderToHistory>,
         to_commander: Sender<HistoryUIOps>,
         storage: Vec<String>,
+        db: Option<UnboundedSender<ToDb>>,
+        annotations: Annotations,
     ) -> Self {
         Self {
+            annotations,
             buf: BytesMut::with_capacity(CAPACITY_2MB),
+            db,
             from_commander,
+            inventory: ParamInventory::default(),
             to_commander,
             msg_storage: Vec::with_capacity(100),
+            search_index: SearchIndex::default(),
+            sitemap: SiteMap::default(),
             ui_storage: storage,
             ws_his: None,
             ws_storage: Vec::with_capacity(100),
         }
     }
 
+    // Send to db writer, if db is enabled
+    fn to_db(&self, msg: ToDb) {
+        if let Some(Err(e)) = self.db.as_ref().map(|db| db.send(msg)) {
+            error!("db| {}", e);
+        }
+    }
+
     /* Steps:
      *      1. If size is 1, pop message, get response
      *          a. If with_sock, return Some(response)
@@ -102,8 +126,59 @@ impl HistoryHandler {
         let response = match msg {
             /* Associcated Values:
              *      data: String
+             *
+             * Steps:
+             *      1. Mark the id as dirty in search index.
+             *      2. Mark the id in parameter inventory.
+             *      3. Add to sitemap.
+             *      4. If db, send the history json to db writer.
+             */
+            CommanderToHistory::Http(data) => {
+                self.search_index.mark(&data);
+                self.inventory.mark(&data);
+                self.sitemap.add(&data);
+                self.to_db(ToDb::Record(data.clone()));
+                Some(data)
+            }
+
+            /* Associcated Values:
+             *      data: String
+             *
+             * Steps:
+             *      Send data to ui as is.
              */
-            CommanderToHistory::Http(data) => Some(data),
+            CommanderToHistory::Reply(data) => Some(data),
+
+            /* Associcated Values:
+             *      filter: Option<DomainFilter>
+             *
+             * Steps:
+             *      Set the domain filter of the links discovered in sitemap.
+             */
+            CommanderToHistory::Scope(filter) => {
+                self.sitemap.set_scope(filter);
+                None
+            }
+
+            /* Associcated Values:
+             *      host    : String
+             *      links   : Vec<Link>
+             *
+             * Steps:
+             *      Add links discovered by the analyzer to sitemap.
+             */
+            CommanderToHistory::Links(host, links) => {
+                self.sitemap.add_links(&host, links);
+                None
+            }
+
+            /* Associcated Values:
+             *      line: String
+             *
+             * Steps:
+             *      Send findings json line from the analyzer to ui as is.
+             */
+            CommanderToHistory::Findings(line) => Some(line),
 
             /* Associcated Values:
              *      reginfo: HistoryWsRegisterInfo
@@ -119,13 +194,15 @@ impl HistoryHandler {
              *          by calling log_data() method on WsRegisterInfo and
              *          write to ws.whis
              *
-             *      3. Create New WsHistory struct which maps id to .wsess file
+             *      3. If db, send ws to db writer.
+             *
+             *      4. Create New WsHistory struct which maps id to .wsess file
              *         and add to self.ws_storage
              *
              * Error:
              *      HistoryError::CreateWs   [1]
              *      HistoryError::WsWrite    [2]
-             *      HistoryError::RegisterWs [3]
+             *      HistoryError::RegisterWs [4]
              */
             CommanderToHistory::RegisterWs(reginfo) => {
                 trace!("ws register");
@@ -152,6 +229,13 @@ impl HistoryHandler {
                 .await
                 .map_err(HistoryError::WsWrite)?;
 
+                let server_info = reginfo.server_info();
+                self.to_db(ToDb::RegisterWs(
+                    reginfo.http_id,
+                    server_info.scheme().to_string(),
+                    server_info.address().to_string(),
+                ));
+
                 let wshis = WsHistory::new(reginfo)
                     .await
                     .map_err(HistoryError::RegisterWs)?;
@@ -163,6 +247,11 @@ impl HistoryHandler {
              *      id      : usize
              *      logdata : String
              *
+             * Steps:
+             *      1. Find the ws history with id.
+             *      2. Write logdata to .wsess file.
+             *      3. If db, send logdata to db writer.
+             *
              * Error:
              *      HistoryError::NoId      [1]
              *      HistoryError::WsWrite   [2]
@@ -177,6 +266,8 @@ impl HistoryHandler {
                 write_and_flush(wslog.file_as_mut(), logdata.as_bytes())
                     .await
                     .map_err(HistoryError::WsWrite)?;
+                let http_id = wslog.http_id();
+                self.to_db(ToDb::WsMessage(http_id, logdata));
                 None
             }
 
@@ -0,0 +1,11 @@
+use std::io;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum HarError {
+    #[error("io| {0}")]
+    Io(#[from] io::Error),
+    #[error("json| {0}")]
+    Json(#[from] serde_json::Error),
+}
@@ -0,0 +1,468 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, SecondsFormat, Utc};
+use tracing::trace;
+
+use super::error::HarError;
+use super::types::*;
+use crate::file_types::{EXT_WREQ, EXT_WRES};
+use crate::history::annotation::{Annotations, HISTORY_ANNOTATIONS};
+use crate::history::http::{HttpMessage, header_value, read_optional};
+use crate::history::wshistory::HISTORY_WS_WSESS;
+use crate::proxy::handler_state::handlers::{get_attr, get_timing_attr};
+use crate::proxy::server_info::scheme::Scheme;
+
+const HISTORY_DIR: &str = "history";
+const WS_DIR: &str = "websocket";
+const CONTENT_TYPE: &str = "content-type";
+const LOCATION: &str = "location";
+
+/* Description:
+ *      Convert the session in directory to HTTP Archive.
+ *
+ * Steps:
+ *      1. Collect numeric directories in session/history, sorted.
+ *      2. Build entry for each id with a .req file [ build_entry() ]
+ *      3. Set entry comment to the annotation note.
+ */
+
+pub fn export_har(session: &Path) -> Result<Har, HarError> {
+    let root = session.join(HISTORY_DIR);
+    let mut ids = fs::read_dir(&root)?
+        .filter_map(|entry| {
+            entry
+                .ok()?
+                .file_name()
+                .to_str()?
+                .parse::<usize>()
+                .ok()
+        })
+        .collect::<Vec<usize>>();
+    ids.sort_unstable();
+
+    let annotations = Annotations::load(session.join(HISTORY_ANNOTATIONS))?;
+    let mut entries = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Some(mut entry) = build_entry(&root.join(id.to_string()), id)? {
+            entry.comment = annotations
+                .get(id)
+                .and_then(|annotation| annotation.note.clone());
+            entries.push(entry);
+        }
+    }
+    trace!("har| {}", entries.len());
+    Ok(Har {
+        log: Log {
+            version: HAR_VERSION.to_string(),
+            creator: Creator::default(),
+            entries,
+        },
+    })
+}
+
+// Export session and write to output
+pub fn write_har(session: &Path, output: &Path) -> Result<usize, HarError> {
+    let har = export_har(session)?;
+    let count = har.log.entries.len();
+    fs::write(output, serde_json::to_vec_pretty(&har)?)?;
+    Ok(count)
+}
+
+/* Steps:
+ *      1. Read $id.req, if missing return None.
+ *
+ *      2. Scheme and host from xattrs, if missing use Host header and https.
+ *
+ *      3. Build request.
+ *
+ *      4. Read $id.res and build response, if missing status is 0.
+ *
+ *      5. Timings [ build_timings() ]
+ *
+ *      6. Add ws messages from websocket/history.wsess
+ */
+
+fn build_entry(dir: &Path, id: usize) -> Result<Option<Entry>, HarError> {
+    // 1. Read request
+    let req_path = dir.join(format!("{id}.req"));
+    let Some(req) = read_optional(&req_path)? else {
+        return Ok(None);
+    };
+    let req = HttpMessage::parse(&req);
+
+    // 2. Server info
+    let (scheme, host) = match get_attr(&req_path) {
+        Ok(info) if info.is_tls() => (Scheme::Https, info.host),
+        Ok(info) => (Scheme::Http, info.host),
+        Err(_) => (
+            Scheme::Https,
+            header_value(&req.headers, "host")
+                .unwrap_or_default()
+                .to_string(),
+        ),
+    };
+
+    // 3. Request
+    let uri = req.second.as_str();
+    let url = if uri.starts_with('/') {
+        format!("{}://{}{}", scheme, host, uri)
+    } else {
+        uri.to_string()
+    };
+    let query_string = uri
+        .split_once('?')
+        .map(|(_, query)| {
+            form_urlencoded::parse(query.as_bytes())
+                .map(|(name, value)| Header {
+                    name: name.into_owned(),
+                    value: value.into_owned(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let cookies = header_value(&req.headers, "cookie")
+        .map(parse_cookie)
+        .unwrap_or_default();
+    let post_data = (!req.body.is_empty()).then(|| PostData {
+        mime_type: header_value(&req.headers, CONTENT_TYPE)
+            .unwrap_or_default()
+            .to_string(),
+        text: String::from_utf8_lossy(&req.body).into_owned(),
+    });
+    let request = Request {
+        method: req.first.clone(),
+        url,
+        http_version: req.third.clone(),
+        cookies,
+        query_string,
+        post_data,
+        headers_size: req.headers_size as i64,
+        body_size: req.body.len() as i64,
+        headers: req.headers,
+    };
+
+    // 4. Response
+    let res_path = dir.join(format!("{id}.res"));
+    let response = match read_optional(&res_path)? {
+        Some(res) => build_response(HttpMessage::parse(&res)),
+        None => Response {
+            status: 0,
+            status_text: String::new(),
+            http_version: String::new(),
+            cookies: Vec::new(),
+            headers: Vec::new(),
+            content: Content::default(),
+            redirect_url: String::new(),
+            headers_size: -1,
+            body_size: -1,
+        },
+    };
+
+    // 5. Timings
+    let (started, timings, server_ip_address) =
+        build_timings(&req_path, &res_path)?;
+
+    // 6. Ws messages
+    let websocket_messages = build_ws_messages(&dir.join(WS_DIR))?;
+
+    Ok(Some(Entry {
+        started_date_time: DateTime::<Utc>::from(started)
+            .to_rfc3339_opts(SecondsFormat::Millis, true),
+        time: timings.wait + timings.receive,
+        request,
+        response,
+        cache: Cache::default(),
+        timings,
+        server_ip_address,
+        comment: None,
+        websocket_messages,
+    }))
+}
+
+/* Steps:
+ *      1. If .res has timing xattrs [ get_timing_attr() ],
+ *          startedDateTime = modified time of .res - duration
+ *          wait            = ttfb
+ *          receive         = duration - ttfb
+ *          serverIPAddress = ip
+ *
+ *      2. Else, startedDateTime is the modified time of .req file and wait
+ *         is the difference between modified time of .res and .req file.
+ */
+
+fn build_timings(
+    req_path: &PathBuf,
+    res_path: &PathBuf,
+) -> Result<(SystemTime, Timings, Option<String>), HarError> {
+    let received = fs::metadata(res_path)
+        .and_then(|meta| meta.modified())
+        .ok();
+    // 1. Recorded
+    let meta = get_timing_attr(res_path).unwrap_or_default();
+    if let (Some(received), Some(duration)) = (received, meta.duration) {
+        let ttfb = meta
+            .ttfb
+            .unwrap_or(duration)
+            .min(duration);
+        let started = received
+            .checked_sub(Duration::from_millis(duration))
+            .unwrap_or(received);
+        let timings = Timings {
+            send: 0.0,
+            wait: ttfb as f64,
+            receive: (duration - ttfb) as f64,
+        };
+        return Ok((started, timings, meta.ip.map(|ip| ip.to_string())));
+    }
+    // 2. Modified time
+    let sent = fs::metadata(req_path)?.modified()?;
+    let wait = received
+        .and_then(|received| received.duration_since(sent).ok())
+        .map_or(0.0, |duration| duration.as_secs_f64() * 1000.0);
+    let timings = Timings {
+        send: 0.0,
+        wait,
+        receive: 0.0,
+    };
+    Ok((sent, timings, None))
+}
+
+/* Steps:
+ *      1. Status code and text from status line.
+ *      2. Cookies from Set-Cookie headers, name=value before first ';'
+ *      3. Body as text if utf8, else base64 encoded.
+ */
+
+fn build_response(res: HttpMessage) -> Response {
+    let cookies = res
+        .headers
+        .iter()
+        .filter(|header| {
+            header
+                .name
+                .eq_ignore_ascii_case("set-cookie")
+        })
+        .filter_map(|header| {
+            let pair = header.value.split(';').next()?;
+            let (name, value) = pair.split_once('=')?;
+            Some(Cookie {
+                name: name.trim().to_string(),
+                value: value.trim().to_string(),
+            })
+        })
+        .collect();
+    let (text, encoding) = match std::str::from_utf8(&res.body) {
+        Ok(text) => (text.to_string(), None),
+        Err(_) => (STANDARD.encode(&res.body), Some("base64".to_string())),
+    };
+    Response {
+        status: res.second.parse().unwrap_or_default(),
+        status_text: res.third.clone(),
+        http_version: res.first.clone(),
+        cookies,
+        content: Content {
+            size: res.body.len() as i64,
+            mime_type: header_value(&res.headers, CONTENT_TYPE)
+                .unwrap_or_default()
+                .to_string(),
+            text: (!res.body.is_empty()).then_some(text),
+            encoding,
+        },
+        redirect_url: header_value(&res.headers, LOCATION)
+            .unwrap_or_default()
+            .to_string(),
+        headers_size: res.headers_size as i64,
+        body_size: res.body.len() as i64,
+        headers: res.headers,
+    }
+}
+
+/* Description:
+ *      Build ws messages from history.wsess, each line is
+ *          $id | -> | $size          => text sent, $id.wreq
+ *          $id | <- | b | $size      => binary received, $id.wres
+ *
+ *      time is the modified time of the message file in seconds.
+ */
+
+fn build_ws_messages(
+    dir: &Path,
+) -> Result<Option<Vec<WebSocketMessage>>, HarError> {
+    let Some(wsess) = read_optional(&dir.join(HISTORY_WS_WSESS))? else {
+        return Ok(None);
+    };
+    let mut messages = Vec::new();
+    for line in String::from_utf8_lossy(&wsess).lines() {
+        let fields: Vec<&str> = line.split(" | ").collect();
+        let (Some(id), Some(arrow)) = (fields.first(), fields.get(1)) else {
+            continue;
+        };
+        let (direction, ext) = match *arrow {
+            "->" => ("send", EXT_WREQ),
+            "<-" => ("receive", EXT_WRES),
+            _ => continue,
+        };
+        let path = dir.join(format!("{}.{}", id.trim(), ext));
+        let Some(data) = read_optional(&path)? else {
+            continue;
+        };
+        let time = fs::metadata(&path)?
+            .modified()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0.0, |duration| duration.as_secs_f64());
+        let is_bin = fields.get(2) == Some(&"b");
+        messages.push(WebSocketMessage {
+            direction: direction.to_string(),
+            time,
+            opcode: if is_bin {
+                2
+            } else {
+                1
+            },
+            data: if is_bin {
+                STANDARD.encode(&data)
+            } else {
+                String::from_utf8_lossy(&data).into_owned()
+            },
+        });
+    }
+    Ok(Some(messages))
+}
+
+// Cookie header, name=value pairs separated by ';'
+fn parse_cookie(value: &str) -> Vec<Cookie> {
+    value
+        .split(';')
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            Some(Cookie {
+                name: name.trim().to_string(),
+                value: value.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::handler_state::handlers::set_timing_attr;
+    use crate::proxy::handler_state::transition::write_history::ResponseMeta;
+
+    const REQ: &str = "POST /login?next=%2Fhome HTTP/1.1\r\nHost: www.example.com\r\nCookie: a=1; b=2\r\nContent-Type: application/x-www-form-urlencoded\r\n\r\nuser=admin";
+    const RES: &str = "HTTP/1.1 302 Found\r\nLocation: /home\r\nSet-Cookie: session=abc; HttpOnly\r\nContent-Type: text/plain\r\n\r\nok";
+
+    #[test]
+    fn test_parse_cookie() {
+        let verify = vec![
+            Cookie {
+                name: "a".to_string(),
+                value: "1".to_string(),
+            },
+            Cookie {
+                name: "b".to_string(),
+                value: "2".to_string(),
+            },
+        ];
+        assert_eq!(parse_cookie("a=1; b=2"), verify);
+    }
+
+    #[test]
+    fn test_export_har() {
+        let session = Path::new("/tmp/zxc_har_export");
+        let _ = fs::remove_dir_all(session);
+        let dir = session.join("history/1");
+        fs::create_dir_all(dir.join(WS_DIR)).unwrap();
+        fs::write(dir.join("1.req"), REQ).unwrap();
+        fs::write(dir.join("1.res"), RES).unwrap();
+        fs::write(
+            dir.join(WS_DIR).join(HISTORY_WS_WSESS),
+            "1 | -> | 5\n2 | <- | b | 2\n",
+        )
+        .unwrap();
+        fs::write(dir.join(WS_DIR).join("1.wreq"), "hello").unwrap();
+        fs::write(dir.join(WS_DIR).join("2.wres"), [0xff, 0x00]).unwrap();
+        fs::create_dir_all(session.join("history/2")).unwrap();
+
+        let har = export_har(session).unwrap();
+        assert_eq!(har.log.entries.len(), 1);
+        let entry = &har.log.entries[0];
+        assert_eq!(entry.request.method, "POST");
+        assert_eq!(
+            entry.request.url,
+            "https://www.example.com/login?next=%2Fhome"
+        );
+        assert_eq!(entry.request.http_version, "HTTP/1.1");
+        assert_eq!(
+            entry.request.query_string,
+            vec![Header {
+                name: "next".to_string(),
+                value: "/home".to_string(),
+            }]
+        );
+        assert_eq!(entry.request.cookies.len(), 2);
+        assert_eq!(
+            entry
+                .request
+                .post_data
+                .as_ref()
+                .unwrap()
+                .text,
+            "user=admin"
+        );
+        assert_eq!(entry.response.status, 302);
+        assert_eq!(entry.response.status_text, "Found");
+        assert_eq!(entry.response.redirect_url, "/home");
+        assert_eq!(entry.response.cookies[0].value, "abc");
+        assert_eq!(entry.response.content.text.as_deref(), Some("ok"));
+        assert_eq!(entry.response.content.mime_type, "text/plain");
+        let ws = entry
+            .websocket_messages
+            .as_ref()
+            .unwrap();
+        assert_eq!(ws[0].direction, "send");
+        assert_eq!(ws[0].data, "hello");
+        assert_eq!(ws[1].opcode, 2);
+        assert_eq!(ws[1].data, "/wA=");
+        fs::remove_dir_all(session).unwrap();
+    }
+
+    #[test]
+    fn test_build_timings_recorded() {
+        let dir = PathBuf::from("/tmp/zxc_har_timings");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let req_path = dir.join("1.req");
+        let res_path = dir.join("1.res");
+        fs::write(&req_path, REQ).unwrap();
+        fs::write(&res_path, RES).unwrap();
+        let meta = ResponseMeta {
+            ttfb: Some(20),
+            duration: Some(35),
+            ip: Some("127.0.0.1".parse().unwrap()),
+            ..Default::default()
+        };
+        set_timing_attr(&res_path, &meta).unwrap();
+        let (started, timings, ip) =
+            build_timings(&req_path, &res_path).unwrap();
+        let received = fs::metadata(&res_path)
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(
+            received
+                .duration_since(started)
+                .unwrap()
+                .as_millis(),
+            35
+        );
+        assert_eq!(timings.wait, 20.0);
+        assert_eq!(timings.receive, 15.0);
+        assert_eq!(ip.as_deref(), Some("127.0.0.1"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,5 @@
+mod error;
+mod export;
+pub mod types;
+
+pub use export::write_har;
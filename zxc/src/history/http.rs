@@ -0,0 +1,199 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use super::har::types::Header;
+
+const TRANSFER_ENCODING: &str = "Transfer-Encoding";
+const CHUNKED: &str = "chunked";
+
+/* Description:
+ *      Parsed http request or response file of the history. Shared parser
+ *      for the views of the history (har, sitemap, search, inventory,
+ *      passive, import, migrate), cookie jar and redirects.
+ *
+ *      Unlike oneone, files are parsed leniently, a malformed info line or
+ *      header is kept or skipped instead of failing the file.
+ */
+
+pub struct HttpMessage {
+    // method | version
+    pub first: String,
+    // uri | status code
+    pub second: String,
+    // version | reason, rest of the line
+    pub third: String,
+    pub headers: Vec<Header>,
+    // size of the headers including the empty line
+    pub headers_size: usize,
+    // dechunked, if Transfer-Encoding is chunked
+    pub body: Vec<u8>,
+}
+
+impl HttpMessage {
+    /* Steps:
+     *      1. Split into headers and body [ split_head() ].
+     *
+     *      2. Split the first line by space into three parts.
+     *
+     *      3. Split each following line at ':' into name and value.
+     *
+     *      4. If Transfer-Encoding is chunked, dechunk the body. If the
+     *         chunks are malformed, body is kept as is.
+     */
+
+    pub fn parse(data: &[u8]) -> Self {
+        // 1. Split
+        let (head, body) = split_head(data);
+        let headers_size = data.len() - body.len();
+        // 2. Info line
+        let head = String::from_utf8_lossy(head);
+        let mut lines = head.lines();
+        let mut infoline = lines
+            .next()
+            .unwrap_or_default()
+            .splitn(3, ' ');
+        let mut next = || {
+            infoline
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        };
+        let (first, second, third) = (next(), next(), next());
+        // 3. Headers
+        let headers: Vec<Header> = lines
+            .filter_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                Some(Header {
+                    name: name.trim().to_string(),
+                    value: value.trim().to_string(),
+                })
+            })
+            .collect();
+        // 4. Dechunk
+        let chunked =
+            header_value(&headers, TRANSFER_ENCODING).is_some_and(|te| {
+                te.to_ascii_lowercase()
+                    .contains(CHUNKED)
+            });
+        let body = chunked
+            .then(|| dechunk(body))
+            .flatten()
+            .unwrap_or_else(|| body.to_vec());
+        Self {
+            first,
+            second,
+            third,
+            headers,
+            headers_size,
+            body,
+        }
+    }
+}
+
+// Split http message after the empty line, CRLFCRLF or LFLF, into headers
+// and body. If missing, whole data is header.
+pub fn split_head(data: &[u8]) -> (&[u8], &[u8]) {
+    let crlf = data
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|pos| (pos, pos + 4));
+    let lf = data
+        .windows(2)
+        .position(|window| window == b"\n\n")
+        .map(|pos| (pos, pos + 2));
+    match crlf.into_iter().chain(lf).min() {
+        Some((end, start)) => (&data[..end], &data[start..]),
+        None => (data, &data[data.len()..]),
+    }
+}
+
+// Value of first header with name, case insensitive
+pub fn header_value<'a>(headers: &'a [Header], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|header| header.name.eq_ignore_ascii_case(name))
+        .map(|header| header.value.as_str())
+}
+
+// Read file, None if not found
+pub fn read_optional(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/* Steps:
+ *      1. Read the hex size of the chunk line, ignoring extensions.
+ *
+ *      2. If size is 0, return the data, trailers are ignored.
+ *
+ *      3. Else append the chunk and skip the CRLF after it.
+ *
+ * Returns:
+ *      Option<Vec<u8>>, None if a size is invalid or a chunk is truncated
+ */
+
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(body.len());
+    loop {
+        // 1. Size
+        let end = body.iter().position(|b| *b == b'\n')?;
+        let line = std::str::from_utf8(&body[..end]).ok()?;
+        let size = line
+            .split(';')
+            .next()
+            .map(str::trim)
+            .and_then(|size| usize::from_str_radix(size, 16).ok())?;
+        body = &body[end + 1..];
+        // 2. Last
+        if size == 0 {
+            return Some(data);
+        }
+        // 3. Chunk
+        data.extend_from_slice(body.get(..size)?);
+        body = &body[size..];
+        body = body
+            .strip_prefix(b"\r\n")
+            .or_else(|| body.strip_prefix(b"\n"))
+            .unwrap_or(body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_message_parse() {
+        let data = b"HTTP/1.1 404 Not Found\r\nContent-Length: 4\r\nX-A: b:c\r\n\r\nnope";
+        let msg = HttpMessage::parse(data);
+        assert_eq!(msg.first, "HTTP/1.1");
+        assert_eq!(msg.second, "404");
+        assert_eq!(msg.third, "Not Found");
+        assert_eq!(header_value(&msg.headers, "x-a"), Some("b:c"));
+        assert_eq!(msg.headers_size, data.len() - 4);
+        assert_eq!(msg.body, b"nope");
+    }
+
+    #[test]
+    fn test_http_message_parse_chunked() {
+        let data = b"HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\n4;ext=1\r\nWiki\r\n6\r\npedia \r\n0\r\n\r\n";
+        let msg = HttpMessage::parse(data);
+        assert_eq!(
+            header_value(&msg.headers, "transfer-encoding"),
+            Some("chunked")
+        );
+        assert_eq!(msg.body, b"Wikipedia ");
+    }
+
+    #[test]
+    fn test_http_message_parse_chunked_malformed() {
+        let data =
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\nWiki";
+        let msg = HttpMessage::parse(data);
+        assert_eq!(msg.body, b"zz\r\nWiki");
+    }
+}
@@ -0,0 +1,19 @@
+use std::io;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("io| {0}")]
+    Io(#[from] io::Error),
+    #[error("json| {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid url| {0}")]
+    InvalidUrl(String),
+    #[error("no host| {0}")]
+    NoHost(String),
+    #[error("invalid request")]
+    InvalidRequest,
+    #[error("base64| {0}")]
+    Base64(#[from] base64::DecodeError),
+}
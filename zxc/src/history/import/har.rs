@@ -0,0 +1,127 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use super::error::ImportError;
+use super::{ImportEntry, build_message, target_from_url};
+use crate::history::har::types::{Entry, Har};
+
+/* Steps:
+ *      1. Deserialize the archive.
+ *      2. Convert each entry [ from_entry() ]
+ */
+
+pub fn read_har(data: &[u8]) -> Result<Vec<ImportEntry>, ImportError> {
+    let har = serde_json::from_slice::<Har>(data)?;
+    har.log
+        .entries
+        .into_iter()
+        .map(from_entry)
+        .collect()
+}
+
+/* Steps:
+ *      1. Get server info and origin form uri from request url.
+ *
+ *      2. Build request from method, uri, headers and postData. Pseudo
+ *         headers of http/2 archives are skipped and Host header is added
+ *         if missing.
+ *
+ *      3. If status is not 0, build response from status, headers and
+ *         content. base64 encoded content is decoded.
+ */
+
+fn from_entry(entry: Entry) -> Result<ImportEntry, ImportError> {
+    // 1. Target
+    let (server_info, uri, host) = target_from_url(&entry.request.url)?;
+
+    // 2. Request
+    let mut headers: Vec<(String, String)> = entry
+        .request
+        .headers
+        .into_iter()
+        .filter(|header| !header.name.starts_with(':'))
+        .map(|header| (header.name, header.value))
+        .collect();
+    if !headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("host"))
+    {
+        headers.insert(0, ("Host".to_string(), host));
+    }
+    let body = entry
+        .request
+        .post_data
+        .map(|data| data.text.into_bytes())
+        .unwrap_or_default();
+    let req = build_message(
+        format!("{} {} HTTP/1.1", entry.request.method, uri),
+        headers,
+        &body,
+    );
+
+    // 3. Response
+    let res = if entry.response.status == 0 {
+        None
+    } else {
+        let content = entry.response.content;
+        let body = match (content.text, content.encoding.as_deref()) {
+            (Some(text), Some("base64")) => STANDARD.decode(text)?,
+            (Some(text), _) => text.into_bytes(),
+            (None, _) => Vec::new(),
+        };
+        let headers = entry
+            .response
+            .headers
+            .into_iter()
+            .map(|header| (header.name, header.value))
+            .collect();
+        Some(build_message(
+            format!(
+                "HTTP/1.1 {} {}",
+                entry.response.status, entry.response.status_text
+            ),
+            headers,
+            &body,
+        ))
+    };
+    Ok(ImportEntry {
+        server_info,
+        req,
+        res,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_har() {
+        let data = r#"{"log": {"version": "1.2", "creator": {"name": "x", "version": "1"}, "entries": [
+            {"startedDateTime": "2024-01-01T00:00:00.000Z",
+             "request": {"method": "POST", "url": "http://example.com:8080/a?b=1", "httpVersion": "HTTP/2",
+                         "headers": [{"name": ":authority", "value": "example.com"}, {"name": "Content-Length", "value": "99"}],
+                         "postData": {"mimeType": "text/plain", "text": "hi"}},
+             "response": {"status": 200, "statusText": "OK", "httpVersion": "HTTP/2",
+                          "headers": [{"name": "Content-Encoding", "value": "gzip"}],
+                          "content": {"size": 2, "mimeType": "text/plain", "text": "b2s=", "encoding": "base64"}}},
+            {"startedDateTime": "2024-01-01T00:00:00.000Z",
+             "request": {"method": "GET", "url": "https://example.com/", "httpVersion": "HTTP/1.1"},
+             "response": {"status": 0}}
+        ]}}"#;
+        let entries = read_har(data.as_bytes()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].server_info.host, "example.com:8080");
+        assert_eq!(entries[0].server_info.http, Some(true));
+        assert_eq!(
+            entries[0].req,
+            b"POST /a?b=1 HTTP/1.1\r\nHost: example.com:8080\r\nContent-Length: 2\r\n\r\nhi"
+        );
+        assert_eq!(
+            entries[0].res.as_deref(),
+            Some(&b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok"[..])
+        );
+        assert!(entries[1].server_info.http.is_none());
+        assert!(entries[1].res.is_none());
+    }
+}
@@ -0,0 +1,277 @@
+use std::borrow::Cow;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use http::Uri;
+use serde::Deserialize;
+use tracing::error;
+
+mod error;
+mod har;
+mod raw;
+pub use error::ImportError;
+
+use crate::builder::HISTORY_STATE_FILE;
+use crate::history::http::HttpMessage;
+use crate::proxy::handler_state::handlers::set_attr;
+use crate::proxy::handler_state::transition::write_history::*;
+use crate::proxy::server_info::json::ServerInfoJson;
+use crate::proxy::server_info::scheme::Scheme;
+
+const HAR_EXT: &str = "har";
+
+// Headers recalculated when the message is rebuilt
+const SKIP_HEADERS: [&str; 3] =
+    ["content-length", "transfer-encoding", "content-encoding"];
+
+/* Description:
+ *      Import info from history ui.
+ *
+ * Format:
+ *      [1, {"operation": {"Import": {
+ *              "path": "/tmp/bug.har",
+ *              "target": "https://example.com:8443"  // raw request only
+ *      }}}]
+ *
+ *      Reply is sent after the history json of the entries,
+ *      [0, {"Import": {"entries": 2}}] or [0, {"Import": {"error": ".."}}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub struct ImportInfo {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+// Http request and response to be written to history
+pub struct ImportEntry {
+    pub server_info: ServerInfoJson,
+    pub req: Vec<u8>,
+    pub res: Option<Vec<u8>>,
+}
+
+/* Steps:
+ *      1. Read the file.
+ *      2. If extension is har, read as HTTP Archive, else as raw request.
+ */
+
+pub fn read_import(
+    path: &Path,
+    target: Option<&str>,
+) -> Result<Vec<ImportEntry>, ImportError> {
+    let data = fs::read(path)?;
+    let is_har = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(HAR_EXT));
+    if is_har {
+        har::read_har(&data)
+    } else {
+        Ok(vec![raw::read_raw(&data, target)?])
+    }
+}
+
+/* Description:
+ *      Write the imported entry to the log directory, dir is
+ *      ./history/$id created by the caller.
+ *
+ * Steps:
+ *      1. Build request history json from the request line and server info.
+ *
+ *      2. Write $id.req and set the server info xattrs.
+ *
+ *      3. If response, build response history json and write $id.res
+ *
+ * Returns:
+ *      History json lines
+ */
+
+pub fn write_entry(
+    dir: &Path,
+    id: usize,
+    entry: ImportEntry,
+) -> Result<Vec<String>, ImportError> {
+    // 1. Request history
+    let req = HttpMessage::parse(&entry.req);
+    let scheme = if entry.server_info.http.is_some() {
+        Scheme::Http
+    } else {
+        Scheme::Https
+    };
+    let request = HistoryEnum::Request(RequestHistory::new(
+        id,
+        Cow::Borrowed(req.first.as_str()),
+        scheme,
+        entry.server_info.host.clone(),
+        Cow::Borrowed(req.second.as_str()),
+    ));
+    let mut lines = vec![serde_json::to_string(&request)?];
+
+    // 2. Write request
+    let req_path = dir.join(format!("{id}.req"));
+    fs::write(&req_path, &entry.req)?;
+    if let Err(e) = set_attr(&req_path, entry.server_info) {
+        error!("import| set attr| {}", e);
+    }
+
+    // 3. Response
+    if let Some(res) = entry.res {
+        let msg = HttpMessage::parse(&res);
+        let response = HistoryEnum::Response(ResponseHistory::new(
+            id,
+            Cow::Borrowed(msg.second.as_str()),
+            msg.body.len(),
+        ));
+        lines.push(serde_json::to_string(&response)?);
+        fs::write(dir.join(format!("{id}.res")), &res)?;
+    }
+    Ok(lines)
+}
+
+/* Description:
+ *      Import files to the session in current directory, when the session is
+ *      not running. Ids are allocated from index, similar to the commander.
+ *
+ * Steps:
+ *      For each entry in each file
+ *          1. Create ./history/$index
+ *          2. Write entry [ write_entry() ]
+ *          3. Append history json lines to .history.state, which is read
+ *             when attaching to the session.
+ *
+ * Returns:
+ *      Number of imported entries
+ */
+
+pub fn import_files(
+    files: &[PathBuf],
+    target: Option<&str>,
+    mut index: usize,
+) -> Result<usize, ImportError> {
+    let mut state = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(HISTORY_STATE_FILE)?;
+    let mut count = 0;
+    for file in files {
+        for entry in read_import(file, target)? {
+            let dir = PathBuf::from(format!("./history/{index}"));
+            fs::create_dir(&dir)?;
+            for line in write_entry(&dir, index, entry)? {
+                writeln!(state, "{}", line)?;
+            }
+            index += 1;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/* Description:
+ *      Server info, origin form uri and Host header value from url.
+ *      Scheme defaults to https and default port is removed from host.
+ *
+ * Error:
+ *      ImportError::InvalidUrl
+ */
+
+fn target_from_url(
+    url: &str,
+) -> Result<(ServerInfoJson, String, String), ImportError> {
+    let invalid = || ImportError::InvalidUrl(url.to_string());
+    let uri = url
+        .parse::<Uri>()
+        .map_err(|_| invalid())?;
+    let scheme = match uri.scheme_str() {
+        Some("http") => Scheme::Http,
+        Some("https") | None => Scheme::Https,
+        Some(_) => return Err(invalid()),
+    };
+    let authority = uri.authority().ok_or_else(invalid)?;
+    let host = match authority.port_u16() {
+        Some(port) if port != scheme.default_port() => {
+            format!("{}:{}", authority.host(), port)
+        }
+        _ => authority.host().to_string(),
+    };
+    let path = uri
+        .path_and_query()
+        .map_or("/", |path| path.as_str())
+        .to_string();
+    let server_info = ServerInfoJson::new(host.clone(), scheme, None);
+    Ok((server_info, path, host))
+}
+
+/* Steps:
+ *      1. Write infoline and headers, except SKIP_HEADERS.
+ *      2. If body is not empty, add Content-Length.
+ *      3. Write body.
+ */
+
+fn build_message(
+    infoline: String,
+    headers: Vec<(String, String)>,
+    body: &[u8],
+) -> Vec<u8> {
+    let mut data = infoline.into_bytes();
+    data.extend_from_slice(b"\r\n");
+    for (name, value) in headers.iter().filter(|(name, _)| {
+        !SKIP_HEADERS
+            .iter()
+            .any(|skip| name.eq_ignore_ascii_case(skip))
+    }) {
+        data.extend_from_slice(format!("{name}: {value}\r\n").as_bytes());
+    }
+    if !body.is_empty() {
+        data.extend_from_slice(
+            format!("Content-Length: {}\r\n", body.len()).as_bytes(),
+        );
+    }
+    data.extend_from_slice(b"\r\n");
+    data.extend_from_slice(body);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_from_url() {
+        let (info, path, host) =
+            target_from_url("https://example.com:443").unwrap();
+        assert_eq!(info.host, "example.com");
+        assert!(info.is_tls());
+        assert_eq!(path, "/");
+        assert_eq!(host, "example.com");
+        assert!(target_from_url("ftp://example.com").is_err());
+    }
+
+    #[test]
+    fn test_write_entry() {
+        let dir = Path::new("/tmp/zxc_import_entry");
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        let entry = ImportEntry {
+            server_info: ServerInfoJson::new(
+                "example.com".to_string(),
+                Scheme::Http,
+                None,
+            ),
+            req: b"GET /a HTTP/1.1\r\nHost: example.com\r\n\r\n".to_vec(),
+            res: Some(b"HTTP/1.1 404 Not Found\r\n\r\nnope".to_vec()),
+        };
+        let lines = write_entry(dir, 7, entry).unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                r#"{"Request":{"id":7,"method":"GET","http":true,"host":"example.com","uri":"/a"}}"#,
+                r#"{"Response":{"id":7,"status":"404","length":4}}"#
+            ]
+        );
+        assert!(dir.join("7.req").exists());
+        assert!(dir.join("7.res").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+use super::error::ImportError;
+use super::{ImportEntry, build_message, target_from_url};
+use crate::history::http::{HttpMessage, header_value};
+use crate::proxy::server_info::json::ServerInfoJson;
+
+/* Description:
+ *      Import a raw http request.
+ *
+ * Steps:
+ *      1. If the request uses LF line endings, convert to CRLF.
+ *
+ *      2. Parse the request, if no method or uri return
+ *         ImportError::InvalidRequest
+ *
+ *      3. Get server info
+ *          a. target       => scheme://host[:port]
+ *          b. absolute uri => from uri, uri is converted to origin form
+ *          c. Host header  => https
+ *
+ *      4. Rebuild the request, so that Content-Length matches the body.
+ */
+
+pub fn read_raw(
+    data: &[u8],
+    target: Option<&str>,
+) -> Result<ImportEntry, ImportError> {
+    // 1. Line endings
+    let data = if data
+        .windows(2)
+        .any(|window| window == b"\r\n")
+    {
+        data.to_vec()
+    } else {
+        String::from_utf8_lossy(data)
+            .replace('\n', "\r\n")
+            .into_bytes()
+    };
+
+    // 2. Parse
+    let msg = HttpMessage::parse(&data);
+    if msg.first.is_empty() || msg.second.is_empty() {
+        return Err(ImportError::InvalidRequest);
+    }
+
+    // 3. Server info
+    let (server_info, uri) = if let Some(target) = target {
+        let (server_info, _, _) = target_from_url(target)?;
+        (server_info, msg.second.clone())
+    } else if msg.second.starts_with("http") {
+        let (server_info, uri, _) = target_from_url(&msg.second)?;
+        (server_info, uri)
+    } else {
+        let host = header_value(&msg.headers, "host")
+            .ok_or_else(|| ImportError::NoHost(msg.second.clone()))?;
+        (
+            ServerInfoJson {
+                host: host.to_string(),
+                http: None,
+                sni: None,
+            },
+            msg.second.clone(),
+        )
+    };
+
+    // 4. Rebuild
+    let headers = msg
+        .headers
+        .into_iter()
+        .map(|header| (header.name, header.value))
+        .collect();
+    let req = build_message(
+        format!("{} {} HTTP/1.1", msg.first, uri),
+        headers,
+        &msg.body,
+    );
+    Ok(ImportEntry {
+        server_info,
+        req,
+        res: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_raw_lf() {
+        let data = b"GET /a HTTP/1.1\nHost: example.com\n\n";
+        let entry = read_raw(data, None).unwrap();
+        assert_eq!(entry.server_info.host, "example.com");
+        assert!(entry.server_info.http.is_none());
+        assert_eq!(entry.req, b"GET /a HTTP/1.1\r\nHost: example.com\r\n\r\n");
+    }
+
+    #[test]
+    fn test_read_raw_target() {
+        let data = b"POST /a HTTP/1.1\r\nHost: example.com\r\nContent-Length: 1\r\n\r\nabc";
+        let entry = read_raw(data, Some("http://127.0.0.1:8000")).unwrap();
+        assert_eq!(entry.server_info.host, "127.0.0.1:8000");
+        assert_eq!(entry.server_info.http, Some(true));
+        assert_eq!(
+            entry.req,
+            b"POST /a HTTP/1.1\r\nHost: example.com\r\nContent-Length: 3\r\n\r\nabc"
+        );
+    }
+
+    #[test]
+    fn test_read_raw_no_host() {
+        let data = b"GET /a HTTP/1.1\r\n\r\n";
+        assert!(matches!(read_raw(data, None), Err(ImportError::NoHost(_))));
+    }
+}
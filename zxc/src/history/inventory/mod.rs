@@ -0,0 +1,463 @@
+pub mod parse;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use parse::{Location, Param, parse_request, parse_set_cookies};
+use serde::{Deserialize, Serialize};
+use tracing::{error, trace};
+
+use crate::builder::HISTORY;
+use crate::history::http::HttpMessage;
+use crate::history::stamp::{FileStamp, changed, read_stable};
+use crate::proxy::handler_state::handlers::get_attr;
+
+const MAX_SAMPLES: usize = 5;
+const MAX_SAMPLE_LEN: usize = 64;
+
+// Only the id of the history json is needed
+#[derive(Deserialize)]
+struct IdOnly {
+    id: usize,
+}
+
+#[derive(Deserialize)]
+enum HistoryId {
+    Request(IdOnly),
+    Response(IdOnly),
+}
+
+/* Description:
+ *      Inventory query from history ui. If host is set, only hosts
+ *      containing it, ignoring case, are returned.
+ *
+ * Format:
+ *      [1, {"operation": {"Inventory": {"host": "example"}}}]
+ */
+
+#[derive(Debug, Default, Deserialize)]
+pub struct InventoryQuery {
+    #[serde(default)]
+    pub host: Option<String>,
+}
+
+// Parameter seen in the history with sample values and endpoints
+#[derive(Debug, Serialize)]
+pub struct ParamEntry {
+    name: String,
+    location: Location,
+    samples: Vec<String>,
+    endpoints: BTreeSet<String>,
+    count: usize,
+}
+
+impl ParamEntry {
+    fn new(name: String, location: Location) -> Self {
+        ParamEntry {
+            name,
+            location,
+            samples: Vec::new(),
+            endpoints: BTreeSet::new(),
+            count: 0,
+        }
+    }
+
+    // Add endpoint and sample, samples are unique and truncated
+    fn add(&mut self, value: &str, endpoint: &str) {
+        self.count += 1;
+        if !self.endpoints.contains(endpoint) {
+            self.endpoints
+                .insert(endpoint.to_string());
+        }
+        if self.samples.len() < MAX_SAMPLES && !value.is_empty() {
+            let sample = truncate(value);
+            if !self.samples.iter().any(|s| s == sample) {
+                self.samples.push(sample.to_string());
+            }
+        }
+    }
+}
+
+// First MAX_SAMPLE_LEN characters of value
+fn truncate(value: &str) -> &str {
+    match value.char_indices().nth(MAX_SAMPLE_LEN) {
+        Some((pos, _)) => &value[..pos],
+        None => value,
+    }
+}
+
+// Param with value truncated to MAX_SAMPLE_LEN
+fn truncate_value(mut param: Param) -> Param {
+    let len = truncate(&param.value).len();
+    param.value.truncate(len);
+    param
+}
+
+type HostParams = BTreeMap<(String, Location), ParamEntry>;
+
+// Params of a parsed id with the stamps of its files, kept to rebuild the
+// inventory when a file changes. Values are truncated to MAX_SAMPLE_LEN.
+#[derive(Debug)]
+struct ParsedEntry {
+    host: String,
+    endpoint: String,
+    params: Vec<Param>,
+    cookies: Vec<Param>,
+    req: FileStamp,
+    res: Option<FileStamp>,
+}
+
+/* Description:
+ *      Per host inventory of the parameters in the http history.
+ *
+ *      Like the search index, ids are marked when the history json is
+ *      received and the files are parsed lazily on the next query. Request
+ *      and response of an id are parsed when their file is present and not
+ *      written while being read.
+ *
+ *      Params of each parsed id are kept with the stamps of its files. If
+ *      a parsed file changed, e.g. it was partially written, it is parsed
+ *      again and the inventory is rebuilt from the parsed ids.
+ *
+ *      On the first query the history directory is scanned to add the
+ *      entries of an attached session.
+ */
+
+#[derive(Debug)]
+pub struct ParamInventory {
+    root: PathBuf,
+    hosts: BTreeMap<String, HostParams>,
+    dirty_req: BTreeSet<usize>,
+    dirty_res: BTreeSet<usize>,
+    parsed: BTreeMap<usize, ParsedEntry>,
+    scanned: bool,
+}
+
+impl Default for ParamInventory {
+    fn default() -> Self {
+        Self::new(PathBuf::from(HISTORY))
+    }
+}
+
+impl ParamInventory {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            hosts: BTreeMap::new(),
+            dirty_req: BTreeSet::new(),
+            dirty_res: BTreeSet::new(),
+            parsed: BTreeMap::new(),
+            scanned: false,
+        }
+    }
+
+    // Mark the id in history json to be parsed
+    pub fn mark(&mut self, data: &str) {
+        match serde_json::from_str::<HistoryId>(data) {
+            Ok(HistoryId::Request(his)) => {
+                self.dirty_req.insert(his.id);
+                self.dirty_res.insert(his.id);
+            }
+            Ok(HistoryId::Response(his)) => {
+                self.dirty_res.insert(his.id);
+            }
+            Err(e) => error!("inventory mark| {}| {}", data, e),
+        }
+    }
+
+    fn add(&mut self, host: &str, endpoint: &str, params: &[Param]) {
+        let entries = self
+            .hosts
+            .entry(host.to_string())
+            .or_default();
+        for param in params {
+            entries
+                .entry((param.name.clone(), param.location))
+                .or_insert_with(|| {
+                    ParamEntry::new(param.name.clone(), param.location)
+                })
+                .add(&param.value, endpoint);
+        }
+    }
+
+    // Clear the hosts and add the params of every parsed id
+    fn rebuild(&mut self) {
+        self.hosts.clear();
+        let parsed = std::mem::take(&mut self.parsed);
+        for entry in parsed.values() {
+            self.add(&entry.host, &entry.endpoint, &entry.params);
+            self.add(&entry.host, &entry.endpoint, &entry.cookies);
+        }
+        self.parsed = parsed;
+    }
+
+    fn file(&self, id: usize, ext: &str) -> PathBuf {
+        self.root
+            .join(id.to_string())
+            .join(format!("{id}.{ext}"))
+    }
+
+    /* Steps:
+     *      1. If not scanned, mark every numeric directory in root.
+     *
+     *      2. Mark the parsed files whose stamp changed as dirty.
+     *
+     *      3. For each dirty request, read the request file. If missing or
+     *         changed while reading, keep it dirty. Host is from xattrs, else
+     *         from Host header. Add the params of a new id, or replace the
+     *         params of a parsed id.
+     *
+     *      4. For each dirty response whose request is parsed, read the
+     *         response file. If missing or changed while reading, keep it
+     *         dirty. Add the Set-Cookie params of a new response, or replace
+     *         the params of a parsed response.
+     *
+     *      5. If params of a parsed id were replaced or removed, rebuild.
+     */
+
+    pub async fn update(&mut self) {
+        // 1. Scan
+        if !self.scanned {
+            self.scanned = true;
+            match tokio::fs::read_dir(&self.root).await {
+                Ok(mut dir) => {
+                    while let Ok(Some(entry)) = dir.next_entry().await {
+                        if let Some(id) = entry
+                            .file_name()
+                            .to_str()
+                            .and_then(|name| name.parse::<usize>().ok())
+                        {
+                            self.dirty_req.insert(id);
+                            self.dirty_res.insert(id);
+                        }
+                    }
+                }
+                Err(e) => trace!("inventory scan| {}", e),
+            }
+        }
+
+        // 2. Changed
+        let mut files = Vec::new();
+        for (id, entry) in &self.parsed {
+            files.push(((*id, true), self.file(*id, "req"), entry.req));
+            if let Some(stamp) = entry.res {
+                files.push(((*id, false), self.file(*id, "res"), stamp));
+            }
+        }
+        for (id, is_req) in changed(files).await {
+            if is_req {
+                self.dirty_req.insert(id);
+            } else {
+                self.dirty_res.insert(id);
+            }
+        }
+
+        // 3. Request
+        let mut rebuild = false;
+        let dirty = std::mem::take(&mut self.dirty_req);
+        for id in dirty {
+            let path = self.file(id, "req");
+            let Some((data, stamp)) = read_stable(&path).await else {
+                self.dirty_req.insert(id);
+                continue;
+            };
+            let host = get_attr(&path)
+                .ok()
+                .map(|info| info.host);
+            let Some((req, host)) = parse_request(&HttpMessage::parse(&data))
+                .and_then(|req| {
+                    let host = host.or_else(|| req.host.clone())?;
+                    Some((req, host))
+                })
+            else {
+                rebuild |= self.parsed.remove(&id).is_some();
+                self.dirty_res.remove(&id);
+                continue;
+            };
+            let params: Vec<Param> = req
+                .params
+                .into_iter()
+                .map(truncate_value)
+                .collect();
+            match self.parsed.get_mut(&id) {
+                Some(entry) => {
+                    entry.host = host;
+                    entry.endpoint = req.endpoint;
+                    entry.params = params;
+                    entry.req = stamp;
+                    rebuild = true;
+                }
+                None => {
+                    self.add(&host, &req.endpoint, &params);
+                    let entry = ParsedEntry {
+                        host,
+                        endpoint: req.endpoint,
+                        params,
+                        cookies: Vec::new(),
+                        req: stamp,
+                        res: None,
+                    };
+                    self.parsed.insert(id, entry);
+                }
+            }
+        }
+
+        // 4. Response
+        let dirty = std::mem::take(&mut self.dirty_res);
+        for id in dirty {
+            if self.dirty_req.contains(&id) {
+                self.dirty_res.insert(id);
+                continue;
+            }
+            if !self.parsed.contains_key(&id) {
+                continue;
+            }
+            let Some((data, stamp)) = read_stable(&self.file(id, "res")).await
+            else {
+                self.dirty_res.insert(id);
+                continue;
+            };
+            let cookies: Vec<Param> = parse_set_cookies(&data)
+                .into_iter()
+                .map(truncate_value)
+                .collect();
+            let Some(mut entry) = self.parsed.remove(&id) else {
+                continue;
+            };
+            if entry.res.replace(stamp).is_some() {
+                rebuild = true;
+            } else {
+                self.add(&entry.host, &entry.endpoint, &cookies);
+            }
+            entry.cookies = cookies;
+            self.parsed.insert(id, entry);
+        }
+
+        // 5. Rebuild
+        if rebuild {
+            self.rebuild();
+        }
+        trace!("inventory| {}", self.hosts.len());
+    }
+
+    // Params of hosts matching the query, sorted by name and location
+    pub fn query(
+        &self,
+        query: &InventoryQuery,
+    ) -> BTreeMap<&str, Vec<&ParamEntry>> {
+        let filter = query
+            .host
+            .as_ref()
+            .map(|host| host.to_ascii_lowercase());
+        self.hosts
+            .iter()
+            .filter(|(host, _)| {
+                filter.as_ref().is_none_or(|filter| {
+                    host.to_ascii_lowercase()
+                        .contains(filter.as_str())
+                })
+            })
+            .map(|(host, params)| (host.as_str(), params.values().collect()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    const REQ: &str = "GET /a?id=1 HTTP/1.1\r\nHost: www.example.com\r\nCookie: sid=x\r\n\r\n";
+    const RES: &str = "HTTP/1.1 200 OK\r\nSet-Cookie: sid=y; Path=/\r\n\r\n";
+
+    fn write(root: &Path, id: usize, ext: &str, data: &str) {
+        let dir = root.join(id.to_string());
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(format!("{id}.{ext}")), data).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_inventory_update() {
+        let root = PathBuf::from("/tmp/zxc_inventory");
+        let _ = fs::remove_dir_all(&root);
+        write(&root, 1, "req", REQ);
+        write(
+            &root,
+            2,
+            "req",
+            "GET /a?id=2&q=3 HTTP/1.1\r\nHost: www.example.com\r\n\r\n",
+        );
+
+        let mut inventory = ParamInventory::new(root.clone());
+        inventory.update().await;
+        // response not written, request parsed once
+        inventory.update().await;
+        write(&root, 1, "res", RES);
+        inventory.mark(r#"{"Response":{"id":1,"status":"200","length":0}}"#);
+        inventory.update().await;
+
+        let result = inventory.query(&InventoryQuery::default());
+        let params = &result["www.example.com"];
+        let names: Vec<(&str, Location, usize)> = params
+            .iter()
+            .map(|p| (p.name.as_str(), p.location, p.count))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("id", Location::Query, 2),
+                ("q", Location::Query, 1),
+                ("sid", Location::Cookie, 1),
+                ("sid", Location::SetCookie, 1)
+            ]
+        );
+        assert_eq!(params[0].samples, vec!["1", "2"]);
+        assert_eq!(
+            params[0]
+                .endpoints
+                .iter()
+                .next()
+                .unwrap(),
+            "GET /a"
+        );
+
+        // changed request replaces its params
+        write(
+            &root,
+            2,
+            "req",
+            "GET /b?zz=1 HTTP/1.1\r\nHost: www.example.com\r\n\r\n",
+        );
+        inventory.update().await;
+        let result = inventory.query(&InventoryQuery::default());
+        let names: Vec<(&str, usize)> = result["www.example.com"]
+            .iter()
+            .map(|p| (p.name.as_str(), p.count))
+            .collect();
+        assert_eq!(names, vec![("id", 1), ("sid", 1), ("sid", 1), ("zz", 1)]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_inventory_query_host() {
+        let mut inventory = ParamInventory::default();
+        inventory.add(
+            "a.com",
+            "GET /",
+            &[Param {
+                name: "x".to_string(),
+                location: Location::Query,
+                value: "a".repeat(100),
+            }],
+        );
+        inventory.add("b.com", "GET /", &[]);
+        let query = InventoryQuery {
+            host: Some("A.C".to_string()),
+        };
+        let result = inventory.query(&query);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result["a.com"][0].samples[0].len(), MAX_SAMPLE_LEN);
+    }
+}
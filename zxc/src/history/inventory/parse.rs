@@ -0,0 +1,315 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::history::har::types::Header;
+use crate::history::http::HttpMessage;
+use crate::history::sitemap::split_uri;
+
+const CONTENT_TYPE: &str = "content-type";
+const COOKIE: &str = "cookie";
+const SET_COOKIE: &str = "set-cookie";
+const HOST: &str = "host";
+const URLENCODED: &str = "application/x-www-form-urlencoded";
+const MULTIPART: &str = "multipart/form-data";
+const JSON: &str = "json";
+
+// Location of a parameter in the http message
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Location {
+    Query,
+    Body,
+    Json,
+    Multipart,
+    Cookie,
+    SetCookie,
+    Header,
+}
+
+impl Location {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Location::Query => "query",
+            Location::Body => "body",
+            Location::Json => "json",
+            Location::Multipart => "multipart",
+            Location::Cookie => "cookie",
+            Location::SetCookie => "setcookie",
+            Location::Header => "header",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub location: Location,
+    pub value: String,
+}
+
+impl Param {
+    fn new(name: &str, location: Location, value: &str) -> Self {
+        Param {
+            name: name.to_string(),
+            location,
+            value: value.to_string(),
+        }
+    }
+}
+
+// Parameters of a request along with the endpoint
+#[derive(Debug, PartialEq)]
+pub struct ParsedRequest {
+    pub host: Option<String>,
+    pub endpoint: String,
+    pub params: Vec<Param>,
+}
+
+// name=value pairs separated by ;
+fn cookie_pairs(value: &str) -> impl Iterator<Item = (&str, &str)> {
+    value.split(';').filter_map(|pair| {
+        let pair = pair.trim();
+        (!pair.is_empty()).then(|| {
+            pair.split_once('=')
+                .unwrap_or((pair, ""))
+        })
+    })
+}
+
+/* Steps:
+ *      1. Endpoint is method and path of the request line.
+ *      2. Query parameters from uri.
+ *      3. Cookie header pairs and Host header.
+ *      4. Body parameters based on content type,
+ *          a. urlencoded   => form pairs
+ *          b. multipart    => part names
+ *          c. json         => flattened keys [ json_params() ]
+ */
+
+pub fn parse_request(msg: &HttpMessage) -> Option<ParsedRequest> {
+    // 1. Endpoint
+    if msg.first.is_empty() || msg.second.is_empty() {
+        return None;
+    }
+    let (path, query) = split_uri(&msg.second);
+    let endpoint = format!("{} {path}", msg.first);
+
+    // 2. Query
+    let mut params: Vec<Param> = query
+        .map(|query| form_params(query.as_bytes(), Location::Query))
+        .unwrap_or_default();
+
+    // 3. Headers
+    let mut host = None;
+    let mut content_type = None;
+    for Header {
+        name,
+        value,
+    } in msg.headers.iter()
+    {
+        if name.eq_ignore_ascii_case(COOKIE) {
+            params.extend(cookie_pairs(value).map(|(name, value)| {
+                Param::new(name, Location::Cookie, value)
+            }));
+        } else if name.eq_ignore_ascii_case(HOST) {
+            host = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case(CONTENT_TYPE) {
+            content_type = Some(value);
+        }
+    }
+
+    // 4. Body
+    let body = msg.body.as_slice();
+    if let Some(content_type) = content_type
+        && !body.is_empty()
+    {
+        // boundary is case sensitive
+        let mime = content_type.to_ascii_lowercase();
+        if mime.starts_with(URLENCODED) {
+            params.extend(form_params(body, Location::Body));
+        } else if mime.starts_with(MULTIPART) {
+            if let Some(boundary) = content_type
+                .split(';')
+                .find_map(|part| part.trim().strip_prefix("boundary="))
+            {
+                multipart_params(
+                    body,
+                    boundary.trim_matches('"'),
+                    &mut params,
+                );
+            }
+        } else if mime.contains(JSON)
+            && let Ok(value) = serde_json::from_slice::<Value>(body)
+        {
+            json_params("", &value, &mut params);
+        }
+    }
+    Some(ParsedRequest {
+        host,
+        endpoint,
+        params,
+    })
+}
+
+// Cookie name and value from Set-Cookie headers of response
+pub fn parse_set_cookies(data: &[u8]) -> Vec<Param> {
+    HttpMessage::parse(data)
+        .headers
+        .iter()
+        .filter(|header| {
+            header
+                .name
+                .eq_ignore_ascii_case(SET_COOKIE)
+        })
+        .filter_map(|header| {
+            let (name, value) = cookie_pairs(&header.value).next()?;
+            Some(Param::new(name, Location::SetCookie, value))
+        })
+        .collect()
+}
+
+fn form_params(data: &[u8], location: Location) -> Vec<Param> {
+    form_urlencoded::parse(data)
+        .filter(|(name, _)| !name.is_empty())
+        .map(|(name, value)| Param::new(&name, location, &value))
+        .collect()
+}
+
+/* Description:
+ *      Flatten json into dotted key names, array items are named with [].
+ *      Only scalar values are added.
+ *
+ *      {"user": {"name": "a"}, "ids": [1, 2]} => user.name, ids[]
+ */
+
+fn json_params(prefix: &str, value: &Value, out: &mut Vec<Param>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter() {
+                let name = if prefix.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                json_params(&name, value, out);
+            }
+        }
+        Value::Array(items) => {
+            let name = format!("{prefix}[]");
+            for item in items.iter() {
+                json_params(&name, item, out);
+            }
+        }
+        Value::String(value) if !prefix.is_empty() => {
+            out.push(Param::new(prefix, Location::Json, value))
+        }
+        value if !prefix.is_empty() => {
+            out.push(Param::new(prefix, Location::Json, &value.to_string()))
+        }
+        _ => (),
+    }
+}
+
+/* Steps:
+ *      1. Split body at "--boundary", stop at closing "--boundary--"
+ *      2. Split each part into headers and value.
+ *      3. Name from Content-Disposition, value is the filename for file
+ *         parts.
+ */
+
+fn multipart_params(body: &[u8], boundary: &str, out: &mut Vec<Param>) {
+    let body = String::from_utf8_lossy(body);
+    let delimiter = format!("--{boundary}");
+    for part in body.split(&delimiter).skip(1) {
+        if part.starts_with("--") {
+            break;
+        }
+        let part = part
+            .strip_prefix("\r\n")
+            .unwrap_or(part);
+        let Some((head, value)) = part.split_once("\r\n\r\n") else {
+            continue;
+        };
+        let Some(disposition) = head.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case("content-disposition")
+                .then_some(value)
+        }) else {
+            continue;
+        };
+        let field = |key: &str| {
+            disposition
+                .split(';')
+                .find_map(|param| {
+                    let (k, v) = param.trim().split_once('=')?;
+                    (k == key).then(|| v.trim_matches('"').to_string())
+                })
+        };
+        if let Some(name) = field("name") {
+            let value = field("filename").unwrap_or_else(|| {
+                value
+                    .strip_suffix("\r\n")
+                    .unwrap_or(value)
+                    .to_string()
+            });
+            out.push(Param::new(&name, Location::Multipart, &value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_urlencoded() {
+        let data = b"POST /login?next=%2Fhome HTTP/1.1\r\nHost: a.com\r\nCookie: sid=1; theme=dark\r\nContent-Type: application/x-www-form-urlencoded\r\n\r\nuser=admin&pass=x";
+        let verify = ParsedRequest {
+            host: Some("a.com".to_string()),
+            endpoint: "POST /login".to_string(),
+            params: vec![
+                Param::new("next", Location::Query, "/home"),
+                Param::new("sid", Location::Cookie, "1"),
+                Param::new("theme", Location::Cookie, "dark"),
+                Param::new("user", Location::Body, "admin"),
+                Param::new("pass", Location::Body, "x"),
+            ],
+        };
+        assert_eq!(parse_request(&HttpMessage::parse(data)), Some(verify));
+    }
+
+    #[test]
+    fn test_parse_request_json() {
+        let data = b"PUT /api HTTP/1.1\r\nContent-Type: application/json\r\n\r\n{\"user\":{\"name\":\"a\",\"admin\":false},\"ids\":[1,2]}";
+        let req = parse_request(&HttpMessage::parse(data)).unwrap();
+        let verify = vec![
+            Param::new("ids[]", Location::Json, "1"),
+            Param::new("ids[]", Location::Json, "2"),
+            Param::new("user.admin", Location::Json, "false"),
+            Param::new("user.name", Location::Json, "a"),
+        ];
+        assert_eq!(req.params, verify);
+    }
+
+    #[test]
+    fn test_parse_request_multipart() {
+        let data = b"POST /upload HTTP/1.1\r\nContent-Type: multipart/form-data; boundary=XyZ\r\n\r\n--XyZ\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nhello\r\n--XyZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.png\"\r\nContent-Type: image/png\r\n\r\n\x89PNG\r\n--XyZ--\r\n";
+        let req = parse_request(&HttpMessage::parse(data)).unwrap();
+        let verify = vec![
+            Param::new("title", Location::Multipart, "hello"),
+            Param::new("file", Location::Multipart, "a.png"),
+        ];
+        assert_eq!(req.params, verify);
+    }
+
+    #[test]
+    fn test_parse_set_cookies() {
+        let data = b"HTTP/1.1 200 OK\r\nSet-Cookie: sid=abc; HttpOnly\r\nset-cookie: csrf=1\r\n\r\nbody";
+        let verify = vec![
+            Param::new("sid", Location::SetCookie, "abc"),
+            Param::new("csrf", Location::SetCookie, "1"),
+        ];
+        assert_eq!(parse_set_cookies(data), verify);
+    }
+}
@@ -1,9 +1,19 @@
 use super::ws_register::HistoryWsRegisterInfo;
+use crate::config::DomainFilter;
+use crate::history::sitemap::links::Link;
 
 // Messages from commander to history
 pub enum CommanderToHistory {
     // From Commander - http
     Http(String),
+    // From Commander - reply to ui operation, sent as is
+    Reply(String),
+    // From Commander - domain filter of config, on start and reload
+    Scope(Option<DomainFilter>),
+    // From Analyzer - links discovered in the response from host
+    Links(String, Vec<Link>),
+    // From Analyzer - findings json line of the response
+    Findings(String),
     // From Soldiers - ws
     RegisterWs(HistoryWsRegisterInfo),
     WebSocket(usize, String),
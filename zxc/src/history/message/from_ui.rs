@@ -1,20 +1,38 @@
+use std::path::PathBuf;
+
 use serde::Deserialize;
 
+use crate::commander::codegen::CopyAsInfo;
+use crate::commander::compare::CompareInfo;
 use crate::forward_info::ForwardInfo;
+use crate::history::annotation::AnnotateInfo;
+use crate::history::import::ImportInfo;
+use crate::history::inventory::InventoryQuery;
+use crate::history::search::SearchQuery;
+use crate::history::sitemap::SitemapInfo;
 
 // Messages from history UI
 #[derive(Debug, Deserialize)]
 pub struct HistoryUImsg {
-    _id: u8,
+    pub id: usize,
     pub operation: HistoryUIOps,
 }
 
 // History UI Operations
 #[derive(Deserialize, Debug)]
 pub enum HistoryUIOps {
+    Annotate(AnnotateInfo),
     Close,
+    Compare(CompareInfo),
+    CopyAs(CopyAsInfo),
+    ExportHar(PathBuf),
     Forward(ForwardInfo),
+    Import(ImportInfo),
+    Inventory(InventoryQuery),
+    Materialize(usize),
     ReloadConfig,
+    Search(SearchQuery),
+    Sitemap(SitemapInfo),
 }
 
 impl From<HistoryUImsg> for HistoryUIOps {
@@ -5,7 +5,7 @@ use crate::proxy::server_info::ServerInfo;
 // Ws Register
 pub struct HistoryWsRegisterInfo {
     pub id: usize,
-    http_id: usize,
+    pub http_id: usize,
     pub path: PathBuf,
     server_info: ServerInfo,
 }
@@ -25,6 +25,10 @@ impl HistoryWsRegisterInfo {
         }
     }
 
+    pub fn server_info(&self) -> &ServerInfo {
+        &self.server_info
+    }
+
     pub fn log_data(&self) -> String {
         format!(
             "{0} | {1} | {2}\n",
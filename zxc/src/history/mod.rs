@@ -1,4 +1,15 @@
+pub mod analyzer;
+pub mod annotation;
+pub mod db;
 mod error;
 pub mod handler;
+pub mod har;
+pub mod http;
+pub mod import;
+pub mod inventory;
 pub mod message;
+pub mod passive;
+pub mod search;
+pub mod sitemap;
+mod stamp;
 pub mod wshistory;
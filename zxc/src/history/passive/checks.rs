@@ -0,0 +1,419 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::history::http::{HttpMessage, header_value};
+
+// 180 days
+const MIN_HSTS_AGE: u64 = 15_552_000;
+// Only the first 1MB of a body is checked
+pub const MAX_BODY: usize = 1024 * 1024;
+const MAX_MATCHES: usize = 5;
+const MAX_DETAIL: usize = 120;
+
+const HTML: &str = "text/html";
+const HSTS: &str = "Strict-Transport-Security";
+const CSP: &str = "Content-Security-Policy";
+const XFO: &str = "X-Frame-Options";
+const XCTO: &str = "X-Content-Type-Options";
+const BANNERS: [&str; 3] =
+    ["X-Powered-By", "X-AspNet-Version", "X-AspNetMvc-Version"];
+
+const MIXED_CONTENT: &str = r#"(?i)<(?:script|iframe|img|link|form|embed|object|audio|video|source)\b[^>]*?\b(?:src|href|action|data)\s*=\s*["']?(http://[^"'\s>]+)"#;
+const STACK_TRACE: &str = concat!(
+    r"Traceback \(most recent call last\)",
+    r"|\bat [\w$.<>]+\([\w$]+\.java:\d+\)",
+    r"|\bat [\w.<>`]+\(.*\) in .+:line \d+",
+    r"|\bat .+ \(.+\.js:\d+:\d+\)",
+    r"|PHP (?:Fatal|Parse) error:",
+    r"|(?:Fatal error|Parse error|Warning)</b>:.+ on line <b>\d+",
+    r"|\.rb:\d+:in `",
+);
+const DIR_LISTING: &str =
+    r"(?i)<title>\s*(?:Index of /|Directory listing for /)[^<]*";
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    MissingHeader,
+    WeakHeader,
+    InsecureCookie,
+    MixedContent,
+    ServerBanner,
+    StackTrace,
+    DirectoryListing,
+    CorsCredentials,
+    Secret,
+    Reflected,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Issue {
+    pub check: Check,
+    pub severity: Severity,
+    pub detail: String,
+    pub location: Option<String>,
+}
+
+impl Issue {
+    pub fn new(check: Check, severity: Severity, detail: &str) -> Self {
+        let detail = match detail.char_indices().nth(MAX_DETAIL) {
+            Some((pos, _)) => &detail[..pos],
+            None => detail,
+        };
+        Issue {
+            check,
+            severity,
+            detail: detail.to_string(),
+            location: None,
+        }
+    }
+
+    pub fn with_location(mut self, location: String) -> Self {
+        self.location = Some(location);
+        self
+    }
+}
+
+// Compiled patterns of the body checks
+pub struct Checks {
+    mixed_content: Regex,
+    stack_trace: Regex,
+    dir_listing: Regex,
+}
+
+impl Default for Checks {
+    fn default() -> Self {
+        // safe to unwrap, constant patterns
+        Self {
+            mixed_content: Regex::new(MIXED_CONTENT).unwrap(),
+            stack_trace: Regex::new(STACK_TRACE).unwrap(),
+            dir_listing: Regex::new(DIR_LISTING).unwrap(),
+        }
+    }
+}
+
+impl Checks {
+    /* Steps:
+     *      1. Security headers    [ headers() ]
+     *      2. Set-Cookie flags    [ cookies() ]
+     *      3. Server banners      [ banners() ]
+     *      4. CORS                [ cors() ]
+     *      5. Body checks, stack traces and directory listing on all text
+     *         bodies, mixed content on https html.
+     */
+
+    pub fn run(&self, tls: bool, res: &HttpMessage) -> Vec<Issue> {
+        let html =
+            header_value(&res.headers, "content-type").is_some_and(|value| {
+                value
+                    .to_ascii_lowercase()
+                    .contains(HTML)
+            });
+        let mut issues = Vec::new();
+        headers(tls, html, res, &mut issues);
+        cookies(tls, res, &mut issues);
+        banners(res, &mut issues);
+        cors(res, &mut issues);
+
+        // 5. Body
+        let body = &res.body[..res.body.len().min(MAX_BODY)];
+        let body = String::from_utf8_lossy(body);
+        if let Some(m) = self.stack_trace.find(&body) {
+            issues.push(Issue::new(
+                Check::StackTrace,
+                Severity::Medium,
+                m.as_str(),
+            ));
+        }
+        if let Some(m) = self.dir_listing.find(&body) {
+            issues.push(Issue::new(
+                Check::DirectoryListing,
+                Severity::Low,
+                m.as_str().trim(),
+            ));
+        }
+        if tls && html {
+            let mut urls: Vec<&str> = Vec::new();
+            for url in self
+                .mixed_content
+                .captures_iter(&body)
+                .filter_map(|caps| caps.get(1))
+            {
+                if !urls.contains(&url.as_str()) {
+                    urls.push(url.as_str());
+                }
+                if urls.len() == MAX_MATCHES {
+                    break;
+                }
+            }
+            issues.extend(urls.into_iter().map(|url| {
+                Issue::new(Check::MixedContent, Severity::Medium, url)
+            }));
+        }
+        issues
+    }
+}
+
+/* Steps:
+ *      Missing
+ *          1. Strict-Transport-Security, https only.
+ *          2. Content-Security-Policy, html only.
+ *          3. X-Frame-Options, html without CSP frame-ancestors.
+ *          4. X-Content-Type-Options, if body present.
+ *      Weak
+ *          5. HSTS max-age less than MIN_HSTS_AGE
+ *          6. CSP with 'unsafe-inline' or 'unsafe-eval'
+ *          7. X-Content-Type-Options other than nosniff
+ */
+
+fn headers(tls: bool, html: bool, res: &HttpMessage, issues: &mut Vec<Issue>) {
+    let missing = |name| Issue::new(Check::MissingHeader, Severity::Low, name);
+    let weak = |severity, detail: String| {
+        Issue::new(Check::WeakHeader, severity, &detail)
+    };
+    // 1. HSTS
+    if tls {
+        match header_value(&res.headers, HSTS) {
+            None => issues.push(missing(HSTS)),
+            Some(value) => {
+                let age = value.split(';').find_map(|directive| {
+                    let (key, age) = directive.trim().split_once('=')?;
+                    key.eq_ignore_ascii_case("max-age")
+                        .then(|| {
+                            age.trim_matches('"')
+                                .parse::<u64>()
+                                .ok()
+                        })?
+                });
+                if age.is_none_or(|age| age < MIN_HSTS_AGE) {
+                    issues
+                        .push(weak(Severity::Low, format!("{HSTS}: {value}")));
+                }
+            }
+        }
+    }
+    // 2. CSP
+    let csp = header_value(&res.headers, CSP);
+    match csp {
+        None if html => issues.push(missing(CSP)),
+        Some(value) => {
+            for keyword in ["'unsafe-inline'", "'unsafe-eval'"] {
+                if value.contains(keyword) {
+                    issues.push(weak(
+                        Severity::Low,
+                        format!("{CSP}: {keyword}"),
+                    ));
+                }
+            }
+        }
+        None => (),
+    }
+    // 3. XFO
+    if html
+        && header_value(&res.headers, XFO).is_none()
+        && !csp.is_some_and(|value| value.contains("frame-ancestors"))
+    {
+        issues.push(missing(XFO));
+    }
+    // 4. XCTO
+    match header_value(&res.headers, XCTO) {
+        None if !res.body.is_empty() => {
+            issues.push(Issue::new(Check::MissingHeader, Severity::Info, XCTO))
+        }
+        Some(value) if !value.eq_ignore_ascii_case("nosniff") => {
+            issues.push(weak(Severity::Info, format!("{XCTO}: {value}")))
+        }
+        _ => (),
+    }
+}
+
+/* Description:
+ *      Set-Cookie without Secure (https only), HttpOnly or SameSite. Missing
+ *      Secure or HttpOnly is low, else info.
+ *
+ * Format:
+ *      sid missing Secure, HttpOnly
+ */
+
+fn cookies(tls: bool, res: &HttpMessage, issues: &mut Vec<Issue>) {
+    for header in res.headers.iter().filter(|header| {
+        header
+            .name
+            .eq_ignore_ascii_case("set-cookie")
+    }) {
+        let mut parts = header.value.split(';');
+        let name = parts
+            .next()
+            .and_then(|pair| pair.split_once('='))
+            .map_or("", |(name, _)| name.trim());
+        let attrs: Vec<String> = parts
+            .map(|attr| {
+                attr.split('=')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_ascii_lowercase()
+            })
+            .collect();
+        let has = |attr: &str| attrs.iter().any(|a| a == attr);
+        let mut missing = Vec::new();
+        if tls && !has("secure") {
+            missing.push("Secure");
+        }
+        if !has("httponly") {
+            missing.push("HttpOnly");
+        }
+        let severity = if missing.is_empty() {
+            Severity::Info
+        } else {
+            Severity::Low
+        };
+        if !has("samesite") {
+            missing.push("SameSite");
+        }
+        if !missing.is_empty() {
+            let detail = format!("{name} missing {}", missing.join(", "));
+            issues.push(Issue::new(Check::InsecureCookie, severity, &detail));
+        }
+    }
+}
+
+// Server header with version and technology headers
+fn banners(res: &HttpMessage, issues: &mut Vec<Issue>) {
+    if let Some(server) = header_value(&res.headers, "server")
+        && server
+            .chars()
+            .any(|c| c.is_ascii_digit())
+    {
+        let detail = format!("Server: {server}");
+        issues.push(Issue::new(Check::ServerBanner, Severity::Info, &detail));
+    }
+    for name in BANNERS {
+        if let Some(value) = header_value(&res.headers, name) {
+            let detail = format!("{name}: {value}");
+            issues.push(Issue::new(
+                Check::ServerBanner,
+                Severity::Info,
+                &detail,
+            ));
+        }
+    }
+}
+
+// Access-Control-Allow-Origin * or null with credentials
+fn cors(res: &HttpMessage, issues: &mut Vec<Issue>) {
+    let credentials =
+        header_value(&res.headers, "access-control-allow-credentials")
+            .is_some_and(|value| value.eq_ignore_ascii_case("true"));
+    if let Some(origin) =
+        header_value(&res.headers, "access-control-allow-origin")
+        && credentials
+        && (origin == "*" || origin.eq_ignore_ascii_case("null"))
+    {
+        let detail =
+            format!("Access-Control-Allow-Origin: {origin} with credentials");
+        issues.push(Issue::new(
+            Check::CorsCredentials,
+            Severity::Medium,
+            &detail,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(tls: bool, data: &str) -> Vec<Issue> {
+        Checks::default().run(tls, &HttpMessage::parse(data.as_bytes()))
+    }
+
+    fn details(issues: &[Issue]) -> Vec<(Check, Severity, &str)> {
+        issues
+            .iter()
+            .map(|issue| (issue.check, issue.severity, issue.detail.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_checks_headers() {
+        let data = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nStrict-Transport-Security: max-age=300\r\nContent-Security-Policy: script-src 'unsafe-inline'\r\n\r\n<html>";
+        let verify = vec![
+            (
+                Check::WeakHeader,
+                Severity::Low,
+                "Strict-Transport-Security: max-age=300",
+            ),
+            (
+                Check::WeakHeader,
+                Severity::Low,
+                "Content-Security-Policy: 'unsafe-inline'",
+            ),
+            (Check::MissingHeader, Severity::Low, "X-Frame-Options"),
+            (Check::MissingHeader, Severity::Info, "X-Content-Type-Options"),
+        ];
+        assert_eq!(details(&run(true, data)), verify);
+    }
+
+    #[test]
+    fn test_checks_cookies_banner_cors() {
+        let data = "HTTP/1.1 200 OK\r\nSet-Cookie: sid=1; Path=/; SameSite=Lax\r\nSet-Cookie: ok=1; Secure; HttpOnly\r\nServer: nginx/1.18.0\r\nX-Powered-By: PHP/7.4\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Credentials: true\r\nX-Content-Type-Options: nosniff\r\n\r\n";
+        let verify = vec![
+            (
+                Check::InsecureCookie,
+                Severity::Low,
+                "sid missing Secure, HttpOnly",
+            ),
+            (Check::InsecureCookie, Severity::Info, "ok missing SameSite"),
+            (Check::ServerBanner, Severity::Info, "Server: nginx/1.18.0"),
+            (Check::ServerBanner, Severity::Info, "X-Powered-By: PHP/7.4"),
+            (
+                Check::CorsCredentials,
+                Severity::Medium,
+                "Access-Control-Allow-Origin: * with credentials",
+            ),
+        ];
+        assert_eq!(details(&run(true, data)[1..]), verify);
+    }
+
+    #[test]
+    fn test_checks_body() {
+        let data = "HTTP/1.1 500 Error\r\nContent-Type: text/html\r\nContent-Security-Policy: frame-ancestors 'none'\r\nX-Content-Type-Options: nosniff\r\n\r\n<title>Index of /files</title>\n<script src=\"http://cdn.example.com/a.js\"></script>\nTraceback (most recent call last):\n";
+        let verify = vec![
+            (
+                Check::StackTrace,
+                Severity::Medium,
+                "Traceback (most recent call last)",
+            ),
+            (Check::DirectoryListing, Severity::Low, "<title>Index of /files"),
+            (
+                Check::MixedContent,
+                Severity::Medium,
+                "http://cdn.example.com/a.js",
+            ),
+        ];
+        assert_eq!(details(&run(true, data)[1..]), verify);
+        // no hsts and mixed content on http
+        assert!(run(false, data).len() == 2);
+    }
+}
@@ -0,0 +1,249 @@
+mod checks;
+mod reflect;
+mod secrets;
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, BufReader, ErrorKind};
+use std::path::PathBuf;
+
+use checks::{Check, Checks, Severity};
+use secrets::Secrets;
+use serde::{Deserialize, Serialize};
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+use tracing::error;
+
+use super::http::HttpMessage;
+use super::sitemap::split_uri;
+
+pub const HISTORY_FINDINGS: &str = "./findings.jsonl";
+
+// Finding of a passive check
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Finding {
+    id: usize,
+    host: String,
+    path: String,
+    check: Check,
+    severity: Severity,
+    detail: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+}
+
+impl Finding {
+    fn key(&self) -> FindingKey {
+        (self.host.clone(), self.path.clone(), self.check, self.detail.clone())
+    }
+}
+
+type FindingKey = (String, String, Check, String);
+
+#[derive(Serialize)]
+enum FindingsLine<'a> {
+    Findings(&'a [Finding]),
+}
+
+/* Description:
+ *      Passive analysis of the logged http responses.
+ *
+ *      Checks and secret detection [ Secrets ] are run in the analyzer
+ *      task, off the proxy flow and the history task.
+ *
+ *      Findings are de-duplicated per host, path, check and detail, appended
+ *      to ./findings.jsonl and sent to history ui. Keys of the existing
+ *      findings are loaded from the file, so that an attached session does
+ *      not report them again.
+ */
+
+pub struct Passive {
+    path: PathBuf,
+    checks: Checks,
+    secrets: Secrets,
+    seen: HashSet<FindingKey>,
+}
+
+impl Default for Passive {
+    fn default() -> Self {
+        Self::new(PathBuf::from(HISTORY_FINDINGS))
+    }
+}
+
+impl Passive {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            checks: Checks::default(),
+            secrets: Secrets::default(),
+            seen: HashSet::new(),
+        }
+    }
+
+    // Load keys of findings in file, empty if file is missing
+    pub fn load(path: PathBuf) -> Result<Self, io::Error> {
+        let mut passive = Self::new(path);
+        let file = match std::fs::File::open(&passive.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(passive),
+            Err(e) => return Err(e),
+        };
+        for line in BufReader::new(file).lines() {
+            match serde_json::from_str::<Finding>(&line?) {
+                Ok(finding) => {
+                    passive.seen.insert(finding.key());
+                }
+                Err(e) => error!("load findings| {}", e),
+            }
+        }
+        Ok(passive)
+    }
+
+    // Replace secret patterns from config, invalid patterns are logged
+    pub fn set_secrets(&mut self, patterns: HashMap<String, String>) {
+        for (name, e) in self.secrets.set(patterns) {
+            error!("secret pattern| {}| {}", name, e);
+        }
+    }
+
+    /* Args:
+     *      id          : log id
+     *      (host, tls) : server info of the request
+     *      req / res   : parsed request and response
+     *
+     * Steps:
+     *      1. Run checks [ Checks::run() ], secret detection
+     *         [ Secrets::scan() ] and reflected input detection
+     *         [ reflect::reflections() ] on the response, with the path of
+     *         the request.
+     *
+     *      2. Keep findings not seen before for the host and path.
+     *
+     *      3. Append new findings to file.
+     *
+     * Returns:
+     *      New findings
+     *
+     * Error:
+     *      io::Error   [3]
+     */
+
+    pub async fn analyze(
+        &mut self,
+        id: usize,
+        (host, tls): (&str, bool),
+        req: &HttpMessage,
+        res: &HttpMessage,
+    ) -> Result<Vec<Finding>, io::Error> {
+        // 1. Check
+        let path = split_uri(&req.second).0.to_string();
+        let findings: Vec<Finding> = self
+            .checks
+            .run(tls, res)
+            .into_iter()
+            .chain(self.secrets.scan(&path, res))
+            .chain(reflect::reflections(req, res))
+            .map(|issue| Finding {
+                id,
+                host: host.to_string(),
+                path: path.clone(),
+                check: issue.check,
+                severity: issue.severity,
+                detail: issue.detail,
+                location: issue.location,
+            })
+            // 2. Dedup
+            .filter(|finding| self.seen.insert(finding.key()))
+            .collect();
+
+        // 3. Write
+        if !findings.is_empty() {
+            let mut data = Vec::new();
+            for finding in findings.iter() {
+                serde_json::to_writer(&mut data, finding)?;
+                data.push(b'\n');
+            }
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await?
+                .write_all(&data)
+                .await?;
+        }
+        Ok(findings)
+    }
+}
+
+/* Description:
+ *      History json line of the findings sent to ui.
+ *
+ * Format:
+ *      {"Findings":[{"id":1,"host":"example.com","path":"/","check":
+ *      "missing_header","severity":"low","detail":"X-Frame-Options"}]}
+ */
+
+pub fn findings_line(findings: &[Finding]) -> String {
+    // safe to unwrap, only strings and enums
+    serde_json::to_string(&FindingsLine::Findings(findings)).unwrap()
+}
+
+// Line of the position in body, as location of the finding
+fn line_location(body: &str, pos: usize) -> String {
+    format!("line {}", body[..pos].matches('\n').count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    const REQ: &str = "GET /a?x=1 HTTP/1.1\r\nHost: a.com\r\n\r\n";
+    const RES: &str = "HTTP/1.1 200 OK\r\nServer: Apache/2.4.1\r\nX-Content-Type-Options: nosniff\r\n\r\n";
+
+    #[tokio::test]
+    async fn test_passive_analyze_dedup() {
+        let root = PathBuf::from("/tmp/zxc_passive");
+        let file = root.join("findings.jsonl");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let req = HttpMessage::parse(REQ.as_bytes());
+        let res = HttpMessage::parse(RES.as_bytes());
+        let server = ("a.com", false);
+        let mut passive = Passive::load(file.clone()).unwrap();
+        let findings = passive
+            .analyze(1, server, &req, &res)
+            .await
+            .unwrap();
+        assert_eq!(
+            findings_line(&findings),
+            r#"{"Findings":[{"id":1,"host":"a.com","path":"/a","check":"server_banner","severity":"info","detail":"Server: Apache/2.4.1"}]}"#
+        );
+        assert!(
+            passive
+                .analyze(2, server, &req, &res)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // loaded keys
+        let mut passive = Passive::load(file.clone()).unwrap();
+        assert!(
+            passive
+                .analyze(2, server, &req, &res)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            fs::read_to_string(&file)
+                .unwrap()
+                .lines()
+                .count(),
+            1
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,325 @@
+use std::fmt;
+
+use super::checks::{Check, Issue, MAX_BODY, Severity};
+use super::line_location;
+use crate::history::http::{HttpMessage, header_value};
+use crate::history::inventory::parse::{Location, Param, parse_request};
+
+// Shorter values match by chance
+const MIN_VALUE_LEN: usize = 4;
+const JSON: &str = "json";
+const SET_COOKIE: &str = "set-cookie";
+// Request headers not checked, either parsed as params or rarely reflected
+const SKIP_HEADERS: [&str; 14] = [
+    "host",
+    "cookie",
+    "content-length",
+    "content-type",
+    "connection",
+    "accept",
+    "accept-encoding",
+    "accept-language",
+    "cache-control",
+    "pragma",
+    "te",
+    "upgrade-insecure-requests",
+    "if-none-match",
+    "if-modified-since",
+];
+const SKIP_HEADER_PREFIX: &str = "sec-";
+
+// Form of the value found in the response
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Raw,
+    Url,
+    Html,
+    Js,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Encoding::Raw => write!(f, "raw"),
+            Encoding::Url => write!(f, "url"),
+            Encoding::Html => write!(f, "html"),
+            Encoding::Js => write!(f, "js"),
+        }
+    }
+}
+
+impl Encoding {
+    fn encode(&self, value: &str) -> String {
+        match self {
+            Encoding::Raw => value.to_string(),
+            Encoding::Url => {
+                form_urlencoded::byte_serialize(value.as_bytes()).collect()
+            }
+            Encoding::Html => {
+                let mut out = String::with_capacity(value.len());
+                for c in value.chars() {
+                    match c {
+                        '&' => out.push_str("&amp;"),
+                        '<' => out.push_str("&lt;"),
+                        '>' => out.push_str("&gt;"),
+                        '"' => out.push_str("&quot;"),
+                        '\'' => out.push_str("&#x27;"),
+                        _ => out.push(c),
+                    }
+                }
+                out
+            }
+            Encoding::Js => {
+                let mut out = String::with_capacity(value.len());
+                for c in value.chars() {
+                    match c {
+                        '\\' | '"' | '\'' | '/' => {
+                            out.push('\\');
+                            out.push(c);
+                        }
+                        '<' => out.push_str("\\u003c"),
+                        '>' => out.push_str("\\u003e"),
+                        _ => out.push(c),
+                    }
+                }
+                out
+            }
+        }
+    }
+}
+
+// Context of the reflection in the response
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Context {
+    HtmlText,
+    Attribute,
+    Script,
+    Json,
+    Header,
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Context::HtmlText => write!(f, "html text"),
+            Context::Attribute => write!(f, "attribute"),
+            Context::Script => write!(f, "script"),
+            Context::Json => write!(f, "json"),
+            Context::Header => write!(f, "header"),
+        }
+    }
+}
+
+/* Description:
+ *      Context of the position in html body, using the lowercase body.
+ *
+ *      script      => after an open <script> tag with no </script>
+ *      attribute   => inside a tag, last < is after last >
+ *      html text   => otherwise
+ */
+
+fn html_context(lower: &str, pos: usize) -> Context {
+    let before = &lower[..pos];
+    if let Some(open) = before.rfind("<script")
+        && !before[open..].contains("</script")
+    {
+        return if before[open..].contains('>') {
+            Context::Script
+        } else {
+            Context::Attribute
+        };
+    }
+    match (before.rfind('<'), before.rfind('>')) {
+        (Some(lt), Some(gt)) if lt > gt => Context::Attribute,
+        (Some(_), None) => Context::Attribute,
+        _ => Context::HtmlText,
+    }
+}
+
+// Raw reflection in html is the one of interest for xss triage
+fn severity(encoding: Encoding, context: Context) -> Severity {
+    match (encoding, context) {
+        (
+            Encoding::Raw,
+            Context::HtmlText | Context::Attribute | Context::Script,
+        ) => Severity::Low,
+        _ => Severity::Info,
+    }
+}
+
+// Request headers worth checking for reflection
+fn header_params(req: &HttpMessage) -> impl Iterator<Item = Param> {
+    req.headers
+        .iter()
+        .filter(|header| {
+            let name = header.name.to_ascii_lowercase();
+            !SKIP_HEADERS.contains(&name.as_str())
+                && !name.starts_with(SKIP_HEADER_PREFIX)
+        })
+        .map(|header| Param {
+            name: header.name.clone(),
+            location: Location::Header,
+            value: header.value.clone(),
+        })
+}
+
+/* Steps:
+ *      1. Collect parameters of the request [ parse_request() ] and request
+ *         headers [ header_params() ], skipping values shorter than
+ *         MIN_VALUE_LEN.
+ *
+ *      2. For each parameter and encoding, skipping encodings same as raw,
+ *          a. Response headers, except Set-Cookie for cookie parameters.
+ *          b. First occurrence in the body, context is json if content type
+ *             is json else from html [ html_context() ].
+ *
+ * Format:
+ *      detail      : q (query) reflected raw in script
+ *      location    : line 4 | header Location
+ */
+
+pub fn reflections(req: &HttpMessage, res: &HttpMessage) -> Vec<Issue> {
+    // 1. Params
+    let mut params = parse_request(req)
+        .map(|parsed| parsed.params)
+        .unwrap_or_default();
+    params.extend(header_params(req));
+    params.retain(|param| param.value.len() >= MIN_VALUE_LEN);
+    if params.is_empty() {
+        return Vec::new();
+    }
+
+    let body = &res.body[..res.body.len().min(MAX_BODY)];
+    let body = String::from_utf8_lossy(body);
+    let lower = body.to_ascii_lowercase();
+    let json = header_value(&res.headers, "content-type")
+        .is_some_and(|value| value.contains(JSON));
+
+    let mut issues = Vec::new();
+    // 2. Check
+    for param in params.iter() {
+        let raw = Encoding::Raw.encode(&param.value);
+        for encoding in
+            [Encoding::Raw, Encoding::Url, Encoding::Html, Encoding::Js]
+        {
+            let value = encoding.encode(&param.value);
+            if encoding != Encoding::Raw && value == raw {
+                continue;
+            }
+            let detail = |context: Context| {
+                format!(
+                    "{} ({}) reflected {} in {}",
+                    param.name,
+                    param.location.as_str(),
+                    encoding,
+                    context
+                )
+            };
+            // a. Headers
+            for header in res.headers.iter().filter(|header| {
+                !(param.location == Location::Cookie
+                    && header
+                        .name
+                        .eq_ignore_ascii_case(SET_COOKIE))
+            }) {
+                if header.value.contains(&value) {
+                    issues.push(
+                        Issue::new(
+                            Check::Reflected,
+                            Severity::Info,
+                            &detail(Context::Header),
+                        )
+                        .with_location(format!("header {}", header.name)),
+                    );
+                }
+            }
+            // b. Body
+            if let Some(pos) = body.find(&value) {
+                let context = if json {
+                    Context::Json
+                } else {
+                    html_context(&lower, pos)
+                };
+                issues.push(
+                    Issue::new(
+                        Check::Reflected,
+                        severity(encoding, context),
+                        &detail(context),
+                    )
+                    .with_location(line_location(&body, pos)),
+                );
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reflect(req: &str, res: &str) -> Vec<(String, String)> {
+        reflections(
+            &HttpMessage::parse(req.as_bytes()),
+            &HttpMessage::parse(res.as_bytes()),
+        )
+        .into_iter()
+        .map(|issue| (issue.detail, issue.location.unwrap()))
+        .collect()
+    }
+
+    #[test]
+    fn test_reflect_html_contexts() {
+        let req = "GET /s?q=abc%3Cx%3E&n=1&t=heading HTTP/1.1\r\nHost: a.com\r\nCookie: sid=session1\r\n\r\n";
+        let res = "HTTP/1.1 302 Found\r\nLocation: /r?q=abc%3Cx%3E\r\nSet-Cookie: sid=session1\r\n\r\n<html><title>heading</title>\n<input value=\"abc<x>\">\n<p>abc&lt;x&gt;</p>\n<script>var s = \"session1\";</script>";
+        let verify = vec![
+            (
+                "q (query) reflected raw in attribute".to_string(),
+                "line 2".to_string(),
+            ),
+            (
+                "q (query) reflected url in header".to_string(),
+                "header Location".to_string(),
+            ),
+            (
+                "q (query) reflected html in html text".to_string(),
+                "line 3".to_string(),
+            ),
+            (
+                "t (query) reflected raw in html text".to_string(),
+                "line 1".to_string(),
+            ),
+            (
+                "sid (cookie) reflected raw in script".to_string(),
+                "line 4".to_string(),
+            ),
+        ];
+        assert_eq!(reflect(req, res), verify);
+    }
+
+    #[test]
+    fn test_reflect_json_and_header() {
+        let req = "POST /api HTTP/1.1\r\nHost: a.com\r\nContent-Type: application/json\r\nX-Trace: trace-42\r\n\r\n{\"user\":{\"name\":\"a/b's\"}}";
+        let res = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nX-Trace: trace-42\r\n\r\n{\"hello\":\"a\\/b\\'s\"}";
+        let verify = vec![
+            (
+                "user.name (json) reflected js in json".to_string(),
+                "line 1".to_string(),
+            ),
+            (
+                "X-Trace (header) reflected raw in header".to_string(),
+                "header X-Trace".to_string(),
+            ),
+        ];
+        assert_eq!(reflect(req, res), verify);
+    }
+
+    #[test]
+    fn test_html_context() {
+        let body = "<a href=\"x\">y</a><script src=\"z\"></script>";
+        assert_eq!(html_context(body, 9), Context::Attribute);
+        assert_eq!(html_context(body, 12), Context::HtmlText);
+        assert_eq!(html_context(body, 25), Context::Attribute);
+        assert_eq!(html_context("<script>var a", 12), Context::Script);
+    }
+}
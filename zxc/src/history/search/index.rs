@@ -0,0 +1,627 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+
+use regex::Regex;
+use serde::Deserialize;
+use tracing::{error, trace};
+
+use super::query::*;
+use crate::history::http::{HttpMessage, header_value, split_head};
+use crate::history::stamp::{FileStamp, changed, read_stable};
+
+const HISTORY_DIR: &str = "./history";
+// Only the first 1MB of a body is indexed and searched
+const MAX_BODY: usize = 1024 * 1024;
+const MAX_SNIPPETS: usize = 3;
+const MAX_RESULTS: usize = 500;
+// Shorter tokens are not indexed
+const MIN_TOKEN: usize = 2;
+
+// Only the id of the history json is needed
+#[derive(Deserialize)]
+struct IdOnly {
+    id: usize,
+}
+
+#[derive(Deserialize)]
+enum HistoryId {
+    Request(IdOnly),
+    Response(IdOnly),
+}
+
+// Filter fields of a log id, parts are read from the files on search
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Default)]
+struct IndexEntry {
+    method: String,
+    host: String,
+    status: String,
+}
+
+impl IndexEntry {
+    /* Steps:
+     *      1. method   => equal, ignoring case
+     *      2. host     => contains, ignoring case
+     *      3. status   => starts with
+     */
+
+    fn filter(&self, filter: &SearchFilter) -> bool {
+        filter
+            .method
+            .as_ref()
+            .is_none_or(|method| method.eq_ignore_ascii_case(&self.method))
+            && filter.host.as_ref().is_none_or(|host| {
+                self.host
+                    .to_ascii_lowercase()
+                    .contains(&host.to_ascii_lowercase())
+            })
+            && filter
+                .status
+                .as_ref()
+                .is_none_or(|status| self.status.starts_with(status.as_str()))
+    }
+}
+
+// Stamp and tokens of an indexed request or response file
+#[derive(Debug)]
+struct IndexedFile {
+    stamp: FileStamp,
+    tokens: HashSet<String>,
+}
+
+/* Description:
+ *      Incrementally maintained token index of the http history used for
+ *      search.
+ *
+ *      Only the filter fields and a posting list of lowercase ascii word
+ *      tokens to ids are kept in memory. Search narrows the ids with the
+ *      tokens of the pattern and verifies the regex only on the files of
+ *      the candidates.
+ *
+ *      Ids are marked dirty when the history json is received from the
+ *      commander and indexed lazily, on the next search. Since history json
+ *      is received before the file is written, a request or response stays
+ *      dirty until its file is present. A response file is only waited for
+ *      after the response history json is received.
+ *
+ *      The stamp and tokens of each indexed file are kept. A file written
+ *      while being read stays dirty, and a file whose stamp changed after
+ *      being indexed, e.g. partially written, is indexed again replacing
+ *      its old tokens.
+ *
+ *      On the first search the history directory is scanned once to index
+ *      the entries of an attached session.
+ */
+
+#[derive(Debug)]
+pub struct SearchIndex {
+    root: PathBuf,
+    entries: BTreeMap<usize, IndexEntry>,
+    postings: HashMap<String, BTreeSet<usize>>,
+    files: HashMap<(usize, bool), IndexedFile>,
+    dirty_req: BTreeSet<usize>,
+    dirty_res: BTreeSet<usize>,
+    scanned: bool,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self::new(PathBuf::from(HISTORY_DIR))
+    }
+}
+
+impl SearchIndex {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            entries: BTreeMap::new(),
+            postings: HashMap::new(),
+            files: HashMap::new(),
+            dirty_req: BTreeSet::new(),
+            dirty_res: BTreeSet::new(),
+            scanned: false,
+        }
+    }
+
+    fn file(&self, id: usize, ext: &str) -> PathBuf {
+        self.root
+            .join(id.to_string())
+            .join(format!("{id}.{ext}"))
+    }
+
+    // Mark the request or response of the id in history json as dirty
+    pub fn mark(&mut self, data: &str) {
+        match serde_json::from_str::<HistoryId>(data) {
+            Ok(HistoryId::Request(his)) => {
+                self.dirty_req.insert(his.id);
+            }
+            Ok(HistoryId::Response(his)) => {
+                self.dirty_res.insert(his.id);
+            }
+            Err(e) => error!("search mark| {}| {}", data, e),
+        }
+    }
+
+    /* Steps:
+     *      1. If not scanned, index the request and response files of every
+     *         numeric directory in root. Missing files are not retried.
+     *
+     *      2. Mark the indexed files whose stamp changed as dirty.
+     *
+     *      3. Index the dirty requests, if file is missing or changed while
+     *         reading keep it dirty.
+     *
+     *      4. Index the dirty responses, if file is missing or changed while
+     *         reading keep it dirty.
+     */
+
+    pub async fn update(&mut self) {
+        // 1. Scan
+        if !self.scanned {
+            self.scanned = true;
+            let mut ids = Vec::new();
+            match tokio::fs::read_dir(&self.root).await {
+                Ok(mut dir) => {
+                    while let Ok(Some(entry)) = dir.next_entry().await {
+                        if let Some(id) = entry
+                            .file_name()
+                            .to_str()
+                            .and_then(|name| name.parse::<usize>().ok())
+                        {
+                            ids.push(id);
+                        }
+                    }
+                }
+                Err(e) => trace!("search scan| {}", e),
+            }
+            for id in ids {
+                self.index_file(id, Part::ReqHeaders)
+                    .await;
+                self.index_file(id, Part::ResHeaders)
+                    .await;
+            }
+        }
+
+        // 2. Changed
+        let files = self
+            .files
+            .iter()
+            .map(|(&(id, is_req), file)| {
+                let ext = if is_req {
+                    "req"
+                } else {
+                    "res"
+                };
+                ((id, is_req), self.file(id, ext), file.stamp)
+            })
+            .collect();
+        for (id, is_req) in changed(files).await {
+            if is_req {
+                self.dirty_req.insert(id);
+            } else {
+                self.dirty_res.insert(id);
+            }
+        }
+
+        // 3. Request
+        for id in std::mem::take(&mut self.dirty_req) {
+            if !self
+                .index_file(id, Part::ReqHeaders)
+                .await
+            {
+                self.dirty_req.insert(id);
+            }
+        }
+
+        // 4. Response
+        for id in std::mem::take(&mut self.dirty_res) {
+            if !self
+                .index_file(id, Part::ResHeaders)
+                .await
+            {
+                self.dirty_res.insert(id);
+            }
+        }
+        trace!(
+            "search index| {}| {}| {}",
+            self.entries.len(),
+            self.postings.len(),
+            self.dirty_req.len() + self.dirty_res.len()
+        );
+    }
+
+    /* Steps:
+     *      1. Read the request (ReqHeaders) or response (ResHeaders) file of
+     *         id, if missing or changed while reading return false.
+     *
+     *      2. Set method and host from request, or status from response.
+     *
+     *      3. Remove the id from the postings of the old tokens of the file,
+     *         that are not tokens of the other file of the id.
+     *
+     *      4. Add the tokens of headers and body to the postings.
+     */
+
+    async fn index_file(&mut self, id: usize, part: Part) -> bool {
+        // 1. Read
+        let is_req = part == Part::ReqHeaders;
+        let ext = if is_req {
+            "req"
+        } else {
+            "res"
+        };
+        let Some((data, stamp)) = read_stable(&self.file(id, ext)).await
+        else {
+            return false;
+        };
+        // 2. Filter fields
+        let (msg, headers, body) = message_parts(&data);
+        let entry = self.entries.entry(id).or_default();
+        if is_req {
+            entry.method = msg.first;
+            if let Some(host) = header_value(&msg.headers, "host") {
+                entry.host = host.to_string();
+            }
+        } else {
+            entry.status = msg.second;
+        }
+        // 3. Old tokens
+        if let Some(old) = self.files.remove(&(id, is_req)) {
+            let other = self.files.get(&(id, !is_req));
+            for token in old.tokens {
+                if other.is_some_and(|file| file.tokens.contains(&token)) {
+                    continue;
+                }
+                if let Some(ids) = self.postings.get_mut(&token) {
+                    ids.remove(&id);
+                    if ids.is_empty() {
+                        self.postings.remove(&token);
+                    }
+                }
+            }
+        }
+        // 4. Tokens
+        let mut indexed = HashSet::new();
+        for text in [headers, body] {
+            for (_, token) in tokens(&text) {
+                indexed.insert(token.to_ascii_lowercase());
+            }
+        }
+        for token in &indexed {
+            self.postings
+                .entry(token.clone())
+                .or_default()
+                .insert(id);
+        }
+        self.files.insert(
+            (id, is_req),
+            IndexedFile {
+                stamp,
+                tokens: indexed,
+            },
+        );
+        true
+    }
+
+    /* Description:
+     *      Ids that can match a literal pattern. None if the pattern is a
+     *      regex or has no word token, i.e. every id is a candidate.
+     *
+     * Steps:
+     *      For each token of the lowercase pattern with at least MIN_TOKEN
+     *      characters, collect ids of the indexed tokens that,
+     *
+     *          a. equal it, if bounded on both sides in the pattern
+     *          b. end with it, if it starts the pattern
+     *          c. start with it, if it ends the pattern
+     *          d. contain it, if it is the whole pattern
+     *
+     *      and intersect the ids of all tokens.
+     */
+
+    fn candidates(&self, query: &SearchQuery) -> Option<BTreeSet<usize>> {
+        if query.regex {
+            return None;
+        }
+        let pattern = query.pattern.to_ascii_lowercase();
+        let mut result: Option<BTreeSet<usize>> = None;
+        for (pos, token) in tokens(&pattern) {
+            let start = pos == 0;
+            let end = pos + token.len() == pattern.len();
+            let ids: BTreeSet<usize> = match (start, end) {
+                (false, false) => self
+                    .postings
+                    .get(token)
+                    .cloned()
+                    .unwrap_or_default(),
+                _ => self
+                    .postings
+                    .iter()
+                    .filter(|(indexed, _)| match (start, end) {
+                        (true, false) => indexed.ends_with(token),
+                        (false, true) => indexed.starts_with(token),
+                        _ => indexed.contains(token),
+                    })
+                    .flat_map(|(_, ids)| ids.iter().copied())
+                    .collect(),
+            };
+            result = Some(match result {
+                Some(result) => &result & &ids,
+                None => ids,
+            });
+        }
+        result
+    }
+
+    /* Steps:
+     *      1. Get the candidate ids of the pattern [ candidates() ]
+     *
+     *      2. Iterate entries in the id range of the filter, skip entries
+     *         not matching the filter or not in candidates.
+     *
+     *      3. Read the targeted parts from the files and find matches, with
+     *         MAX_SNIPPETS per entry.
+     *
+     *      4. Stop at MAX_RESULTS entries.
+     */
+
+    pub async fn search(
+        &self,
+        re: &Regex,
+        query: &SearchQuery,
+    ) -> Vec<SearchResult> {
+        let from = query.filter.from.unwrap_or(0);
+        let to = query.filter.to.unwrap_or(usize::MAX);
+        let mut results = Vec::new();
+        if from > to {
+            return results;
+        }
+        // 1. Candidates
+        let candidates = self.candidates(query);
+        for (id, entry) in self.entries.range(from..=to) {
+            // 2. Filter
+            if !entry.filter(&query.filter)
+                || candidates
+                    .as_ref()
+                    .is_some_and(|ids| !ids.contains(id))
+            {
+                continue;
+            }
+            // 3. Verify
+            let parts = self.read_parts(*id, query).await;
+            let matches: Vec<Snippet> = parts
+                .iter()
+                .flat_map(|(part, data)| {
+                    re.find_iter(data)
+                        .map(move |m| Snippet {
+                            part: *part,
+                            snippet: snippet(data, m.start(), m.end()),
+                        })
+                })
+                .take(MAX_SNIPPETS)
+                .collect();
+            if !matches.is_empty() {
+                results.push(SearchResult {
+                    id: *id,
+                    matches,
+                });
+                // 4. Limit
+                if results.len() == MAX_RESULTS {
+                    break;
+                }
+            }
+        }
+        results
+    }
+
+    // Targeted parts of the request and response files of id
+    async fn read_parts(
+        &self,
+        id: usize,
+        query: &SearchQuery,
+    ) -> Vec<(Part, String)> {
+        let mut parts = Vec::new();
+        for (ext, head, body) in [
+            ("req", Part::ReqHeaders, Part::ReqBody),
+            ("res", Part::ResHeaders, Part::ResBody),
+        ] {
+            let target = query.target;
+            if !target.includes(head) && !target.includes(body) {
+                continue;
+            }
+            let Ok(data) = tokio::fs::read(self.file(id, ext)).await else {
+                continue;
+            };
+            let (_, headers, content) = message_parts(&data);
+            parts.extend(
+                [(head, headers), (body, content)]
+                    .into_iter()
+                    .filter(|(part, _)| target.includes(*part)),
+            );
+        }
+        parts
+    }
+}
+
+// Runs of ascii alphanumeric and '_' of at least MIN_TOKEN, with position
+fn tokens(data: &str) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut start = None;
+    for (pos, c) in data
+        .char_indices()
+        .chain([(data.len(), ' ')])
+    {
+        let word = c.is_ascii_alphanumeric() || c == '_';
+        match (word, start) {
+            (true, None) => start = Some(pos),
+            (false, Some(from)) => {
+                if pos - from >= MIN_TOKEN {
+                    result.push((from, &data[from..pos]));
+                }
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    result
+}
+
+// Parsed http message with lossy utf8 headers and body, body is truncated
+// to MAX_BODY
+fn message_parts(data: &[u8]) -> (HttpMessage, String, String) {
+    let (head, _) = split_head(data);
+    let headers = String::from_utf8_lossy(head).into_owned();
+    let msg = HttpMessage::parse(data);
+    let body = &msg.body[..msg.body.len().min(MAX_BODY)];
+    let body = String::from_utf8_lossy(body).into_owned();
+    (msg, headers, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::history::search::query::SearchTarget;
+
+    const REQ: &str =
+        "GET /login HTTP/1.1\r\nHost: www.example.com\r\n\r\nuser=admin";
+    const RES: &str = "HTTP/1.1 200 OK\r\nSet-Cookie: session=abc\r\n\r\n{\"token\": \"secret\"}";
+
+    fn build_query(pattern: &str, target: SearchTarget) -> SearchQuery {
+        SearchQuery {
+            pattern: pattern.to_string(),
+            regex: false,
+            case_sensitive: false,
+            target,
+            filter: SearchFilter::default(),
+        }
+    }
+
+    #[test]
+    fn test_search_index_mark() {
+        let mut index = SearchIndex::default();
+        index.mark(
+            r#"{"Request":{"id":3,"method":"GET","host":"a","uri":"/"}}"#,
+        );
+        index.mark(r#"{"Response":{"id":4,"status":"200","length":10}}"#);
+        index.mark("invalid");
+        assert_eq!(index.dirty_req, BTreeSet::from([3]));
+        assert_eq!(index.dirty_res, BTreeSet::from([4]));
+    }
+
+    #[test]
+    fn test_search_index_message_parts() {
+        let (msg, headers, body) = message_parts(REQ.as_bytes());
+        assert_eq!(msg.first, "GET");
+        assert_eq!(headers, "GET /login HTTP/1.1\r\nHost: www.example.com");
+        assert_eq!(body, "user=admin");
+        let (msg, _, body) = message_parts(RES.as_bytes());
+        assert_eq!(msg.second, "200");
+        assert_eq!(body, "{\"token\": \"secret\"}");
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(
+            tokens("a=bc; X_Y.9z"),
+            vec![(2, "bc"), (6, "X_Y"), (10, "9z")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_index_update_and_search() {
+        let root = PathBuf::from("/tmp/zxc_search_index");
+        let _ = std::fs::remove_dir_all(&root);
+        for (id, res) in [(1, true), (2, false)] {
+            let dir = root.join(id.to_string());
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::File::create(dir.join(format!("{id}.req")))
+                .unwrap()
+                .write_all(REQ.as_bytes())
+                .unwrap();
+            if res {
+                std::fs::File::create(dir.join(format!("{id}.res")))
+                    .unwrap()
+                    .write_all(RES.as_bytes())
+                    .unwrap();
+            }
+        }
+        let mut index = SearchIndex::new(root.clone());
+        index.update().await;
+        assert_eq!(index.entries.len(), 2);
+        // 2 has no response, not retried
+        assert!(index.dirty_res.is_empty());
+        // marked response without file is retried
+        index.mark(r#"{"Response":{"id":3,"status":"200","length":10}}"#);
+        index.update().await;
+        assert_eq!(index.dirty_res, BTreeSet::from([3]));
+
+        let query = build_query("SECRET", SearchTarget::Body);
+        let re = query.build_regex().unwrap();
+        let verify = vec![SearchResult {
+            id: 1,
+            matches: vec![Snippet {
+                part: Part::ResBody,
+                snippet: "{\"token\": \"secret\"}".to_string(),
+            }],
+        }];
+        assert_eq!(index.search(&re, &query).await, verify);
+
+        let query = build_query("admin", SearchTarget::Headers);
+        let re = query.build_regex().unwrap();
+        assert!(
+            index
+                .search(&re, &query)
+                .await
+                .is_empty()
+        );
+
+        let mut query = build_query("admin", SearchTarget::Request);
+        query.filter.status = Some("2".to_string());
+        let re = query.build_regex().unwrap();
+        assert_eq!(index.search(&re, &query).await.len(), 1);
+
+        // candidates
+        let query = build_query("ecre", SearchTarget::All);
+        assert_eq!(index.candidates(&query), Some(BTreeSet::from([1])));
+        let query = build_query("user=adm", SearchTarget::All);
+        assert_eq!(index.candidates(&query), Some(BTreeSet::from([1, 2])));
+        let query = build_query("oken\": \"nope", SearchTarget::All);
+        assert_eq!(index.candidates(&query), Some(BTreeSet::new()));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_index_reindex_changed() {
+        let root = PathBuf::from("/tmp/zxc_search_reindex");
+        let _ = std::fs::remove_dir_all(&root);
+        let dir = root.join("1");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("1.req"), REQ).unwrap();
+        // partially written
+        std::fs::write(dir.join("1.res"), "HTTP/1.1 200 OK\r\n").unwrap();
+        let mut index = SearchIndex::new(root.clone());
+        index.update().await;
+        assert_eq!(index.entries[&1].status, "200");
+
+        std::fs::write(dir.join("1.res"), RES).unwrap();
+        index.update().await;
+        let query = build_query("secret", SearchTarget::All);
+        assert_eq!(index.candidates(&query), Some(BTreeSet::from([1])));
+
+        // old tokens removed, tokens shared with request kept
+        std::fs::write(
+            dir.join("1.res"),
+            "HTTP/1.1 404 Not Found\r\n\r\nuser",
+        )
+        .unwrap();
+        index.update().await;
+        assert_eq!(index.entries[&1].status, "404");
+        assert_eq!(index.candidates(&query), Some(BTreeSet::new()));
+        assert!(!index.postings.contains_key("secret"));
+        let query = build_query("user", SearchTarget::All);
+        assert_eq!(index.candidates(&query), Some(BTreeSet::from([1])));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
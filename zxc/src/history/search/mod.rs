@@ -0,0 +1,5 @@
+mod index;
+mod query;
+
+pub use index::SearchIndex;
+pub use query::SearchQuery;
@@ -0,0 +1,195 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+// Characters of context on either side of a match
+const SNIPPET_CONTEXT: usize = 40;
+
+/* Description:
+ *      Search query from history ui.
+ *
+ * Format:
+ *      [1, {"operation": {"Search": {
+ *              "pattern": "token",
+ *              "regex": false,
+ *              "case_sensitive": false,
+ *              "target": "body",
+ *              "filter": {"host": "example", "status": "2", "from": 10}
+ *      }}}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub pattern: String,
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub target: SearchTarget,
+    #[serde(default)]
+    pub filter: SearchFilter,
+}
+
+impl SearchQuery {
+    /* Steps:
+     *      1. If not regex, escape the pattern
+     *      2. Build regex with case sensitivity
+     */
+
+    pub fn build_regex(&self) -> Result<Regex, regex::Error> {
+        let pattern = if self.regex {
+            self.pattern.clone()
+        } else {
+            regex::escape(&self.pattern)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+    }
+}
+
+// Part of the http message to search in
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchTarget {
+    #[default]
+    All,
+    Request,
+    Response,
+    Headers,
+    Body,
+}
+
+impl SearchTarget {
+    pub fn includes(&self, part: Part) -> bool {
+        match self {
+            SearchTarget::All => true,
+            SearchTarget::Request => {
+                matches!(part, Part::ReqHeaders | Part::ReqBody)
+            }
+            SearchTarget::Response => {
+                matches!(part, Part::ResHeaders | Part::ResBody)
+            }
+            SearchTarget::Headers => {
+                matches!(part, Part::ReqHeaders | Part::ResHeaders)
+            }
+            SearchTarget::Body => {
+                matches!(part, Part::ReqBody | Part::ResBody)
+            }
+        }
+    }
+}
+
+/* Description:
+ *      Filters applied before matching.
+ *
+ *      method  => equal, case insensitive
+ *      host    => contains, case insensitive
+ *      status  => status code starts with
+ *      from/to => log id range, inclusive
+ */
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchFilter {
+    pub method: Option<String>,
+    pub host: Option<String>,
+    pub status: Option<String>,
+    pub from: Option<usize>,
+    pub to: Option<usize>,
+}
+
+// Indexed part of a http message
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Part {
+    ReqHeaders,
+    ReqBody,
+    ResHeaders,
+    ResBody,
+}
+
+// Match in a part with surrounding context
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Snippet {
+    pub part: Part,
+    pub snippet: String,
+}
+
+// Matching log id with snippets
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub id: usize,
+    pub matches: Vec<Snippet>,
+}
+
+/* Description:
+ *      Build snippet around the match.
+ *
+ * Steps:
+ *      1. Extend start and end by SNIPPET_CONTEXT, moved to char boundary.
+ *      2. Replace CR and LF with space to keep snippet in a single line.
+ */
+
+pub fn snippet(data: &str, start: usize, end: usize) -> String {
+    let mut from = start.saturating_sub(SNIPPET_CONTEXT);
+    while !data.is_char_boundary(from) {
+        from -= 1;
+    }
+    let mut to = (end + SNIPPET_CONTEXT).min(data.len());
+    while !data.is_char_boundary(to) {
+        to += 1;
+    }
+    data[from..to].replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_query_literal() {
+        let query = SearchQuery {
+            pattern: "a.b".to_string(),
+            regex: false,
+            case_sensitive: false,
+            target: SearchTarget::All,
+            filter: SearchFilter::default(),
+        };
+        let re = query.build_regex().unwrap();
+        assert!(re.is_match("xA.By"));
+        assert!(!re.is_match("axb"));
+    }
+
+    #[test]
+    fn test_search_query_deserialize() {
+        let data = r#"{"pattern": "tok[0-9]+", "regex": true, "target": "body", "filter": {"status": "2"}}"#;
+        let query: SearchQuery = serde_json::from_str(data).unwrap();
+        assert_eq!(query.target, SearchTarget::Body);
+        assert_eq!(query.filter.status.as_deref(), Some("2"));
+        assert!(
+            query
+                .build_regex()
+                .unwrap()
+                .is_match("TOK12")
+        );
+    }
+
+    #[test]
+    fn test_search_target_includes() {
+        assert!(SearchTarget::Headers.includes(Part::ResHeaders));
+        assert!(!SearchTarget::Headers.includes(Part::ReqBody));
+        assert!(SearchTarget::Request.includes(Part::ReqBody));
+    }
+
+    #[test]
+    fn test_snippet() {
+        let data = format!("{}secret\r\n{}", "a".repeat(50), "b".repeat(50));
+        let result = snippet(&data, 50, 56);
+        assert_eq!(
+            result,
+            format!("{}secret  {}", "a".repeat(40), "b".repeat(38))
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+
+use super::split_uri;
+
+const MAX_LINKS: usize = 200;
+const HTML: &str = "html";
+const JAVASCRIPT: &str = "javascript";
+const JS_EXT: &str = ".js";
+const SKIP_PREFIX: [&str; 4] = ["data:", "javascript:", "mailto:", "tel:"];
+
+// Attributes with links in html
+const HTML_LINK: &str = r#"(?i)\b(?:href|src|action|formaction|data-url)\s*=\s*["']([^"'\s<>]+)["']"#;
+// fetch, xhr open, axios and jquery calls, allows relative path
+const JS_CALL: &str = r#"(?:\bfetch|\.open\(\s*["'][A-Za-z]+["']\s*,|\baxios\.(?:get|post|put|patch|delete)|\$\.(?:get|post|ajax|getJSON))\(?\s*["'`]([^"'`\s<>]+)["'`]"#;
+// Quoted absolute url or path in js
+const JS_LITERAL: &str = r#"["'`]((?:https?:)?//[A-Za-z0-9.\-]+(?::\d+)?(?:/[^"'`\s<>\\]*)?|/[A-Za-z0-9_\-.~%/]+(?:\?[^"'`\s<>\\]*)?)["'`]"#;
+
+// Response with links
+pub struct Source<'a> {
+    pub scheme: &'a str,
+    pub host: &'a str,
+    pub path: &'a str,
+    pub content_type: Option<&'a str>,
+}
+
+// Link resolved against the source
+#[derive(Debug, PartialEq)]
+pub struct Link {
+    pub scheme: String,
+    pub host: String,
+    pub uri: String,
+}
+
+/* Description:
+ *      Link extractor for html and javascript responses.
+ *
+ *      html        : link attributes, and js patterns for inline scripts
+ *      javascript  : fetch/xhr calls and quoted absolute urls or paths
+ */
+
+#[derive(Debug)]
+pub struct Links {
+    html: Regex,
+    call: Regex,
+    literal: Regex,
+}
+
+impl Default for Links {
+    fn default() -> Self {
+        // safe to unwrap, constant patterns
+        Self {
+            html: Regex::new(HTML_LINK).unwrap(),
+            call: Regex::new(JS_CALL).unwrap(),
+            literal: Regex::new(JS_LITERAL).unwrap(),
+        }
+    }
+}
+
+impl Links {
+    /* Steps:
+     *      1. Check if source is html or javascript, from content type or
+     *         path extension.
+     *
+     *      2. Collect unique candidates from the patterns.
+     *
+     *      3. Resolve candidates against the source [ resolve() ], upto
+     *         MAX_LINKS.
+     */
+
+    pub fn extract(&self, source: &Source, body: &str) -> Vec<Link> {
+        // 1. Type
+        let content_type = source
+            .content_type
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let html = content_type.contains(HTML);
+        let js =
+            content_type.contains(JAVASCRIPT) || source.path.ends_with(JS_EXT);
+        if !html && !js {
+            return Vec::new();
+        }
+
+        // 2. Candidates
+        let mut regexes = vec![&self.call, &self.literal];
+        if html {
+            regexes.insert(0, &self.html);
+        }
+        let mut seen = HashSet::new();
+        regexes
+            .into_iter()
+            .flat_map(|re| {
+                re.captures_iter(body)
+                    .filter_map(|caps| caps.get(1))
+                    .map(|m| m.as_str())
+            })
+            .filter(|candidate| seen.insert(*candidate))
+            // 3. Resolve
+            .filter_map(|candidate| resolve(source, candidate))
+            .take(MAX_LINKS)
+            .collect()
+    }
+}
+
+/* Description:
+ *      Resolve link against the source.
+ *
+ *      http://h/p  => scheme and host of link
+ *      //h/p       => scheme of source
+ *      /p          => scheme and host of source
+ *      p           => relative to directory of source path
+ *
+ *      Skips data:, javascript:, mailto:, tel:, fragment only and template
+ *      links.
+ */
+
+fn resolve(source: &Source, link: &str) -> Option<Link> {
+    let lower = link.to_ascii_lowercase();
+    if link.starts_with('#')
+        || link.contains("${")
+        || link.contains("{{")
+        || SKIP_PREFIX
+            .iter()
+            .any(|prefix| lower.starts_with(prefix))
+    {
+        return None;
+    }
+    let (scheme, rest) = if let Some(rest) = lower
+        .strip_prefix("http://")
+        .map(|_| &link[7..])
+    {
+        ("http", Some(rest))
+    } else if let Some(rest) = lower
+        .strip_prefix("https://")
+        .map(|_| &link[8..])
+    {
+        ("https", Some(rest))
+    } else if let Some(rest) = link.strip_prefix("//") {
+        (source.scheme, Some(rest))
+    } else {
+        (source.scheme, None)
+    };
+    let (host, uri) = match rest {
+        Some(rest) => {
+            let (host, uri) = match rest.find(['/', '?', '#']) {
+                Some(pos) => (&rest[..pos], &rest[pos..]),
+                None => (rest, "/"),
+            };
+            if host.is_empty() {
+                return None;
+            }
+            (host.to_ascii_lowercase(), uri.to_string())
+        }
+        None if link.starts_with('/') => {
+            (source.host.to_string(), link.to_string())
+        }
+        None if link.contains(':') => return None,
+        None => {
+            let dir = source
+                .path
+                .rfind('/')
+                .map_or("/", |i| &source.path[..=i]);
+            (source.host.to_string(), format!("{dir}{link}"))
+        }
+    };
+    let (path, query) = split_uri(&uri);
+    let path = normalize(path);
+    let uri = match query {
+        Some(query) => format!("{path}?{query}"),
+        None => path,
+    };
+    Some(Link {
+        scheme: scheme.to_string(),
+        host,
+        uri,
+    })
+}
+
+// Remove . and .. segments of path
+fn normalize(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    let mut out = format!("/{}", segments.join("/"));
+    if path.ends_with('/') && !segments.is_empty() {
+        out.push('/');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source<'a>(path: &'a str, content_type: &'a str) -> Source<'a> {
+        Source {
+            scheme: "https",
+            host: "a.com",
+            path,
+            content_type: Some(content_type),
+        }
+    }
+
+    fn uris(links: Vec<Link>) -> Vec<String> {
+        links
+            .into_iter()
+            .map(|link| format!("{}://{}{}", link.scheme, link.host, link.uri))
+            .collect()
+    }
+
+    #[test]
+    fn test_links_html() {
+        let body = r##"<a href="/login?next=1">x</a><a href="../help">h</a>
+<img src="//cdn.b.com/i.png"><a href="mailto:x@a.com">m</a><a href="#top">t</a>
+<form action="submit"></form><script>fetch("/api/items")</script>"##;
+        let verify = vec![
+            "https://a.com/login?next=1",
+            "https://a.com/help",
+            "https://cdn.b.com/i.png",
+            "https://a.com/docs/submit",
+            "https://a.com/api/items",
+        ];
+        assert_eq!(
+            uris(
+                Links::default()
+                    .extract(&source("/docs/guide", "text/html"), body)
+            ),
+            verify
+        );
+    }
+
+    #[test]
+    fn test_links_js() {
+        let body = r#"fetch(`api/v1/users`); xhr.open("POST", "/api/v1/login");
+axios.get('/api/v1/me'); const u = "https://api.a.com/v2/x?y=1";
+const t = `/u/${id}`; const m = "text/html"; const p = "/static/app.css";"#;
+        let verify = vec![
+            "https://a.com/js/api/v1/users",
+            "https://a.com/api/v1/login",
+            "https://a.com/api/v1/me",
+            "https://api.a.com/v2/x?y=1",
+            "https://a.com/static/app.css",
+        ];
+        assert_eq!(
+            uris(
+                Links::default()
+                    .extract(&source("/js/app.js", "text/plain"), body)
+            ),
+            verify
+        );
+        assert!(
+            Links::default()
+                .extract(&source("/a.png", "image/png"), body)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("/a/./b/../c/"), "/a/c/");
+        assert_eq!(normalize("/../a"), "/a");
+        assert_eq!(normalize("/"), "/");
+    }
+}
@@ -0,0 +1,593 @@
+pub mod links;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, trace};
+
+use self::links::{Link, Links, Source};
+use crate::builder::HISTORY;
+use crate::config::DomainFilter;
+use crate::history::http::{HttpMessage, header_value};
+use crate::jar::cookie::strip_port;
+use crate::proxy::handler_state::handlers::get_attr;
+use crate::proxy::server_info::scheme::Scheme;
+
+const INDENT: &str = "  ";
+
+// Fields of the history json needed for the sitemap
+#[derive(Deserialize)]
+struct SiteRequest {
+    id: usize,
+    method: String,
+    #[serde(default)]
+    http: Option<bool>,
+    host: String,
+    uri: String,
+}
+
+#[derive(Deserialize)]
+struct SiteResponse {
+    id: usize,
+    status: String,
+}
+
+#[derive(Deserialize)]
+enum SiteEntry {
+    Request(SiteRequest),
+    Response(SiteResponse),
+}
+
+// Sitemap output format
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum SitemapFormat {
+    #[default]
+    Json,
+    Text,
+}
+
+/* Description:
+ *      Sitemap info from history ui. If path is set, sitemap is written to
+ *      the file instead of the reply.
+ *
+ * Format:
+ *      [1, {"operation": {"Sitemap": {"format": "Text"}}}]
+ *      [1, {"operation": {"Sitemap": {"format": "Json", "path": "site.json"}}}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub struct SitemapInfo {
+    #[serde(default)]
+    pub format: SitemapFormat,
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+// Node of the sitemap tree, scheme -> host -> path segments
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Default, Serialize)]
+pub struct SiteNode {
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    methods: BTreeSet<String>,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    status: BTreeSet<String>,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    params: BTreeSet<String>,
+    #[serde(skip_serializing_if = "is_zero")]
+    hits: usize,
+    // linked from a response but not requested
+    #[serde(skip_serializing_if = "is_false")]
+    discovered: bool,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    children: BTreeMap<String, SiteNode>,
+}
+
+fn is_zero(hits: &usize) -> bool {
+    *hits == 0
+}
+
+fn is_false(discovered: &bool) -> bool {
+    !*discovered
+}
+
+impl SiteNode {
+    /* Description:
+     *      Write node as indented text line followed by children.
+     *
+     * Format:
+     *      /api  GET POST  200 404  ?id&page  (3)
+     *      /admin  ?next  [discovered]
+     */
+
+    fn write_text(&self, name: &str, depth: usize, out: &mut String) {
+        for _ in 0..depth {
+            out.push_str(INDENT);
+        }
+        out.push_str(name);
+        if self.hits > 0 {
+            for set in [&self.methods, &self.status] {
+                if !set.is_empty() {
+                    out.push_str("  ");
+                    out.push_str(&join(set, " "));
+                }
+            }
+            if !self.params.is_empty() {
+                let _ = write!(out, "  ?{}", join(&self.params, "&"));
+            }
+            let _ = write!(out, "  ({})", self.hits);
+        } else if self.discovered {
+            if !self.params.is_empty() {
+                let _ = write!(out, "  ?{}", join(&self.params, "&"));
+            }
+            out.push_str("  [discovered]");
+        }
+        out.push('\n');
+        for (child, node) in self.children.iter() {
+            let name = if depth == 0 {
+                child.to_string()
+            } else {
+                format!("/{child}")
+            };
+            node.write_text(&name, depth + 1, out);
+        }
+    }
+}
+
+fn join(set: &BTreeSet<String>, sep: &str) -> String {
+    set.iter()
+        .map(String::as_str)
+        .collect::<Vec<&str>>()
+        .join(sep)
+}
+
+/* Description:
+ *      Split uri into path and query. Fragment is removed and absolute form
+ *      uri is reduced to path.
+ */
+
+pub fn split_uri(uri: &str) -> (&str, Option<&str>) {
+    let uri = uri.split('#').next().unwrap_or(uri);
+    let path_query = if uri.starts_with('/') {
+        uri
+    } else {
+        match uri.split_once("://") {
+            Some((_, rest)) => rest
+                .find('/')
+                .map_or("/", |pos| &rest[pos..]),
+            None => uri,
+        }
+    };
+    match path_query.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (path_query, None),
+    }
+}
+
+/* Description:
+ *      Sitemap of the http history, updated incrementally with the history
+ *      json received from the commander.
+ *
+ *      Request adds the method, parameter names and a hit to the node of the
+ *      uri. Node is remembered till the response of the id adds the status.
+ *
+ *      Links in html and javascript responses [ Links::extract() ] are
+ *      extracted by the analyzer task and added as discovered nodes, till
+ *      requested. Links to the host of the response are added. Links to
+ *      other hosts are added if the domain filter of the config proxies the
+ *      host, or without filter, if the host is already in the sitemap, to
+ *      skip third party links.
+ *
+ *      On the first view, the history directory is scanned to add the
+ *      entries of an attached session.
+ */
+
+#[derive(Debug)]
+pub struct SiteMap {
+    root: PathBuf,
+    tree: SiteNode,
+    ids: BTreeSet<usize>,
+    pending: HashMap<usize, Vec<String>>,
+    scanned: bool,
+    links: Links,
+    scope: Option<DomainFilter>,
+}
+
+impl Default for SiteMap {
+    fn default() -> Self {
+        Self::new(PathBuf::from(HISTORY))
+    }
+}
+
+impl SiteMap {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            tree: SiteNode::default(),
+            ids: BTreeSet::new(),
+            pending: HashMap::new(),
+            scanned: false,
+            links: Links::default(),
+            scope: None,
+        }
+    }
+
+    // Domain filter of config
+    pub fn set_scope(&mut self, scope: Option<DomainFilter>) {
+        self.scope = scope;
+    }
+
+    // Add history json to the sitemap
+    pub fn add(&mut self, data: &str) {
+        match serde_json::from_str::<SiteEntry>(data) {
+            Ok(SiteEntry::Request(req)) => self.add_request(req),
+            Ok(SiteEntry::Response(res)) => {
+                self.add_status(res.id, res.status)
+            }
+            Err(e) => error!("sitemap add| {}| {}", data, e),
+        }
+    }
+
+    /* Steps:
+     *      1. Skip if id already added.
+     *      2. Build node path of scheme, host and non empty path segments.
+     *      3. Add method, query parameter names and hit to the node.
+     */
+
+    fn add_request(&mut self, req: SiteRequest) {
+        // 1. Added
+        if !self.ids.insert(req.id) {
+            return;
+        }
+        // 2. Node path
+        let keys = node_keys(scheme(req.http), req.host, &req.uri);
+        // 3. Update
+        let node = self.node_mut(&keys);
+        node.methods.insert(req.method);
+        add_params(node, &req.uri);
+        node.hits += 1;
+        node.discovered = false;
+        self.pending.insert(req.id, keys);
+    }
+
+    // Add link as discovered node, if not requested
+    fn add_link(&mut self, link: Link) {
+        let keys = node_keys(link.scheme, link.host, &link.uri);
+        let node = self.node_mut(&keys);
+        if node.hits == 0 {
+            node.discovered = true;
+            add_params(node, &link.uri);
+        }
+    }
+
+    // Host is proxied by the domain filter, else host is in the sitemap
+    fn in_scope(&self, host: &str) -> bool {
+        match self.scope.as_ref() {
+            Some(filter) => filter.contains(&strip_port(host)),
+            None => self
+                .tree
+                .children
+                .values()
+                .any(|node| node.children.contains_key(host)),
+        }
+    }
+
+    /* Steps:
+     *      1. Get scheme and host of the request, host from Host header if
+     *         xattr is missing.
+     *
+     *      2. Extract links from the response [ extract_links() ] and add
+     *         them [ add_links() ].
+     */
+
+    fn discover(
+        &mut self,
+        info: Option<(String, String)>,
+        req: &HttpMessage,
+        res: &HttpMessage,
+    ) {
+        let (scheme, host) = info.unwrap_or_else(|| {
+            (
+                Scheme::Http.to_string(),
+                header_value(&req.headers, "host")
+                    .unwrap_or_default()
+                    .to_string(),
+            )
+        });
+        let links = extract_links(&self.links, &scheme, &host, req, res);
+        self.add_links(&host, links);
+    }
+
+    /* Description:
+     *      Add links discovered in the response from host. Only links to
+     *      the same host or to a host in scope [ in_scope() ] are added.
+     */
+
+    pub fn add_links(&mut self, host: &str, links: Vec<Link>) {
+        for link in links {
+            if link.host == host || self.in_scope(&link.host) {
+                self.add_link(link);
+            }
+        }
+    }
+
+    fn add_status(&mut self, id: usize, status: String) {
+        if let Some(keys) = self.pending.remove(&id) {
+            self.node_mut(&keys)
+                .status
+                .insert(status);
+        }
+    }
+
+    fn node_mut(&mut self, keys: &[String]) -> &mut SiteNode {
+        keys.iter()
+            .fold(&mut self.tree, |node, key| {
+                node.children
+                    .entry(key.clone())
+                    .or_default()
+            })
+    }
+
+    /* Steps:
+     *      1. If not scanned, for each numeric directory in root not already
+     *         added,
+     *          a. Read and parse .req file [ HttpMessage::parse() ].
+     *          b. Get host and scheme from xattrs of .req file.
+     *          c. Add request.
+     *          d. Read and parse .res file, if present, add status and
+     *             discover links.
+     */
+
+    pub async fn update(&mut self) {
+        if self.scanned {
+            return;
+        }
+        self.scanned = true;
+        let mut dir = match tokio::fs::read_dir(&self.root).await {
+            Ok(dir) => dir,
+            Err(e) => {
+                trace!("sitemap scan| {}", e);
+                return;
+            }
+        };
+        let mut ids = Vec::new();
+        while let Ok(Some(entry)) = dir.next_entry().await {
+            if let Some(id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<usize>().ok())
+                && !self.ids.contains(&id)
+            {
+                ids.push(id);
+            }
+        }
+        ids.sort_unstable();
+        for id in ids {
+            let dir = self.root.join(id.to_string());
+            // a. Request line
+            let req_path = dir.join(format!("{id}.req"));
+            let Ok(data) = tokio::fs::read(&req_path).await else {
+                continue;
+            };
+            let req = HttpMessage::parse(&data);
+            if req.first.is_empty() || req.second.is_empty() {
+                continue;
+            }
+            // b. Server info
+            let Ok(info) = get_attr(&req_path) else {
+                continue;
+            };
+            // c. Add
+            let source = (scheme(info.http).to_string(), info.host.clone());
+            self.add_request(SiteRequest {
+                id,
+                method: req.first.clone(),
+                http: info.http,
+                host: info.host,
+                uri: req.second.clone(),
+            });
+            // d. Status
+            if let Ok(data) =
+                tokio::fs::read(dir.join(format!("{id}.res"))).await
+            {
+                let res = HttpMessage::parse(&data);
+                if !res.second.is_empty() {
+                    self.add_status(id, res.second.clone());
+                }
+                self.discover(Some(source), &req, &res);
+            }
+        }
+        trace!("sitemap| {}", self.ids.len());
+    }
+
+    pub fn to_json(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(&self.tree.children)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for (scheme, node) in self.tree.children.iter() {
+            node.write_text(&format!("{scheme}://"), 0, &mut out);
+        }
+        out
+    }
+
+    pub fn render(
+        &self,
+        format: SitemapFormat,
+    ) -> Result<Value, serde_json::Error> {
+        match format {
+            SitemapFormat::Json => self.to_json(),
+            SitemapFormat::Text => Ok(Value::String(self.to_text())),
+        }
+    }
+}
+
+/* Description:
+ *      Links in the response body, decompressed when logged, resolved
+ *      against the scheme and host and the path of the request.
+ */
+
+pub fn extract_links(
+    links: &Links,
+    scheme: &str,
+    host: &str,
+    req: &HttpMessage,
+    res: &HttpMessage,
+) -> Vec<Link> {
+    if res.body.is_empty() {
+        return Vec::new();
+    }
+    let source = Source {
+        scheme,
+        host,
+        path: split_uri(&req.second).0,
+        content_type: header_value(&res.headers, "content-type"),
+    };
+    links.extract(&source, &String::from_utf8_lossy(&res.body))
+}
+
+fn scheme(http: Option<bool>) -> Scheme {
+    if http.is_some() {
+        Scheme::Http
+    } else {
+        Scheme::Https
+    }
+}
+
+// Scheme, host and non empty path segments of the uri
+fn node_keys(scheme: impl ToString, host: String, uri: &str) -> Vec<String> {
+    let mut keys = vec![scheme.to_string(), host];
+    keys.extend(
+        split_uri(uri)
+            .0
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string),
+    );
+    keys
+}
+
+// Query parameter names of the uri
+fn add_params(node: &mut SiteNode, uri: &str) {
+    if let Some(query) = split_uri(uri).1 {
+        node.params.extend(
+            form_urlencoded::parse(query.as_bytes())
+                .map(|(key, _)| key.into_owned())
+                .filter(|key| !key.is_empty()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sitemap() -> SiteMap {
+        let mut sitemap = SiteMap::new(PathBuf::from("/tmp/zxc_no_history"));
+        sitemap.add(
+            r#"{"Request":{"id":1,"method":"GET","host":"a.com","uri":"/api/users?id=1&page=2"}}"#,
+        );
+        sitemap.add(
+            r#"{"Response":{"id":1,"status":"200","length":10,"mime":"json"}}"#,
+        );
+        sitemap.add(
+            r#"{"Request":{"id":2,"method":"POST","host":"a.com","uri":"/api/users/"}}"#,
+        );
+        sitemap.add(r#"{"Response":{"id":2,"status":"403","length":0}}"#);
+        sitemap.add(
+            r#"{"Request":{"id":3,"method":"GET","http":true,"host":"b.com","uri":"http://b.com/#top"}}"#,
+        );
+        sitemap
+    }
+
+    #[test]
+    fn test_sitemap_text() {
+        let verify = "\
+http://
+  b.com  GET  (1)
+https://
+  a.com
+    /api
+      /users  GET POST  200 403  ?id&page  (2)
+";
+        assert_eq!(sitemap().to_text(), verify);
+    }
+
+    #[test]
+    fn test_sitemap_json() {
+        let verify = r#"{"http":{"children":{"b.com":{"hits":1,"methods":["GET"]}}},"https":{"children":{"a.com":{"children":{"api":{"children":{"users":{"hits":2,"methods":["GET","POST"],"params":["id","page"],"status":["200","403"]}}}}}}}}"#;
+        assert_eq!(sitemap().to_json().unwrap().to_string(), verify);
+    }
+
+    #[test]
+    fn test_sitemap_duplicate_id() {
+        let mut sitemap = sitemap();
+        sitemap.add(
+            r#"{"Request":{"id":1,"method":"PUT","host":"a.com","uri":"/api/users"}}"#,
+        );
+        assert!(
+            sitemap
+                .to_text()
+                .contains("/users  GET POST  200 403")
+        );
+    }
+
+    #[test]
+    fn test_sitemap_discovered() {
+        let mut sitemap = sitemap();
+        let req = b"GET /app.js HTTP/1.1\r\nHost: a.com\r\n\r\n";
+        let res = b"HTTP/1.1 200 OK\r\nContent-Type: application/javascript\r\n\r\nfetch('/api/admin?debug=1'); fetch('/api/users'); fetch('http://b.com/x'); fetch('https://cdn.c.com/y')";
+        sitemap.discover(
+            Some(("https".to_string(), "a.com".to_string())),
+            &HttpMessage::parse(req),
+            &HttpMessage::parse(res),
+        );
+        let verify = "\
+http://
+  b.com  GET  (1)
+    /x  [discovered]
+https://
+  a.com
+    /api
+      /admin  ?debug  [discovered]
+      /users  GET POST  200 403  ?id&page  (2)
+";
+        assert_eq!(sitemap.to_text(), verify);
+        sitemap.add(
+            r#"{"Request":{"id":4,"method":"GET","host":"a.com","uri":"/api/admin"}}"#,
+        );
+        assert!(
+            sitemap
+                .to_text()
+                .contains("/admin  GET  ?debug  (1)")
+        );
+        // filter
+        sitemap.set_scope(Some(DomainFilter::Include(
+            vec!["*.c.com".to_string()].into(),
+        )));
+        sitemap.discover(
+            Some(("https".to_string(), "a.com".to_string())),
+            &HttpMessage::parse(req),
+            &HttpMessage::parse(res),
+        );
+        assert!(
+            sitemap
+                .to_text()
+                .contains("  cdn.c.com\n    /y  [discovered]")
+        );
+    }
+
+    #[test]
+    fn test_split_uri() {
+        assert_eq!(split_uri("/a?b=1#c"), ("/a", Some("b=1")));
+        assert_eq!(
+            split_uri("https://a.com/x?u=http://b"),
+            ("/x", Some("u=http://b"))
+        );
+        assert_eq!(split_uri("http://a.com"), ("/", None));
+        assert_eq!(split_uri("/r?u=http://b/c"), ("/r", Some("u=http://b/c")));
+    }
+}
@@ -0,0 +1,82 @@
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use tokio::task::spawn_blocking;
+use tracing::error;
+
+/* Description:
+ *      Length and modification time of a history file. Used by the lazily
+ *      built views of the history to detect files that were still being
+ *      written when read, or were rewritten after being read.
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl From<&Metadata> for FileStamp {
+    fn from(meta: &Metadata) -> Self {
+        FileStamp {
+            len: meta.len(),
+            modified: meta.modified().ok(),
+        }
+    }
+}
+
+impl FileStamp {
+    pub async fn of(path: &Path) -> Option<Self> {
+        tokio::fs::metadata(path)
+            .await
+            .ok()
+            .map(|meta| FileStamp::from(&meta))
+    }
+}
+
+/* Steps:
+ *      1. Get the stamp of the file before reading.
+ *
+ *      2. Read the file.
+ *
+ *      3. Get the stamp after reading, if it differs the file was written
+ *         while being read.
+ *
+ * Returns:
+ *      Option<(data, stamp)>, None if missing or changed while reading
+ */
+
+pub async fn read_stable(path: &Path) -> Option<(Vec<u8>, FileStamp)> {
+    let before = FileStamp::of(path).await?;
+    let data = tokio::fs::read(path).await.ok()?;
+    let after = FileStamp::of(path).await?;
+    (before == after).then_some((data, after))
+}
+
+/* Description:
+ *      Keys of the files whose stamp differs from the stamp when they were
+ *      read. Removed files are not reported. Metadata is read in a single
+ *      blocking task.
+ */
+
+pub async fn changed<K>(files: Vec<(K, PathBuf, FileStamp)>) -> Vec<K>
+where
+    K: Send + 'static,
+{
+    spawn_blocking(move || {
+        files
+            .into_iter()
+            .filter(|(_, path, stamp)| {
+                std::fs::metadata(path)
+                    .is_ok_and(|meta| FileStamp::from(&meta) != *stamp)
+            })
+            .map(|(key, ..)| key)
+            .collect()
+    })
+    .await
+    .unwrap_or_else(|e| {
+        error!("stamp| {}", e);
+        Vec::new()
+    })
+}
@@ -13,6 +13,7 @@ pub const HISTORY_WS_HIS: &str = "./ws.whis";
 #[derive(Id)]
 pub struct WsHistory {
     id: usize,
+    http_id: usize,
     file: File,
 }
 
@@ -26,10 +27,15 @@ impl WsHistory {
             .await?;
         Ok(Self {
             id: info.id,
+            http_id: info.http_id,
             file,
         })
     }
 
+    pub fn http_id(&self) -> usize {
+        self.http_id
+    }
+
     pub fn file_as_mut(&mut self) -> &mut File {
         &mut self.file
     }
@@ -4,6 +4,8 @@ use tokio::sync::mpsc::error::SendError;
 
 use super::message::from_ui::InterUIOps;
 use crate::commander::codec::error::CodecError;
+use crate::commander::codegen::error::CodeGenError;
+use crate::commander::compare::error::CompareError;
 use crate::io::unix_sock::error::UnixSockError;
 use crate::run::boundary::IsUIError;
 
@@ -27,6 +29,10 @@ pub enum InterceptorError {
     // ----- codec_op ------
     #[error("{0}")]
     Codec(#[from] CodecError),
+    #[error("copy as| {0}")]
+    CodeGen(#[from] CodeGenError),
+    #[error("compare| {0}")]
+    Compare(#[from] CompareError),
     // unknown
     #[error("{0}")]
     UIError(#[from] UnixSockError),
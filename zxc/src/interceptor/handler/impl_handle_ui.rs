@@ -1,5 +1,7 @@
 use super::InterceptorHandler;
 use crate::commander::codec::perform_codec_op;
+use crate::commander::codegen::perform_copy_as;
+use crate::commander::compare::perform_compare;
 use crate::interceptor::error::InterceptorError;
 use crate::interceptor::message::from_ui::{InterUIOps, InterUImsg};
 use crate::run::boundary::HandleUI;
@@ -12,13 +14,18 @@ use crate::run::boundary::HandleUI;
  *      3. If Toggle, change intercept_state to !intercept_state and send to
  *      commander
  *
- *      4. If Resume | Forward | Drop , send to commander
+ *      4. If Resume | Respond | Forward | Drop | ToggleResponse, send to
+ *         commander
  *
  *      5. If Close, return InterceptorError::UIclosed
  *
  *      6. If encode | decode , call perform_codec_op(), set result to Some
  *
- *      7. If result is Some, return Some((msg.id, result))
+ *      7. If copy as, call perform_copy_as(), set result to Some
+ *
+ *      8. If compare, call perform_compare(), set result to Some
+ *
+ *      9. If result is Some, return Some((msg.id, result))
  *
  * Errors:
  *      InterceptorError
@@ -26,6 +33,8 @@ use crate::run::boundary::HandleUI;
  *          CommanderSend       [3] [4]
  *          Codec               [5]
  *          UIclosed            [6]
+ *          CodeGen             [7]
+ *          Compare             [8]
  */
 
 impl HandleUI for InterceptorHandler {
@@ -38,8 +47,10 @@ impl HandleUI for InterceptorHandler {
         let result = match msg.op() {
             InterUIOps::Toggle
             | InterUIOps::Resume(_)
+            | InterUIOps::Respond(_)
             | InterUIOps::Forward(_)
-            | InterUIOps::Drop(..) => {
+            | InterUIOps::Drop(..)
+            | InterUIOps::ToggleResponse => {
                 if matches!(msg.op(), InterUIOps::Toggle) {
                     self.intercept_state = !self.intercept_state;
                 }
@@ -64,6 +75,8 @@ impl HandleUI for InterceptorHandler {
                 let result = perform_codec_op(false, codec, data.as_bytes())?;
                 Some(result)
             }
+            InterUIOps::CopyAs(info) => Some(perform_copy_as(info).await?),
+            InterUIOps::Compare(info) => Some(perform_compare(info).await?),
         };
         if let Some(result) = result {
             return Ok(Some((id, result.to_string())));
@@ -1,6 +1,8 @@
 use serde::Deserialize;
 
 use crate::commander::codec::Codec;
+use crate::commander::codegen::CopyAsInfo;
+use crate::commander::compare::CompareInfo;
 use crate::file_types::FileType;
 use crate::forward_info::ForwardInfo;
 pub mod ftspec;
@@ -33,6 +35,8 @@ pub enum InterUIOps {
     Close,
     Drop(usize, FileType),
     Resume(ResumeInfo),
+    // Respond to the paused request with the edited .res file
+    Respond(usize),
     Encode {
         codec: Codec,
         data: String,
@@ -42,6 +46,12 @@ pub enum InterUIOps {
         codec: Codec,
         data: String,
     },
+    // Generate code for the request file
+    CopyAs(CopyAsInfo),
+    // Diff two request or response files
+    Compare(CompareInfo),
     Forward(ForwardInfo),
     Toggle,
+    // Intercept all http responses
+    ToggleResponse,
 }
@@ -15,8 +15,11 @@ use crate::proxy::server_info::json::ServerInfoJson;
  *              'log_id': 1
  *              'is_bin: true
  *              }
+ *          'auto': 'resume' | 'drop'
  *          }
  *
+ *      'auto' is only present when the commander resolved a queued message
+ *      after the intercept timeout expired.
  */
 
 #[derive(Debug, Serialize)]
@@ -27,6 +30,8 @@ pub struct InterToUI {
     server_info: Option<ServerInfoJson>,
     #[serde(skip_serializing_if = "Option::is_none")]
     ws_info: Option<WsInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auto: Option<AutoResolve>,
 }
 
 impl InterToUI {
@@ -39,6 +44,7 @@ impl InterToUI {
             ft: FileType::Req,
             server_info,
             ws_info: None,
+            auto: None,
         }
     }
 
@@ -48,6 +54,7 @@ impl InterToUI {
             ft: FileType::Res,
             server_info: None,
             ws_info: None,
+            auto: None,
         }
     }
 
@@ -64,6 +71,21 @@ impl InterToUI {
             ft,
             server_info: None,
             ws_info,
+            auto: None,
+        }
+    }
+
+    pub fn build_auto_resolved(
+        id: usize,
+        ft: FileType,
+        action: AutoResolve,
+    ) -> Self {
+        Self {
+            id,
+            ft,
+            server_info: None,
+            ws_info: None,
+            auto: Some(action),
         }
     }
 
@@ -84,6 +106,14 @@ impl InterToUI {
     }
 }
 
+// Action taken on a queued message when the intercept timeout expires
+#[derive(Debug, Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoResolve {
+    Resume,
+    Drop,
+}
+
 #[derive(Debug, Serialize)]
 pub struct WsInfo {
     log_id: usize,
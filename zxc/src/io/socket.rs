@@ -1,9 +1,11 @@
+use std::net::SocketAddr;
+
 use buffer::{Cursor, Event};
 use thiserror::Error;
 use tokio::io::{
     AsyncReadExt, {self}
 };
-use tokio::net::TcpStream;
+use tokio::net::{TcpStream, lookup_host};
 use tracing::trace;
 
 use crate::proxy::server_info::address::Address;
@@ -52,6 +54,36 @@ pub async fn establish_connection(
     result.map_err(|e| ConnectError::from((address, e)))
 }
 
+/* Description:
+ *      Resolve address to socket addresses, used to time dns resolution
+ *      separately from connect.
+ *
+ * Error:
+ *      ConnectError
+ */
+
+pub async fn resolve(
+    address: &Address,
+) -> Result<Vec<SocketAddr>, ConnectError> {
+    match address {
+        Address::Socket(socket_addr) => Ok(vec![*socket_addr]),
+        Address::Dns((host, port)) => lookup_host((host.as_str(), *port))
+            .await
+            .map(|addrs| addrs.collect())
+            .map_err(|e| ConnectError::from((address, e))),
+    }
+}
+
+// Connect to resolved socket addresses, first successful is returned
+pub async fn connect_resolved(
+    address: &Address,
+    addrs: &[SocketAddr],
+) -> Result<TcpStream, ConnectError> {
+    TcpStream::connect(addrs)
+        .await
+        .map_err(|e| ConnectError::from((address, e)))
+}
+
 /* Description:
  *      Given a generic type that implements AsyncReadExt and a buffer
  *      (Cursor), read the buffer from the generic type.
@@ -88,3 +120,34 @@ where
         Ok(Event::Read(buf))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_resolve_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let address = Address::Dns(("localhost".to_string(), port));
+        let addrs = resolve(&address).await.unwrap();
+        assert!(
+            addrs
+                .iter()
+                .all(|addr| addr.port() == port)
+        );
+
+        let socket = Address::Socket(listener.local_addr().unwrap());
+        let addrs = resolve(&socket).await.unwrap();
+        assert_eq!(addrs, vec![listener.local_addr().unwrap()]);
+        assert!(
+            connect_resolved(&socket, &addrs)
+                .await
+                .is_ok()
+        );
+    }
+}
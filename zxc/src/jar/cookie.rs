@@ -0,0 +1,231 @@
+use chrono::{DateTime, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+const ROOT: &str = "/";
+// Netscape cookie date format
+const EXPIRES_DASHED: &str = "%a, %d-%b-%Y %H:%M:%S GMT";
+
+fn root() -> String {
+    ROOT.to_string()
+}
+
+/* Description:
+ *      Cookie in the jar.
+ *
+ *      domain      : lowercase, without leading dot
+ *      host_only   : no Domain attribute, only sent to exact host
+ *      expires     : unix time, None is session cookie
+ *
+ * Format:
+ *      {"name":"sid","value":"1","domain":"a.com","host_only":true,
+ *       "path":"/","secure":true,"expires":1760000000}
+ */
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    #[serde(default)]
+    pub host_only: bool,
+    #[serde(default = "root")]
+    pub path: String,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<i64>,
+}
+
+// Host without port, lowercase
+pub fn strip_port(host: &str) -> String {
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    host.to_ascii_lowercase()
+}
+
+// host is domain or a subdomain of domain
+pub fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|rest| rest.ends_with('.'))
+}
+
+// request path is path or under path
+fn path_match(request: &str, path: &str) -> bool {
+    request == path
+        || (request.starts_with(path)
+            && (path.ends_with('/') || request[path.len()..].starts_with('/')))
+}
+
+// Directory of the request path
+fn default_path(request: &str) -> String {
+    match request.rfind('/') {
+        Some(0) | None => root(),
+        Some(pos) => request[..pos].to_string(),
+    }
+}
+
+fn parse_expires(value: &str) -> Option<i64> {
+    DateTime::parse_from_rfc2822(value)
+        .map(|date| date.timestamp())
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(value, EXPIRES_DASHED)
+                .map(|date| date.and_utc().timestamp())
+        })
+        .ok()
+}
+
+impl Cookie {
+    /* Description:
+     *      Parse Set-Cookie header value received from host for the request
+     *      path.
+     *
+     * Steps:
+     *      1. name=value pair, name should not be empty.
+     *
+     *      2. Attributes,
+     *          Domain      => ignore cookie if host does not match domain
+     *          Path        => if starts with /, else default path
+     *          Secure
+     *          Max-Age     => now + max age, precedes Expires
+     *          Expires
+     */
+
+    pub fn parse(
+        host: &str,
+        request_path: &str,
+        value: &str,
+        now: i64,
+    ) -> Option<Cookie> {
+        let host = strip_port(host);
+        let mut parts = value.split(';');
+        // 1. Pair
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(request_path),
+            secure: false,
+            expires: None,
+        };
+        // 2. Attributes
+        let mut max_age = None;
+        for attr in parts {
+            let (key, value) = attr
+                .split_once('=')
+                .unwrap_or((attr, ""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" => {
+                    let domain = value
+                        .trim_start_matches('.')
+                        .to_ascii_lowercase();
+                    if domain.is_empty() {
+                        continue;
+                    }
+                    if !domain_match(&host, &domain) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => {
+                    cookie.path = value.to_string()
+                }
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" => cookie.expires = parse_expires(value),
+                _ => {}
+            }
+        }
+        if let Some(max_age) = max_age {
+            cookie.expires = Some(now.saturating_add(max_age));
+        }
+        Some(cookie)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires
+            .is_some_and(|expires| expires <= now)
+    }
+
+    // Same cookie, replaced on update
+    pub fn same(&self, other: &Cookie) -> bool {
+        self.name == other.name
+            && self.domain == other.domain
+            && self.path == other.path
+    }
+
+    // Should be sent in request to host (without port) and path
+    pub fn matches(
+        &self,
+        host: &str,
+        path: &str,
+        tls: bool,
+        now: i64,
+    ) -> bool {
+        (if self.host_only {
+            host == self.domain
+        } else {
+            domain_match(host, &self.domain)
+        }) && path_match(path, &self.path)
+            && (tls || !self.secure)
+            && !self.is_expired(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cookie_parse() {
+        let cookie = Cookie::parse(
+            "www.a.com:8443",
+            "/app/login",
+            "sid=abc; Domain=.A.com; Path=/app; Secure; HttpOnly; Max-Age=60; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+            100,
+        )
+        .unwrap();
+        let verify = Cookie {
+            name: "sid".to_string(),
+            value: "abc".to_string(),
+            domain: "a.com".to_string(),
+            host_only: false,
+            path: "/app".to_string(),
+            secure: true,
+            expires: Some(160),
+        };
+        assert_eq!(cookie, verify);
+        assert!(cookie.matches("api.a.com", "/app/x", true, 100));
+        assert!(!cookie.matches("api.a.com", "/app/x", false, 100));
+        assert!(!cookie.matches("a.com", "/application", true, 100));
+        assert!(!cookie.matches("a.com", "/app", true, 160));
+    }
+
+    #[test]
+    fn test_cookie_parse_defaults() {
+        let cookie = Cookie::parse(
+            "a.com",
+            "/x/y",
+            "t=1; Expires=Thu, 01-Jan-1970 00:00:10 GMT",
+            0,
+        )
+        .unwrap();
+        assert!(cookie.host_only);
+        assert_eq!(cookie.path, "/x");
+        assert_eq!(cookie.expires, Some(10));
+        assert!(!cookie.matches("b.a.com", "/x", false, 0));
+        assert!(Cookie::parse("a.com", "/", "t=1; Domain=b.com", 0).is_none());
+        assert!(Cookie::parse("a.com", "/", "=1", 0).is_none());
+    }
+}
@@ -0,0 +1,463 @@
+pub mod cookie;
+
+use std::cmp::Reverse;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use bytes::BytesMut;
+use chrono::Utc;
+use cookie::{Cookie, strip_port};
+use oneone::{OneOne, Request, UpdateFrameError, UpdateHttp};
+use protocol_traits::Frame;
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::{error, trace};
+
+use crate::history::http::HttpMessage;
+use crate::history::sitemap::split_uri;
+
+pub const SESSION_COOKIES: &str = "./cookies.json";
+const COOKIE: &str = "cookie";
+const SET_COOKIE: &str = "set-cookie";
+
+pub type SharedJar = Arc<Mutex<CookieJar>>;
+
+#[derive(Debug, Error)]
+pub enum JarError {
+    #[error("io| {0}")]
+    Io(#[from] io::Error),
+    #[error("parse| {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("request| {0}")]
+    Request(#[from] UpdateFrameError),
+}
+
+fn now() -> i64 {
+    Utc::now().timestamp()
+}
+
+/* Description:
+ *      Cookie jar of the session, shared by analyzer, repeater and addons.
+ *
+ *      Populated from Set-Cookie of the proxied responses in analyzer and of
+ *      the repeater responses. Stored as json array in ./cookies.json.
+ */
+
+#[cfg_attr(test, derive(Default))]
+#[derive(Debug)]
+pub struct CookieJar {
+    path: PathBuf,
+    cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            cookies: Vec::new(),
+        }
+    }
+
+    // Load cookies from file, empty if file is missing
+    pub fn load(path: PathBuf) -> Result<Self, JarError> {
+        let cookies = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            cookies,
+        })
+    }
+
+    pub fn shared(self) -> SharedJar {
+        Arc::new(Mutex::new(self))
+    }
+
+    // Add or replace cookie, expired cookie removes the existing one
+    pub fn set(&mut self, cookie: Cookie) {
+        self.cookies
+            .retain(|stored| !stored.same(&cookie));
+        if !cookie.is_expired(now()) {
+            self.cookies.push(cookie);
+        }
+    }
+
+    // Remove cookie by domain, name and path, returns true if removed
+    pub fn remove(&mut self, domain: &str, name: &str, path: &str) -> bool {
+        let len = self.cookies.len();
+        self.cookies.retain(|cookie| {
+            !(cookie.domain == domain
+                && cookie.name == name
+                && cookie.path == path)
+        });
+        len != self.cookies.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.cookies.clear();
+    }
+
+    // Cookies that are not expired
+    pub fn cookies(&self) -> Vec<Cookie> {
+        let now = now();
+        self.cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now))
+            .cloned()
+            .collect()
+    }
+
+    /* Description:
+     *      name, value pairs to be sent to host and path. Longer paths are
+     *      sent first.
+     */
+
+    pub fn matching(
+        &self,
+        host: &str,
+        path: &str,
+        tls: bool,
+    ) -> Vec<(String, String)> {
+        let host = strip_port(host);
+        let now = now();
+        let mut matched: Vec<&Cookie> = self
+            .cookies
+            .iter()
+            .filter(|cookie| cookie.matches(&host, path, tls, now))
+            .collect();
+        matched.sort_by_key(|cookie| Reverse(cookie.path.len()));
+        matched
+            .into_iter()
+            .map(|cookie| (cookie.name.clone(), cookie.value.clone()))
+            .collect()
+    }
+
+    /* Description:
+     *      Update jar from Set-Cookie headers of the response to the request
+     *      sent to host.
+     *
+     * Returns:
+     *      true if any cookie was set
+     */
+
+    pub fn update(
+        &mut self,
+        host: &str,
+        req_path: &str,
+        res: &HttpMessage,
+    ) -> bool {
+        let now = now();
+        let mut updated = false;
+        for header in res.headers.iter().filter(|header| {
+            header
+                .name
+                .eq_ignore_ascii_case(SET_COOKIE)
+        }) {
+            if let Some(cookie) =
+                Cookie::parse(host, req_path, &header.value, now)
+            {
+                trace!("jar| {}| {}", cookie.domain, cookie.name);
+                self.set(cookie);
+                updated = true;
+            }
+        }
+        updated
+    }
+}
+
+/* Description:
+ *      Write cookies of the jar to file. Serialized under the lock, written
+ *      after releasing it.
+ *
+ * Error:
+ *      JarError::Parse
+ *      JarError::Io
+ */
+
+pub async fn persist(jar: &SharedJar) -> Result<(), JarError> {
+    let (path, data) = {
+        let jar = jar.lock().unwrap();
+        (jar.path.clone(), serde_json::to_vec(&jar.cookies)?)
+    };
+    tokio::fs::write(path, data).await?;
+    Ok(())
+}
+
+/* Description:
+ *      Update jar from the response and persist if changed. Path of the
+ *      request is taken from the request line.
+ */
+
+pub async fn update_from_response(
+    jar: &SharedJar,
+    host: &str,
+    req: &[u8],
+    res: &[u8],
+) {
+    update_from_parsed(
+        jar,
+        host,
+        &HttpMessage::parse(req),
+        &HttpMessage::parse(res),
+    )
+    .await
+}
+
+// Update from the parsed request and response, used by the analyzer
+pub async fn update_from_parsed(
+    jar: &SharedJar,
+    host: &str,
+    req: &HttpMessage,
+    res: &HttpMessage,
+) {
+    let path = split_uri(&req.second).0;
+    let updated = jar
+        .lock()
+        .unwrap()
+        .update(host, path, res);
+    if updated && let Err(e) = persist(jar).await {
+        error!("persist jar| {}", e);
+    }
+}
+
+/* Description:
+ *      Request to merge the cookies in jar for the host.
+ *
+ * Steps:
+ *      1. Get the cookies matching the host and path of request.
+ *
+ *      2. If Cookie header is present, replace the value of the pairs with
+ *         same name and append the rest, else add Cookie header.
+ */
+
+pub struct JarRequest {
+    pub jar: SharedJar,
+    pub host: String,
+    pub tls: bool,
+}
+
+impl JarRequest {
+    pub fn merge(&self, req: &mut OneOne<Request>) {
+        // 1. Matching
+        let uri = req.uri_as_string().to_string();
+        let cookies = self.jar.lock().unwrap().matching(
+            &self.host,
+            split_uri(&uri).0,
+            self.tls,
+        );
+        if cookies.is_empty() {
+            return;
+        }
+        // 2. Merge
+        match req.has_header_key(COOKIE) {
+            Some(pos) => {
+                let value = req
+                    .value_for_key(COOKIE)
+                    .unwrap_or_default();
+                let value = merge_cookies(value, &cookies);
+                req.header_map_as_mut()
+                    .change_header_value_on_pos(pos, &value);
+            }
+            None => {
+                let value = merge_cookies("", &cookies);
+                req.add_header("Cookie", &value);
+            }
+        }
+    }
+
+    // Parse the raw request, merge cookies and return the raw request
+    pub fn merge_raw(&self, data: &[u8]) -> Result<BytesMut, JarError> {
+        let mut req = OneOne::<Request>::update(BytesMut::from(data))?;
+        self.merge(&mut req);
+        Ok(req.into_data())
+    }
+}
+
+/* Steps:
+ *      1. Read and parse the request in file.
+ *      2. Merge cookies and write the request back to file.
+ *
+ * Error:
+ *      JarError::Io        [1] [2]
+ *      JarError::Request   [1]
+ */
+
+pub async fn merge_file(
+    jar: &JarRequest,
+    path: &Path,
+) -> Result<(), JarError> {
+    // 1. Read
+    let data = tokio::fs::read(path).await?;
+    // 2. Merge
+    let data = jar.merge_raw(&data)?;
+    tokio::fs::write(path, data).await?;
+    Ok(())
+}
+
+// Replace values of existing pairs, append missing pairs
+fn merge_cookies(header: &str, cookies: &[(String, String)]) -> String {
+    let mut pairs: Vec<(String, String)> = header
+        .split(';')
+        .filter_map(|pair| {
+            let pair = pair.trim();
+            (!pair.is_empty()).then(|| {
+                let (name, value) = pair
+                    .split_once('=')
+                    .unwrap_or((pair, ""));
+                (name.to_string(), value.to_string())
+            })
+        })
+        .collect();
+    for (name, value) in cookies {
+        match pairs
+            .iter_mut()
+            .find(|(existing, _)| existing == name)
+        {
+            Some(pair) => pair.1.clone_from(value),
+            None => pairs.push((name.clone(), value.clone())),
+        }
+    }
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/* Description:
+ *      Jar operation from repeater ui.
+ *
+ * Format:
+ *      [0,{"Jar": "View"}]
+ *      [0,{"Jar": {"Set": {"name": "sid", "value": "1", "domain": "a.com"}}}]
+ *      [0,{"Jar": {"Remove": {"domain": "a.com", "name": "sid", "path": "/"}}}]
+ *      [0,{"Jar": "Clear"}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub enum JarOp {
+    View,
+    Set(Cookie),
+    Remove {
+        domain: String,
+        name: String,
+        path: String,
+    },
+    Clear,
+}
+
+/* Steps:
+ *      1. Perform operation on the jar.
+ *      2. Persist if jar was modified.
+ *
+ * Returns:
+ *      Cookies in the jar
+ *
+ * Error:
+ *      JarError    [2]
+ */
+
+pub async fn perform_jar_op(
+    jar: &SharedJar,
+    op: JarOp,
+) -> Result<Vec<Cookie>, JarError> {
+    // 1. Operation
+    let (modified, cookies) = {
+        let mut jar = jar.lock().unwrap();
+        let modified = match op {
+            JarOp::View => false,
+            JarOp::Set(mut cookie) => {
+                cookie.domain = cookie
+                    .domain
+                    .trim_start_matches('.')
+                    .to_ascii_lowercase();
+                jar.set(cookie);
+                true
+            }
+            JarOp::Remove {
+                domain,
+                name,
+                path,
+            } => jar.remove(&domain, &name, &path),
+            JarOp::Clear => {
+                jar.clear();
+                true
+            }
+        };
+        (modified, jar.cookies())
+    };
+    // 2. Persist
+    if modified {
+        persist(jar).await?;
+    }
+    Ok(cookies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jar_update_matching() {
+        let mut jar = CookieJar::default();
+        let res = b"HTTP/1.1 200 OK\r\nSet-Cookie: sid=1; Path=/\r\nSet-Cookie: pref=a; Path=/app\r\nset-cookie: old=x; Max-Age=0\r\n\r\n";
+        assert!(jar.update("a.com", "/login", &HttpMessage::parse(res)));
+        assert_eq!(
+            jar.matching("a.com:443", "/app/x", false),
+            vec![
+                ("pref".to_string(), "a".to_string()),
+                ("sid".to_string(), "1".to_string())
+            ]
+        );
+        // replace
+        jar.update(
+            "a.com",
+            "/",
+            &HttpMessage::parse(
+                b"HTTP/1.1 200 OK\r\nSet-Cookie: sid=2\r\n\r\n",
+            ),
+        );
+        assert_eq!(jar.cookies().len(), 2);
+        assert!(jar.remove("a.com", "pref", "/app"));
+        assert_eq!(
+            jar.matching("a.com", "/", false),
+            vec![("sid".to_string(), "2".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_jar_merge() {
+        let mut jar = CookieJar::default();
+        jar.update(
+            "a.com",
+            "/",
+            &HttpMessage::parse(
+                b"HTTP/1.1 200 OK\r\nSet-Cookie: sid=new\r\nSet-Cookie: csrf=t\r\n\r\n",
+            ),
+        );
+        let request = JarRequest {
+            jar: jar.shared(),
+            host: "a.com".to_string(),
+            tls: true,
+        };
+
+        let data = "GET / HTTP/1.1\r\nHost: a.com\r\nCookie: theme=dark; sid=old\r\n\r\n";
+        let mut req = OneOne::<Request>::update(BytesMut::from(data)).unwrap();
+        request.merge(&mut req);
+        assert_eq!(
+            req.into_data(),
+            "GET / HTTP/1.1\r\nHost: a.com\r\nCookie: theme=dark; sid=new; csrf=t\r\n\r\n"
+        );
+
+        let data = "GET / HTTP/1.1\r\nHost: a.com\r\n\r\n";
+        let mut req = OneOne::<Request>::update(BytesMut::from(data)).unwrap();
+        request.merge(&mut req);
+        assert_eq!(
+            req.into_data(),
+            "GET / HTTP/1.1\r\nHost: a.com\r\nCookie: sid=new; csrf=t\r\n\r\n"
+        );
+    }
+}
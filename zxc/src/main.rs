@@ -5,6 +5,7 @@ mod config;
 use chrono::Local;
 use clap::Parser;
 use config::CliArgs;
+use config::local::command::{Command, ExportFormat};
 use config::local::io::write_local_config;
 use tmux::Session;
 mod addons;
@@ -15,11 +16,13 @@ mod history;
 mod id;
 mod interceptor;
 mod io;
+mod jar;
 mod proxy;
 mod repeater;
 mod setup;
 use std::fs::{create_dir, remove_dir_all};
 use std::io::Error;
+use std::path::Path;
 mod builder;
 mod forward_info;
 mod run;
@@ -27,6 +30,9 @@ mod tmux;
 
 use builder::*;
 use commander::{CommanderRequest, run_commander};
+use history::analyzer::run_analyzer;
+use history::har::write_har;
+use history::import::import_files;
 use run::starter::start_module;
 use setup::*;
 use tokio::net::TcpListener;
@@ -41,6 +47,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .sanitize()
         .unwrap_or_default();
 
+    if let Some(command) = args.command.take() {
+        return run_command(command);
+    }
+
     let attach = args.should_attach();
 
     let session_name = args
@@ -88,15 +98,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _ = setup_logging(args.debug)
         .map_err(|e| eprintln!("setup logging| {}", e));
 
+    builder.parse_global_config();
+
     // History
     match builder.build_listener(HISTORY) {
         Ok(listener) => {
-            let history_handler = builder.build_history();
-            let token = token.clone();
+            let db = local_config
+                .as_ref()
+                .is_some_and(|config| config.db.unwrap_or(false));
+            let (history_handler, analyzer) = builder.build_history(db);
+            let history_token = token.clone();
             let _abort_history = set.spawn(async move {
                 let span = span!(Level::INFO, HISTORY);
                 let _ = span.enter();
-                let _ = start_module(history_handler, listener, token)
+                let _ = start_module(history_handler, listener, history_token)
+                    .instrument(span)
+                    .await;
+            });
+            let analyzer_token = token.clone();
+            let _abort_analyzer = set.spawn(async move {
+                let span = span!(Level::INFO, ANALYZER);
+                let _ = span.enter();
+                run_analyzer(analyzer, analyzer_token)
                     .instrument(span)
                     .await;
             });
@@ -137,7 +160,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Addons
-    builder.parse_global_config();
     match builder.build_listener(ADDONS) {
         Ok(listener) => {
             let addon_handler = builder.build_addons();
@@ -203,6 +225,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/* Steps:
+ *      Export
+ *          har => write session history as HTTP Archive to output
+ *
+ *      Import
+ *          1. If the session is running, i.e. history socket exists in
+ *             /tmp/$session_name, return MainError::SessionRunning. Import
+ *             from history ui instead.
+ *          2. Convert files to absolute path and change to session directory.
+ *          3. Get next log index, same as attaching to session.
+ *          4. Import files [ import_files() ]
+ */
+
+fn run_command(command: Command) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Command::Export(ExportFormat::Har(args)) => {
+            let output = args.output();
+            let count = write_har(&args.session, &output)?;
+            println!("exported {} entries| {}", count, output.display());
+        }
+        Command::Import(args) => {
+            if let Some(name) = args.session_name()
+                && Path::new(&format!("/tmp/{name}/{HISTORY}.sock")).exists()
+            {
+                return Err(MainError::SessionRunning(name).into());
+            }
+            let files = args
+                .files
+                .iter()
+                .map(std::path::absolute)
+                .collect::<Result<Vec<_>, _>>()?;
+            std::env::set_current_dir(&args.session)
+                .map_err(MainError::CurrentDir)?;
+            let index = get_largest_file_index().map_or(1, |index| index + 1);
+            let count = import_files(&files, args.target.as_deref(), index)?;
+            println!("imported {} entries| from id {}", count, index);
+        }
+    }
+    Ok(())
+}
+
 #[inline]
 fn create_session_dirs(sname: &str) -> Result<(), Error> {
     create_dir(sname)?;
@@ -228,4 +291,6 @@ enum MainError {
     BuildSession(Error),
     #[error("attach session| {}", .0)]
     AttachSession(Error),
+    #[error("session running, import from history ui| {}", .0)]
+    SessionRunning(String),
 }
@@ -2,9 +2,11 @@ use std::path::PathBuf;
 
 use bytes::BytesMut;
 use rustls_pki_types::ServerName;
-use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender};
 
 use crate::commander::CommanderResponse;
+use crate::history::analyzer::ToAnalyzer;
+use crate::history::db::writer::ToDb;
 use crate::history::message::from_commander::CommanderToHistory;
 use crate::proxy::server_info::ServerInfo;
 use crate::proxy::server_info::address::Address;
@@ -19,18 +21,21 @@ use crate::proxy::server_info::address::Address;
 pub struct AdditionalHandlerInfo {
     pub log_id: usize,
     pub payload: BytesMut,
-    pub path: PathBuf,
+    pub path: Option<PathBuf>,
     pub should_intercept: bool,
+    pub intercept_response: bool,
     pub receiver: Receiver<CommanderResponse>,
     pub server_info: ServerInfo,
     pub history_sender: Option<Sender<CommanderToHistory>>,
+    pub analyzer_sender: Option<UnboundedSender<ToAnalyzer>>,
+    pub db_sender: Option<UnboundedSender<ToDb>>,
 }
 
 impl AdditionalHandlerInfo {
     pub fn new(
         log_id: usize,
         payload: BytesMut,
-        path: PathBuf,
+        path: Option<PathBuf>,
         need_response: bool,
         receiver: Receiver<CommanderResponse>,
         server_info: ServerInfo,
@@ -41,12 +46,33 @@ impl AdditionalHandlerInfo {
             payload,
             path,
             should_intercept: need_response,
+            intercept_response: false,
             receiver,
             server_info,
             history_sender,
+            analyzer_sender: None,
+            db_sender: None,
         }
     }
 
+    pub fn with_senders(
+        mut self,
+        analyzer_sender: Option<UnboundedSender<ToAnalyzer>>,
+        db_sender: Option<UnboundedSender<ToDb>>,
+    ) -> Self {
+        self.analyzer_sender = analyzer_sender;
+        self.db_sender = db_sender;
+        self
+    }
+
+    pub fn with_intercept_response(
+        mut self,
+        intercept_response: bool,
+    ) -> Self {
+        self.intercept_response = intercept_response;
+        self
+    }
+
     pub fn address(&self) -> &Address {
         self.server_info.address()
     }
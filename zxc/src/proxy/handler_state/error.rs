@@ -6,6 +6,7 @@ use super::handlers::oneonestruct::OneOneRWError;
 use super::transition::update_frame::error::ProxyUpdateFrameError;
 use crate::commander::CommanderRequest;
 use crate::commander::communicate::response::convert::WrongMessage;
+use crate::history::analyzer::ToAnalyzer;
 use crate::history::message::from_commander::CommanderToHistory;
 use crate::io::file::FileErrorInfo;
 use crate::io::socket::ConnectError;
@@ -35,6 +36,8 @@ pub enum ProxyStateError {
     Serialize(#[from] serde_json::Error),
     #[error("ws History Send")]
     HistorySend(#[from] SendError<CommanderToHistory>),
+    #[error("Analyzer Send| {0}")]
+    AnalyzerSend(#[from] SendError<ToAnalyzer>),
 
     // ----- File Ops -----
     #[error("file| {0}")]
@@ -8,9 +8,11 @@ use crate::io::socket::fill_buffer;
 use crate::proxy::handler_state::ProxyState;
 use crate::proxy::handler_state::read_write::ReadWrite;
 use crate::proxy::handler_state::transition::reconnect::Reconnect;
+use crate::proxy::handler_state::transition::write_history::ResponseMeta;
 use crate::proxy::handler_state::transition::write_log::log::Log;
 use crate::proxy::server_info::ServerInfo;
 use crate::proxy::server_info::json::ServerInfoJson;
+use crate::proxy::server_info::upstream::GetUpstream;
 use crate::proxy::states::error::StateError;
 use crate::proxy::states::*;
 pub mod oneonestruct;
@@ -25,13 +27,16 @@ use tokio::io::{
 };
 use tracing::trace;
 mod error;
+mod respond;
 pub mod scode;
 use std::fmt::Debug;
-use std::io::Error;
+use std::io::{Error, ErrorKind};
 use std::path::PathBuf;
+use std::time::Instant;
 
 use error::HandleOneOneError;
 use oneone::HeaderStruct;
+use respond::respond;
 use scode::*;
 use tracing::error;
 
@@ -40,6 +45,9 @@ use super::handle_websocket;
 const XATTR_HOST: &str = "user.host";
 const XATTR_HTTP: &str = "user.http";
 const XATTR_SNI: &str = "user.sni";
+const XATTR_TTFB: &str = "user.ttfb";
+const XATTR_DURATION: &str = "user.duration";
+const XATTR_IP: &str = "user.ip";
 
 // client type alias
 type OneOneRequest<T, E> = OneOneStruct<T, E, Request>;
@@ -85,6 +93,7 @@ pub async fn handle_http<T, E, U>(
 where
     T: AsyncReadExt + AsyncWriteExt + Unpin + Sync + Send + 'static + Debug,
     E: AsyncReadExt + AsyncWriteExt + Unpin + Sync + Send + 'static + Debug,
+    E: GetUpstream,
     OneOneRequest<T, E>: Reconnect,
     ConnectionState<U>: From<OneOneRequest<T, E>>,
 {
@@ -237,7 +246,8 @@ where
 *       - OneOneResponse<T, E>: ReadWrite,
 *
 * Steps:
-*      1. Run client_state until it ends.
+*      1. Run client_state until it ends. If client_state is Respond, respond
+*         to client with the response from interceptor [ respond() ].
 *
 *      2. Convert ClientState to OneOneRequest
 *         [ TryFrom trait in convert/try_from_proxy_state ]
@@ -252,7 +262,9 @@ where
 *
 *      3. Check If client is logged,
 *
-*      4. If client is logged, set the extended attributes to the request file.
+*      4. If client is logged, set the sent time and upstream info of the
+*         server connection, store the request in db [ store() ] and set the
+*         extended attributes to the request file.
 *
 *      5. Convert OneOneRequest to OneOneResponse [ From trait in
 *         convert/request_to_response ] and create new server_state,
@@ -268,6 +280,8 @@ where
 *           a. If server_state is ServerClose
 *               return HandleOneOneError::ReadFromServer
 *
+*          b. Set timing attributes and store the response in db.
+*
 *      8. Get the status code from server_state.
 *
 *      9. If the status code is 101, return ProxyState::SwitchProtocol(Ws)
@@ -293,7 +307,7 @@ pub async fn handle_one_one<T, E>(
 ) -> Result<ServerState<E, T>, HandleOneOneError<T, E>>
 where
     T: AsyncReadExt + AsyncWriteExt + Unpin,
-    E: AsyncReadExt + AsyncWriteExt + Unpin,
+    E: AsyncReadExt + AsyncWriteExt + Unpin + GetUpstream,
     OneOneRequest<T, E>: ReadWrite
         + Reconnect
         + Into<OneOneResponse<E, T>>
@@ -301,13 +315,21 @@ where
     OneOneResponse<T, E>: ReadWrite,
 {
     client_state = async_run(client_state).await?;
+    if let ProxyState::Respond(client_conn) = client_state {
+        trace!("respond");
+        return respond(client_conn).await;
+    }
     let mut client_conn = OneOneRequest::<T, E>::try_from(client_state)?;
 
     match client_conn.path.is_some() {
         // 3. client logged
         true => {
-            // set xttr
+            // request sent, set time and upstream info for response history
+            let upstream = client_conn.writer.upstream();
+            client_conn.set_sent(upstream);
+            // set xttr and store in db
             let info = ServerInfoJson::from(&client_conn.server_info);
+            client_conn.store(&info);
             let path = client_conn.path();
             if let Err(e) = set_attr(path, info) {
                 error!("Set Attr| {}", e);
@@ -317,6 +339,12 @@ where
             server_state = async_run(server_state).await?;
             let mut server_conn =
                 OneOneResponse::<E, T>::try_from(server_state)?;
+            // set timing xattr on .res for export
+            let meta = server_conn.response_meta();
+            if let Err(e) = set_timing_attr(server_conn.path(), &meta) {
+                error!("Set Timing Attr| {}", e);
+            }
+            server_conn.store();
 
             // safe to unwrap
             let scode = get_status_code(server_conn.payload.take().unwrap())?;
@@ -347,6 +375,38 @@ pub async fn read_http<T, U>(
     reader: &mut T,
     buf: &mut BytesMut,
 ) -> Result<OneOne<U>, OneOneRWError>
+where
+    T: AsyncReadExt + Unpin,
+    U: InfoLine,
+    HeaderStruct<U>: ParseBodyHeaders,
+{
+    read_http_meta(reader, buf)
+        .await
+        .map(|(frame, _)| frame)
+}
+
+// Read time and body size of http message before conversion
+#[derive(Debug)]
+pub struct ReadMeta {
+    pub first_byte: Instant,
+    pub end: Instant,
+    pub encoded_len: usize,
+}
+
+/* Description:
+ *      Read http message along with ReadMeta.
+ *
+ * Steps:
+ *      1. Set first_byte after the first read.
+ *      2. When frame state ends, get the body length as received, i.e.
+ *         before decompression [ OneOne::body_len() ]
+ *      3. Convert the frame [ into_frame() ] and set end.
+ */
+
+pub async fn read_http_meta<T, U>(
+    reader: &mut T,
+    buf: &mut BytesMut,
+) -> Result<(OneOne<U>, ReadMeta), OneOneRWError>
 where
     T: AsyncReadExt + Unpin,
     U: InfoLine,
@@ -354,13 +414,28 @@ where
 {
     let mut frame_state = OneOneState::<U>::new();
     let mut cbuf = Cursor::new(buf);
+    let mut first_byte = None;
     loop {
         let event = fill_buffer(reader, &mut cbuf)
             .await
             .map_err(OneOneRWError::Read)?;
+        // 1. First byte
+        let first_byte = *first_byte.get_or_insert_with(Instant::now);
         frame_state = frame_state.next(event)?;
         if frame_state.is_ended() {
-            return Ok(frame_state.into_frame()?);
+            // 2. Body length
+            let encoded_len = match &frame_state {
+                OneOneState::End(one) => one.body_len(),
+                _ => 0,
+            };
+            // 3. Convert
+            let frame = frame_state.into_frame()?;
+            let meta = ReadMeta {
+                first_byte,
+                end: Instant::now(),
+                encoded_len,
+            };
+            return Ok((frame, meta));
         }
     }
 }
@@ -385,3 +460,89 @@ pub fn set_attr(path: &PathBuf, info: ServerInfoJson) -> Result<(), Error> {
     trace!("attr set");
     Ok(())
 }
+
+/* Description:
+ *      Function to get the server info from extended attributes set by
+ *      set_attr()
+ *
+ * Steps:
+ *      1. Get XATTR_HOST, if missing return ErrorKind::NotFound
+ *      2. If XATTR_HTTP is present, scheme is http
+ *      3. Get XATTR_SNI
+ */
+
+pub fn get_attr(path: &PathBuf) -> Result<ServerInfoJson, Error> {
+    let host = xattr::get(path, XATTR_HOST)?
+        .ok_or(Error::new(ErrorKind::NotFound, XATTR_HOST))?;
+    let http = xattr::get(path, XATTR_HTTP)?.map(|_| true);
+    let sni = xattr::get(path, XATTR_SNI)?
+        .map(|sni| String::from_utf8_lossy(&sni).into_owned());
+    Ok(ServerInfoJson {
+        host: String::from_utf8_lossy(&host).into_owned(),
+        http,
+        sni,
+    })
+}
+
+/* Description:
+ *      Function to set extended attributes of the response file to the
+ *      timing and upstream ip of the response.
+ *
+ * Steps:
+ *      If Some, set XATTR_TTFB, XATTR_DURATION (in ms) and XATTR_IP
+ */
+
+pub fn set_timing_attr(
+    path: &PathBuf,
+    meta: &ResponseMeta,
+) -> Result<(), Error> {
+    if let Some(ttfb) = meta.ttfb {
+        xattr::set(path, XATTR_TTFB, ttfb.to_string().as_bytes())?;
+    }
+    if let Some(duration) = meta.duration {
+        xattr::set(path, XATTR_DURATION, duration.to_string().as_bytes())?;
+    }
+    if let Some(ip) = meta.ip {
+        xattr::set(path, XATTR_IP, ip.to_string().as_bytes())?;
+    }
+    Ok(())
+}
+
+/* Description:
+ *      Function to get the timing and upstream ip set by set_timing_attr().
+ *      Missing or invalid attributes are None.
+ */
+
+pub fn get_timing_attr(path: &PathBuf) -> Result<ResponseMeta, Error> {
+    let get = |name| -> Result<Option<String>, Error> {
+        Ok(xattr::get(path, name)?
+            .map(|value| String::from_utf8_lossy(&value).into_owned()))
+    };
+    Ok(ResponseMeta {
+        ttfb: get(XATTR_TTFB)?.and_then(|ttfb| ttfb.parse().ok()),
+        duration: get(XATTR_DURATION)?
+            .and_then(|duration| duration.parse().ok()),
+        ip: get(XATTR_IP)?.and_then(|ip| ip.parse().ok()),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_http_meta_chunked() {
+        let data = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                     7\r\nMozilla\r\n0\r\n\r\n";
+        let mut reader = &data[..];
+        let mut buf = BytesMut::with_capacity(1024);
+        let (frame, meta) =
+            read_http_meta::<_, Response>(&mut reader, &mut buf)
+                .await
+                .unwrap();
+        assert_eq!(meta.encoded_len, 7);
+        assert!(meta.end >= meta.first_byte);
+        assert_eq!(frame.body_len(), 7);
+    }
+}
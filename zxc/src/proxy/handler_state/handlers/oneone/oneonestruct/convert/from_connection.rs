@@ -29,9 +29,16 @@ where
             reader: conn.reader,
             role: Role::Server,
             need_response: false,
+            intercept_response: false,
             writer: conn.writer,
             log_id: 0,
             history_sendr: None,
+            analyzer_sendr: None,
+            db_sendr: None,
+            mock: None,
+            original: None,
+            need_new_conn: false,
+            meta: HttpMeta::default(),
         }
     }
 }
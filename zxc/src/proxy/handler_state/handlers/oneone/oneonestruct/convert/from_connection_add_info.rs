@@ -22,10 +22,13 @@ where
             addinfo.server_info,
         ));
         one.log_id = addinfo.log_id;
-        one.path = Some(addinfo.path);
+        one.path = addinfo.path;
         one.payload = Some(addinfo.payload);
         one.need_response = addinfo.should_intercept;
+        one.intercept_response = addinfo.intercept_response;
         one.history_sendr = addinfo.history_sender;
+        one.analyzer_sendr = addinfo.analyzer_sender;
+        one.db_sendr = addinfo.db_sender;
         one
     }
 }
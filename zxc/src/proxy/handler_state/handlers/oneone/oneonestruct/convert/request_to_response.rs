@@ -17,9 +17,16 @@ impl<T, E> From<OneOneStruct<T, E, Request>> for OneOneStruct<E, T, Response> {
             reader: request.writer,
             role: Role::Client,
             need_response: request.need_response,
+            intercept_response: request.intercept_response,
             writer: request.reader,
             server_info: request.server_info,
             history_sendr: request.history_sendr,
+            analyzer_sendr: request.analyzer_sendr,
+            db_sendr: request.db_sendr,
+            mock: request.mock,
+            original: None,
+            need_new_conn: false,
+            meta: request.meta,
         }
     }
 }
@@ -23,9 +23,16 @@ impl<T, E> From<OneOneStruct<T, E, Response>> for OneOneStruct<E, T, Request> {
             reader: response.writer,
             role: Role::Server,
             need_response: response.need_response,
+            intercept_response: response.intercept_response,
             writer: response.reader,
             server_info: response.server_info,
             history_sendr: response.history_sendr,
+            analyzer_sendr: response.analyzer_sendr,
+            db_sendr: response.db_sendr,
+            mock: None,
+            original: None,
+            need_new_conn: false,
+            meta: HttpMeta::default(),
         }
     }
 }
@@ -19,12 +19,14 @@ where
         let addinfo = AdditionalHandlerInfo::new(
             oneone.log_id,
             oneone.payload.unwrap(),
-            oneone.path.unwrap(),
+            oneone.path,
             oneone.need_response,
             oneone.commander_recvr,
             oneone.server_info,
             oneone.history_sendr,
-        );
+        )
+        .with_senders(oneone.analyzer_sendr, oneone.db_sendr)
+        .with_intercept_response(oneone.intercept_response);
         let conn = Connection {
             buf: oneone.buf,
             commander: oneone.commander_sendr,
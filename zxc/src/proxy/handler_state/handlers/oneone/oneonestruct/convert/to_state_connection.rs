@@ -1,6 +1,6 @@
 use super::*;
 use crate::proxy::states::{
-    ClientTlsStream, ConnectionState, ServerTlsStream, Tcp
+    ClientTlsStream, ConnectionState, ServerTlsStream, Tcp, ZStream
 };
 
 /* OneOneStruct<T,Tcp,Request> => ConnectionState<T>
@@ -19,6 +19,7 @@ where
 {
     fn from(oneone: OneOneStruct<T, Tcp, Request>) -> Self {
         let (conn, addinfo) = oneone.into();
+        let conn = Connection::from((conn, ZStream));
         ConnectionState::EstablishTcpTls(conn, addinfo)
     }
 }
@@ -47,10 +48,62 @@ where
         >,
     ) -> Self {
         let (conn, addinfo) = oneone.into();
+        let conn = Connection::from((conn, ZStream));
         ConnectionState::EstablishTlsTcp(conn, addinfo)
     }
 }
 
+/* OneOneStruct<T,ZStream,Request> => ConnectionState<T>
+ *
+ * Used in:
+ *      ProxyState::NewConnection
+ *      when,
+ *          client      = tcp://
+ *          server      = mocked, not connected
+ *          new server  = tcp:// | tls://
+ */
+
+impl<T> From<OneOneStruct<T, ZStream, Request>> for ConnectionState<T>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    fn from(oneone: OneOneStruct<T, ZStream, Request>) -> Self {
+        let (conn, addinfo) = oneone.into();
+        if addinfo.server_info.is_tls() {
+            ConnectionState::EstablishTcpTls(conn, addinfo)
+        } else {
+            ConnectionState::EstablishTcpTcp(conn, addinfo)
+        }
+    }
+}
+
+/* OneOneStruct<ServerTlsStream<T>,ZStream,Request> => ConnectionState<T>
+ *
+ * Used in:
+ *      ProxyState::NewConnection
+ *      when,
+ *          client      = tls://
+ *          server      = mocked, not connected
+ *          new server  = tls:// | tcp://
+ */
+
+impl<T> From<OneOneStruct<ServerTlsStream<T>, ZStream, Request>>
+    for ConnectionState<T>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    fn from(
+        oneone: OneOneStruct<ServerTlsStream<T>, ZStream, Request>,
+    ) -> Self {
+        let (conn, addinfo) = oneone.into();
+        if addinfo.server_info.is_tls() {
+            ConnectionState::EstablishTlsTls(conn, addinfo)
+        } else {
+            ConnectionState::EstablishTlsTcp(conn, addinfo)
+        }
+    }
+}
+
 /* OneOneStruct<ServerTlsStream<T>,Tcp,Request> => ConnectionState<T>
  *
  *  Blank implementation
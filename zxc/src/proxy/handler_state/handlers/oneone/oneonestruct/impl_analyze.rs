@@ -0,0 +1,25 @@
+use oneone::{Request, Response};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::OneOneStruct;
+use crate::history::analyzer::ToAnalyzer;
+use crate::proxy::handler_state::transition::analyze::Analyze;
+
+// Request is analyzed along with the response
+impl<T, E> Analyze for OneOneStruct<T, E, Request> {
+    fn analyze_msg(
+        &self,
+    ) -> Option<(&UnboundedSender<ToAnalyzer>, ToAnalyzer)> {
+        None
+    }
+}
+
+impl<T, E> Analyze for OneOneStruct<T, E, Response> {
+    fn analyze_msg(
+        &self,
+    ) -> Option<(&UnboundedSender<ToAnalyzer>, ToAnalyzer)> {
+        self.analyzer_sendr
+            .as_ref()
+            .map(|sender| (sender, ToAnalyzer::Response(self.log_id)))
+    }
+}
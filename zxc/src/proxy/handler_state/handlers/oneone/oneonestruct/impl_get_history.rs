@@ -1,8 +1,8 @@
-use oneone::{Request, Response};
+use oneone::{OneOne, Request, Response};
 
-use super::OneOneStruct;
+use super::{HttpMeta, OneOneStruct};
 use crate::proxy::handler_state::transition::write_history::{
-    GetHistory, HistoryEnum, RequestHistory, ResponseHistory
+    GetHistory, HistoryEnum, RequestHistory, ResponseHistory, ResponseMeta
 };
 
 impl<T, E> GetHistory for OneOneStruct<T, E, Request> {
@@ -11,8 +11,11 @@ impl<T, E> GetHistory for OneOneStruct<T, E, Request> {
         let method = req.method_as_string();
         let uri = req.uri_as_string();
         let host = self.server_info.address_to_string();
-        let req =
+        let mut req =
             RequestHistory::new(self.log_id, method, self.scheme(), host, uri);
+        if let Some(original) = self.original.as_ref() {
+            req.set_original(original.clone());
+        }
         HistoryEnum::Request(req)
     }
 }
@@ -22,8 +25,75 @@ impl<T, E> GetHistory for OneOneStruct<T, E, Response> {
         let res = self.frame.as_ref().unwrap(); // safe to unwrap
         let status_code = res.status_code();
         let content_length = res.content_length();
-        let res =
+        let mut history =
             ResponseHistory::new(self.log_id, status_code, content_length);
-        HistoryEnum::Response(res)
+        history.set_meta(self.response_meta());
+        HistoryEnum::Response(history)
+    }
+}
+
+impl<T, E> OneOneStruct<T, E, Response> {
+    pub fn response_meta(&self) -> ResponseMeta {
+        response_meta(self.frame.as_ref().unwrap(), &self.meta) // safe to unwrap
+    }
+}
+
+/* Steps:
+ *      1. Set mime from content type of body headers.
+ *
+ *      2. If content encoded, set encoding as comma separated names and
+ *         encoded_length as the body length before decompression.
+ *
+ *      3. If request sent and response read, set ttfb and duration.
+ *
+ *      4. Set upstream ip and tls version.
+ */
+
+fn response_meta(res: &OneOne<Response>, meta: &HttpMeta) -> ResponseMeta {
+    let body_headers = res.body_headers().as_ref();
+    // 1. Mime
+    let mime = body_headers.and_then(|headers| headers.content_type);
+
+    // 2. Encoding
+    let encoding = body_headers
+        .and_then(|headers| headers.content_encoding.as_ref())
+        .map(|encodings| {
+            encodings
+                .iter()
+                .map(|encoding| encoding.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        });
+    let encoded_length = encoding
+        .as_ref()
+        .and(meta.read.as_ref())
+        .map(|read| read.encoded_len);
+
+    // 3. Timing
+    let (ttfb, duration) = match (meta.sent, meta.read.as_ref()) {
+        (Some(sent), Some(read)) => (
+            Some(
+                read.first_byte
+                    .saturating_duration_since(sent)
+                    .as_millis() as u64,
+            ),
+            Some(
+                read.end
+                    .saturating_duration_since(sent)
+                    .as_millis() as u64,
+            ),
+        ),
+        _ => (None, None),
+    };
+
+    // 4. Upstream
+    ResponseMeta {
+        mime,
+        encoding,
+        encoded_length,
+        ttfb,
+        duration,
+        ip: meta.upstream.ip,
+        tls: meta.upstream.tls.map(str::to_string),
     }
 }
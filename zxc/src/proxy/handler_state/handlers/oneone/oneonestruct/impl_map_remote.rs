@@ -0,0 +1,124 @@
+use oneone::const_headers::HOST;
+use oneone::{Request, Response};
+use tracing::{error, trace};
+
+use super::*;
+use crate::commander::communicate::response::convert::WrongMessage;
+use crate::config::Remap;
+use crate::proxy::handler_state::transition::map_remote::MapRemote;
+use crate::proxy::server_info::json::ServerInfoJson;
+
+impl<T, E> MapRemote for OneOneStruct<T, E, Request> {
+    /* Steps:
+     *      1. If mocked, return None.
+     *      2. Build CommanderRequest::MapRemote with id, scheme, host and
+     *         uri.
+     */
+
+    fn get_remap_request(&self) -> Option<CommanderRequest> {
+        if self.is_mocked() {
+            return None;
+        }
+        let frame = self.frame.as_ref().unwrap(); // safe to unwrap
+        Some(CommanderRequest::MapRemote(
+            self.id,
+            self.scheme(),
+            self.server_info.address_to_string(),
+            frame.uri_as_string().to_string(),
+        ))
+    }
+
+    /* Steps:
+     *      1. If no rule matched, return.
+     *
+     *      2. Build the new ServerInfo from the remap. If rewrite_host is
+     *         false, keep the original sni. Invalid address is logged and
+     *         the request is sent to the original server.
+     *
+     *      3. Save the original target, scheme://host/uri, for history.
+     *
+     *      4. Rewrite uri and, if rewrite_host, the Host header.
+     *
+     *      5. If address, scheme or sni changed, set need_new_conn so that
+     *         the server connection is established before sending
+     *         [ ReadWrite::write() ]. Deferred connection of a mocked
+     *         connection is kept.
+     */
+
+    fn update_remap(
+        &mut self,
+        response: CommanderResponse,
+    ) -> Result<(), WrongMessage> {
+        // 1. No match
+        let Some(remap) = Option::<Remap>::try_from(response)? else {
+            return Ok(());
+        };
+        trace!("remap| {:?}", remap);
+
+        // 2. Build ServerInfo
+        let json = ServerInfoJson::new(remap.host.clone(), remap.scheme, None);
+        let mut server_info = match ServerInfo::try_from(json) {
+            Ok(server_info) => server_info,
+            Err(e) => {
+                error!("remap| {}| {}", remap.host, e);
+                return Ok(());
+            }
+        };
+        if !remap.rewrite_host && server_info.is_tls() {
+            let sni = if self.server_info.is_tls() {
+                Ok(self.server_info.sni().to_owned())
+            } else {
+                self.address()
+                    .parse_sni(None)
+                    .map(|sni| sni.to_owned())
+            };
+            match sni {
+                Ok(sni) => server_info.set_sni(sni),
+                Err(e) => error!("remap sni| {}", e),
+            }
+        }
+
+        // 3. Original target
+        let frame = self.frame.as_mut().unwrap(); // safe to unwrap
+        self.original = Some(format!(
+            "{}://{}{}",
+            self.server_info.scheme(),
+            self.server_info.address_to_string(),
+            frame.uri_as_string()
+        ));
+
+        // 4. Rewrite request
+        if let Some(uri) = remap.uri {
+            let buf = frame.infoline_as_mut().uri_as_mut();
+            buf.clear();
+            buf.extend_from_slice(uri.as_bytes());
+        }
+        if remap.rewrite_host {
+            frame
+                .header_map_as_mut()
+                .change_header_value_on_key(HOST, &remap.host);
+        }
+
+        // 5. Need new connection
+        self.need_new_conn |= server_info.address() != self.address()
+            || server_info.scheme() != self.scheme()
+            || (server_info.is_tls()
+                && server_info.sni() != self.server_info.sni());
+        self.server_info = server_info;
+        Ok(())
+    }
+}
+
+// Response is never remapped, update is a no-op
+impl<T, E> MapRemote for OneOneStruct<T, E, Response> {
+    fn get_remap_request(&self) -> Option<CommanderRequest> {
+        None
+    }
+
+    fn update_remap(
+        &mut self,
+        _: CommanderResponse,
+    ) -> Result<(), WrongMessage> {
+        Ok(())
+    }
+}
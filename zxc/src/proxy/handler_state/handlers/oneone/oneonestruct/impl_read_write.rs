@@ -10,9 +10,10 @@ use tracing::trace;
 use super::OneOneStruct;
 use crate::io::write::write_and_flush;
 use crate::proxy::handler_state::ProxyState;
-use crate::proxy::handler_state::handlers::read_http;
+use crate::proxy::handler_state::handlers::read_http_meta;
 use crate::proxy::handler_state::read_write::ReadWrite;
 use crate::proxy::handler_state::role::{GetRole, Role};
+use crate::proxy::server_info::json::ServerInfoJson;
 
 #[derive(Debug, Error)]
 pub enum OneOneRWError {
@@ -37,9 +38,10 @@ where
     type State = ProxyState<Self>;
 
     /* Steps:
-     *      1. call read_http() with reader and buf as args.
+     *      1. call read_http_meta() with reader and buf as args.
      *
-     *      2. If Ok(frame) is returned, set the self.frame to the frame.
+     *      2. If Ok((frame, meta)) is returned, set the self.frame to the
+     *         frame, store meta for history and return ProxyState::ShouldMock
      *
      *      3. If Err(e) is returned, check role
      *
@@ -49,7 +51,7 @@ where
      *          b. if role is server, return Err(e)
      *
      * Transition:
-     *      Read -> ShouldLog | ServerClose
+     *      Read -> ShouldMock | ServerClose
      *
      * Error:
      *      OneOneRWError::Read
@@ -57,10 +59,11 @@ where
 
     async fn read(mut self) -> Result<ProxyState<Self>, OneOneRWError> {
         trace!("reading");
-        match read_http::<T, U>(&mut self.reader, &mut self.buf).await {
-            Ok(frame) => {
+        match read_http_meta::<T, U>(&mut self.reader, &mut self.buf).await {
+            Ok((frame, meta)) => {
                 self.frame = Some(frame);
-                Ok(ProxyState::ShouldLog(self))
+                self.meta.read = Some(meta);
+                Ok(ProxyState::ShouldMock(self))
             }
             Err(e) => match self.role() {
                 Role::Client => Ok(ProxyState::ServerClose(self, e.into())),
@@ -70,25 +73,40 @@ where
     }
 
     /* Steps:
-     *      1. call write_and_flush() with writer and data as args
+     *      1. If request is mocked, return ProxyState::Respond without
+     *         writing to server.
      *
-     *      2. If Err(e) is returned, check role
+     *      2. If request is remapped to a new server, return
+     *         ProxyState::NewConnection with the new server info.
+     *
+     *      3. call write_and_flush() with writer and data as args
+     *
+     *      4. If Err(e) is returned, check role
      *
      *          a. if role is server, then server has closed return
      *          ProxyState::ServerClose
      *
      *          b. if role is client, return Err(e)
      *
-     *      3. Else Transition to End
+     *      5. Else Transition to End
      *
      * Transition:
-     *      WriteResponse -> End | ServerClose
+     *      WriteResponse -> End | Respond | NewConnection | ServerClose
      *
      * Error:
      *      OneOneRWError::Write
      */
 
     async fn write(mut self) -> Result<ProxyState<Self>, OneOneRWError> {
+        if self.is_mocked() {
+            trace!("mocked");
+            return Ok(ProxyState::Respond(self));
+        }
+        if self.need_new_conn {
+            trace!("remapped");
+            let info = ServerInfoJson::from(&self.server_info);
+            return Ok(ProxyState::NewConnection(self, info));
+        }
         trace!("writing");
         if let Err(e) =
             write_and_flush(&mut self.writer, self.payload.as_ref().unwrap())
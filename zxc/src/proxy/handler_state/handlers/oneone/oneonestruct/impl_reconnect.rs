@@ -1,15 +1,17 @@
+use std::io::ErrorKind;
+
 use oneone::Request;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tracing::trace;
 
 use super::OneOneStruct;
-use crate::io::socket::establish_connection;
+use crate::io::socket::{ConnectError, establish_connection};
 use crate::proxy::handler_state::error::ProxyStateError;
 use crate::proxy::handler_state::transition::reconnect::Reconnect;
 use crate::proxy::server_info::ServerInfo;
-use crate::proxy::states::ClientTlsStream;
 use crate::proxy::states::connection::encrypt::server_encrypt;
+use crate::proxy::states::{ClientTlsStream, ZStream};
 
 /* Description:
  *      Reconnect trait imeplementation for OneOneHandler<T , Tcp, Request>
@@ -99,3 +101,32 @@ where
         Ok(())
     }
 }
+
+/* Description:
+ *      Reconnect trait implementation for OneOneHandler<T , ZStream, Request>
+ *      of a mocked connection.
+ *
+ *      Server connection is never established in place, as the type of the
+ *      server stream changes. Request that is not mocked returns
+ *      ProxyState::NewConnection [ OneOneStruct::defer_connection() ]
+ *
+ * Trait in:
+ *      reconnect
+ */
+
+impl<T> Reconnect for OneOneStruct<T, ZStream, Request>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    fn can_reconnect(&self, _: &ServerInfo) -> bool {
+        false
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ProxyStateError> {
+        let e = ConnectError::from((
+            self.address(),
+            ErrorKind::NotConnected.into(),
+        ));
+        Err(e.into())
+    }
+}
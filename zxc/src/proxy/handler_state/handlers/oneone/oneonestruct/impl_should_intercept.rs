@@ -1,38 +1,116 @@
-use oneone::{Request, Response};
+use bytes::BytesMut;
+use oneone::abnf::HEADER_DELIMITER;
+use oneone::const_headers::CONTENT_TYPE;
+use oneone::{OneOne, Request, Response};
 
 use super::*;
 use crate::proxy::handler_state::QueryCommanderShouldIntercept;
 use crate::proxy::handler_state::error::ProxyStateError;
 use crate::proxy::handler_state::transition::should_intercept::ShouldIntercept;
 
+const SHOULD_INTERCEPT_RESPONSE: &str = "ShouldInterceptResponse";
+
 /* Steps:
- *      For request, always true.
+ *      For request, true unless mocked.
+ *
+ *      For response,
+ *          1. If need_response set by user in resume_info is true, Some(true).
+ *
+ *          2. If response interception was inactive (no toggle response and
+ *             no response rules) when the request was logged, Some(false).
  *
- *      For response, need_response value by user in resume_info
+ *          3. Else None, query commander whether response should be
+ *             intercepted.
  */
 
 impl<T, E> ShouldIntercept for OneOneStruct<T, E, Request> {
     #[inline(always)]
     fn should_intercept(&self) -> Option<bool> {
-        Some(true)
+        Some(!self.is_mocked())
     }
 }
 
 impl<T, E> ShouldIntercept for OneOneStruct<T, E, Response> {
     #[inline(always)]
     fn should_intercept(&self) -> Option<bool> {
-        Some(self.need_response)
+        if self.need_response {
+            Some(true)
+        } else if !self.intercept_response {
+            Some(false)
+        } else {
+            None
+        }
     }
 }
 
 // Blanket implementation, always panic
-impl<T, E, U> QueryCommanderShouldIntercept for OneOneStruct<T, E, U>
-where
-    U: InfoLine,
-{
+impl<T, E> QueryCommanderShouldIntercept for OneOneStruct<T, E, Request> {
     async fn query_commander_should_intercept(
         &mut self,
     ) -> Result<bool, ProxyStateError> {
-        panic!("No QueryCommanderShouldIntercept for OneOneStruct");
+        panic!("No QueryCommanderShouldIntercept for OneOneStruct Request");
+    }
+}
+
+/* Steps:
+ *      1. Parse the header of the logged response payload to get status code
+ *         and Content-Type.
+ *
+ *      2. Build CommanderRequest::ShouldInterceptResponse with id, host,
+ *         status code and content type.
+ *
+ *      3. Send the request to the commander and receive the response.
+ *
+ *      4. If the response is CommanderResponse::ResponseInterceptReply(true),
+ *         return true.
+ *
+ * Errors:
+ *      ProxyStateError::CommanderRequest   [3]
+ *      ProxyStateError::CommanderResponse  [3]
+ */
+
+impl<T, E> QueryCommanderShouldIntercept for OneOneStruct<T, E, Response> {
+    async fn query_commander_should_intercept(
+        &mut self,
+    ) -> Result<bool, ProxyStateError> {
+        // 1. Parse header
+        let Some(header) = self
+            .payload
+            .as_ref()
+            .and_then(|payload| {
+                payload
+                    .windows(HEADER_DELIMITER.len())
+                    .position(|window| window == HEADER_DELIMITER)
+                    .map(|index| {
+                        BytesMut::from(
+                            &payload[..index + HEADER_DELIMITER.len()],
+                        )
+                    })
+            })
+        else {
+            return Ok(false);
+        };
+        let Ok(frame) = OneOne::<Response>::new(header) else {
+            return Ok(false);
+        };
+        let status = frame.status_code().to_string();
+        let ct = frame
+            .value_for_key(CONTENT_TYPE)
+            .map(|ct| ct.to_string());
+
+        // 2. Query commander
+        let host = self.server_info.address_to_string();
+        let req = CommanderRequest::ShouldInterceptResponse(
+            self.id, host, status, ct,
+        );
+        self.commander_sendr.send(req).await?;
+        let result = self
+            .commander_recvr
+            .recv()
+            .await
+            .ok_or(ProxyStateError::CommanderResponse(
+                SHOULD_INTERCEPT_RESPONSE,
+            ))?;
+        Ok(matches!(result, CommanderResponse::ResponseInterceptReply(true)))
     }
 }
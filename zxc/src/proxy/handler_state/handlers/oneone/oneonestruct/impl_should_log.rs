@@ -1,29 +1,31 @@
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::str::FromStr;
 
 use http::uri::PathAndQuery;
 use mime::ContentType;
 use oneone::enums::request_methods::*;
 use oneone::{Request, Response};
-use tokio::sync::mpsc::Sender;
 use tracing::{error, trace};
 
 use super::OneOneStruct;
 use crate::CommanderRequest;
 use crate::commander::CommanderResponse;
+use crate::commander::communicate::response::HttpLogInfo;
 use crate::commander::communicate::response::convert::WrongMessage;
-use crate::history::message::from_commander::CommanderToHistory;
 use crate::proxy::handler_state::ShouldLog;
 
 const ACCEPT: &str = "Accept";
 
 impl<T, E> ShouldLog for OneOneStruct<T, E, Request> {
-    type LogResult = (usize, PathBuf, Sender<CommanderToHistory>);
+    type LogResult = HttpLogInfo;
 
     /* Steps:
-     *      1. Get the method as enum.
+     *      1. If mocked, always log. Return CommanderRequest::ShouldLogHttp
+     *         with empty extension.
      *
-     *      2. Match method.
+     *      2. Get the method as enum.
+     *
+     *      3. Match method.
      *          a. If GET,
      *              1. Get the uri as string by calling uri_as_string().
      *
@@ -48,7 +50,7 @@ impl<T, E> ShouldLog for OneOneStruct<T, E, Request> {
      *
      *          c. Else return Some("").
      *
-     *      3. Default, CommanderRequest::ShouldLogHttp(id, ext).
+     *      4. Default, CommanderRequest::ShouldLogHttp(id, ext).
      *
      * Returns:
      *      Option<CommanderRequest::ShouldLogHttp (id, ext)> |
@@ -56,11 +58,18 @@ impl<T, E> ShouldLog for OneOneStruct<T, E, Request> {
      */
 
     fn get_log_request(&self) -> Option<CommanderRequest> {
+        // 1. Mocked
+        if self.is_mocked() {
+            return Some(CommanderRequest::ShouldLogHttp(
+                self.id,
+                String::new(),
+            ));
+        }
         let frame = self.frame.as_ref().unwrap(); // safe to unwrap
         let method = frame.method_as_enum();
 
         let ext = match method {
-            // 2.a. If GET
+            // 3.a. If GET
             Method::GET => {
                 let uri = frame.uri_as_string();
                 trace!("URI| {}", uri);
@@ -103,30 +112,23 @@ impl<T, E> ShouldLog for OneOneStruct<T, E, Request> {
 
     /* Steps:
      *      Use TryFrom trait implementation in communicate/response/convert.rs
-     *      to convert CommanderResponse::HttpLog to
-     *      Option<(usize, PathBuf, Sender<CommanderToHistory>)>
+     *      to convert CommanderResponse::HttpLog to Option<HttpLogInfo>
      */
 
     fn parse_log_response(
         &self,
         response: CommanderResponse,
     ) -> Result<Option<Self::LogResult>, WrongMessage> {
-        Option::<(usize, PathBuf, Sender<CommanderToHistory>)>::try_from(
-            response,
-        )
+        Option::<HttpLogInfo>::try_from(response)
     }
 
-    fn update_path(
-        &mut self,
-        (index, path, history_sender): (
-            usize,
-            PathBuf,
-            Sender<CommanderToHistory>,
-        ),
-    ) {
-        self.log_id = index;
-        self.path = Some(path);
-        self.history_sendr = Some(history_sender);
+    fn update_path(&mut self, info: HttpLogInfo) {
+        self.log_id = info.index;
+        self.path = Some(info.path);
+        self.history_sendr = Some(info.history);
+        self.analyzer_sendr = Some(info.analyzer);
+        self.db_sendr = info.db;
+        self.intercept_response = info.intercept_response;
     }
 }
 
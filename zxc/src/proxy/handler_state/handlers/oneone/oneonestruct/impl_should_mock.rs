@@ -0,0 +1,44 @@
+use oneone::{Request, Response};
+
+use super::*;
+use crate::commander::communicate::response::convert::WrongMessage;
+use crate::proxy::handler_state::transition::should_mock::ShouldMock;
+
+/* Steps:
+ *      1. Get method and uri from the request frame.
+ *      2. Build CommanderRequest::ShouldMock with id, method, host and path.
+ */
+
+impl<T, E> ShouldMock for OneOneStruct<T, E, Request> {
+    fn get_mock_request(&self) -> Option<CommanderRequest> {
+        let frame = self.frame.as_ref().unwrap(); // safe to unwrap
+        Some(CommanderRequest::ShouldMock(
+            self.id,
+            frame.method_as_string().to_string(),
+            self.server_info.address_to_string(),
+            frame.uri_as_string().to_string(),
+        ))
+    }
+
+    fn update_mock(
+        &mut self,
+        response: CommanderResponse,
+    ) -> Result<(), WrongMessage> {
+        self.mock = Option::<Mock>::try_from(response)?;
+        Ok(())
+    }
+}
+
+// Response is never mocked, update is a no-op
+impl<T, E> ShouldMock for OneOneStruct<T, E, Response> {
+    fn get_mock_request(&self) -> Option<CommanderRequest> {
+        None
+    }
+
+    fn update_mock(
+        &mut self,
+        _: CommanderResponse,
+    ) -> Result<(), WrongMessage> {
+        Ok(())
+    }
+}
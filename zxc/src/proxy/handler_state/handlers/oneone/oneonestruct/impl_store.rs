@@ -0,0 +1,38 @@
+use bytes::Bytes;
+use oneone::{Request, Response};
+use tracing::error;
+
+use super::OneOneStruct;
+use crate::history::db::writer::ToDb;
+use crate::proxy::server_info::json::ServerInfoJson;
+
+/* Description:
+ *      Store the logged message in the history db as sent, i.e. after
+ *      intercept and rewrite. No-op if db is not enabled.
+ */
+
+impl<T, E> OneOneStruct<T, E, Request> {
+    pub fn store(&self, info: &ServerInfoJson) {
+        let (Some(db), Some(payload)) = (&self.db_sendr, &self.payload) else {
+            return;
+        };
+        let info = serde_json::to_string(info).unwrap_or_default();
+        let msg =
+            ToDb::Request(self.log_id, Bytes::copy_from_slice(payload), info);
+        if let Err(e) = db.send(msg) {
+            error!("db request| {}", e);
+        }
+    }
+}
+
+impl<T, E> OneOneStruct<T, E, Response> {
+    pub fn store(&self) {
+        let (Some(db), Some(payload)) = (&self.db_sendr, &self.payload) else {
+            return;
+        };
+        let msg = ToDb::Response(self.log_id, Bytes::copy_from_slice(payload));
+        if let Err(e) = db.send(msg) {
+            error!("db response| {}", e);
+        }
+    }
+}
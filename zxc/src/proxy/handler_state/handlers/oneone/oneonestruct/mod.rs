@@ -1,14 +1,18 @@
 use std::fmt::{Debug, Display};
 use std::path::PathBuf;
+use std::time::Instant;
 
 use bytes::BytesMut;
 use oneone::{InfoLine, OneOne};
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender};
 use zxc_derive::{CanCommunicate, FileOps, GetRole, Id};
 
 use crate::commander::{CommanderRequest, CommanderResponse};
+use crate::config::Mock;
+use crate::history::analyzer::ToAnalyzer;
+use crate::history::db::writer::ToDb;
 use crate::history::message::from_commander::CommanderToHistory;
 use crate::id::Id;
 use crate::proxy::handler_state::role::{GetRole, Role};
@@ -16,8 +20,10 @@ use crate::proxy::handler_state::transition::can_communicate::CanCommunicate;
 use crate::proxy::server_info::ServerInfo;
 use crate::proxy::server_info::address::Address;
 use crate::proxy::server_info::scheme::Scheme;
+use crate::proxy::server_info::upstream::Upstream;
 mod convert;
 mod impl_add_raw;
+mod impl_analyze;
 mod impl_bytes_to_frame;
 mod impl_can_log;
 mod impl_drop_msg;
@@ -25,19 +31,32 @@ mod impl_frame_to_payload;
 mod impl_get_history;
 mod impl_intercept;
 mod impl_log;
+mod impl_map_remote;
 mod impl_read_write;
 mod impl_reconnect;
 mod impl_rewrite;
 mod impl_send_history;
 mod impl_should_intercept;
 mod impl_should_log;
+mod impl_should_mock;
 mod impl_should_rewrite;
+mod impl_store;
 mod impl_update_log_extension;
 mod impl_update_resume_info;
 pub use impl_read_write::OneOneRWError;
 
+use super::ReadMeta;
 use crate::proxy::handler_state::transition::write_log::file_ops::FileOps;
 
+// Time request was sent, upstream info and read meta of response, used in
+// response history
+#[derive(Debug, Default)]
+pub struct HttpMeta {
+    sent: Option<Instant>,
+    upstream: Upstream,
+    read: Option<ReadMeta>,
+}
+
 // http/1.1 handler struct.
 #[derive(FileOps, GetRole, CanCommunicate, Id)]
 pub struct OneOneStruct<T, E, U>
@@ -57,8 +76,15 @@ where
     pub reader: T,
     pub writer: E,
     history_sendr: Option<Sender<CommanderToHistory>>,
+    analyzer_sendr: Option<UnboundedSender<ToAnalyzer>>,
+    db_sendr: Option<UnboundedSender<ToDb>>,
     role: Role,
     need_response: bool,
+    intercept_response: bool,
+    mock: Option<Mock>,
+    original: Option<String>,
+    need_new_conn: bool,
+    meta: HttpMeta,
 }
 
 impl<T, E, U> OneOneStruct<T, E, U>
@@ -73,8 +99,38 @@ where
         self.server_info.address()
     }
 
+    // Connection to the new server is established by the caller
     pub fn set_server_info(&mut self, server_info: ServerInfo) {
         self.server_info = server_info;
+        self.need_new_conn = false;
+    }
+
+    pub fn is_mocked(&self) -> bool {
+        self.mock.is_some()
+    }
+
+    pub fn take_mock(&mut self) -> Option<Mock> {
+        self.mock.take()
+    }
+
+    pub fn set_mock(&mut self, mock: Mock) {
+        self.mock = Some(mock);
+    }
+
+    /* Description:
+     *      Server connection of a mocked connection is not established.
+     *      Request that is not mocked is sent on a new connection
+     *      [ ReadWrite::write() => ProxyState::NewConnection ]
+     */
+
+    pub fn defer_connection(&mut self) {
+        self.need_new_conn = true;
+    }
+
+    // Called after the request is written to server
+    pub fn set_sent(&mut self, upstream: Upstream) {
+        self.meta.sent = Some(Instant::now());
+        self.meta.upstream = upstream;
     }
 }
 
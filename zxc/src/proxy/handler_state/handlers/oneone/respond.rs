@@ -0,0 +1,255 @@
+use std::path::Path;
+
+use bytes::BytesMut;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{error, trace};
+
+use super::error::HandleOneOneError;
+use super::{OneOneRequest, OneOneResponse, ServerState};
+use crate::async_step::async_run;
+use crate::config::Mock;
+use crate::history::message::from_commander::CommanderToHistory;
+use crate::io::file::{FileErrorInfo, FileEvent, read_file};
+use crate::proxy::handler_state::ProxyState;
+use crate::proxy::handler_state::error::ProxyStateError;
+use crate::proxy::handler_state::transition::frame_to_payload::FrameToPayload;
+use crate::proxy::handler_state::transition::update_frame::bytes_to_frame::BytesToFrame;
+use crate::proxy::handler_state::transition::write_history::*;
+use crate::proxy::handler_state::transition::write_log::log::Log;
+use crate::proxy::handler_state::transition::write_log::update_log_extension::UpdateLogExt;
+use crate::proxy::handler_state::transition::write_log::write_log;
+
+const NOT_FOUND: &[u8] =
+    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n";
+const BAD_GATEWAY: &[u8] =
+    b"HTTP/1.1 502 Bad Gateway\r\nContent-Length: 0\r\n\r\n";
+
+/* Description:
+ *      Function to respond to the client without writing the request to the
+ *      server, either with the response written in interceptor ui or with
+ *      the response of the matched mock rule.
+ *
+ *      Server connection is not established for a mocked tcp request or for
+ *      a tls connection whose host matches a mock rule, as mock rules are
+ *      checked before connecting [ ConnectionState::ShouldMock ]. For other
+ *      tls connections, the server is connected for certificate mimicry
+ *      before the request is read.
+ *
+ * Steps:
+ *      1. Take the mock and convert OneOneRequest to OneOneResponse. If
+ *         logged, set the .res extension.
+ *
+ *      2. Read the response
+ *          a. mocked       => read_mock()
+ *          b. not mocked   => open and read the .res file written by ui.
+ *                             If it can't be opened, respond with 502.
+ *
+ *      3. Parse the buf as response, Content-Length is updated
+ *         [ update_one_one::<Response> ]
+ *
+ *      4. If not logged (mocked only), convert frame to payload and run
+ *         server_state from ProxyState::Send.
+ *
+ *      5. Get the response history, mark it as mocked or synthesized and
+ *         send it to history.
+ *
+ *      6. Write the .res file with the updated frame [ write_log() ] and
+ *         run server_state from ProxyState::Send. Analyze and intercept are
+ *         skipped, since the response is not received from server.
+ *
+ *      7. Convert ServerState to OneOneResponse and return ProxyState::End
+ *
+ * Error:
+ *      HandleOneOneError::ProxyError
+ *          ProxyStateError::FileIo             [2] [6]
+ *          ProxyStateError::UpdateFrame        [3]
+ *          ProxyStateError::OneOne             [4] [6]
+ *          ProxyStateError::Serialize          [5]
+ *          ProxyStateError::HistorySend        [5]
+ */
+
+pub async fn respond<T, E>(
+    mut client_conn: OneOneRequest<T, E>,
+) -> Result<ServerState<E, T>, HandleOneOneError<T, E>>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin,
+    E: AsyncReadExt + AsyncWriteExt + Unpin,
+    OneOneRequest<T, E>: Into<OneOneResponse<E, T>>,
+{
+    // 1. Convert to response
+    let mock = client_conn.take_mock();
+    let mut server_conn: OneOneResponse<E, T> = client_conn.into();
+    let logged = server_conn.path.is_some();
+    if logged {
+        server_conn.update_extension();
+        trace!("respond| {}", server_conn.path().display());
+    }
+
+    // 2. Read response
+    if let Some(mock) = mock.as_ref() {
+        trace!("mock| {:?}", mock);
+        read_mock(mock, &mut server_conn.buf).await
+    } else {
+        let path = server_conn.path().clone();
+        match File::open(&path).await {
+            Ok(mut file) => read_file(&mut file, &mut server_conn.buf).await,
+            Err(e) => {
+                error!("respond| {}| {}", path.display(), e);
+                server_conn
+                    .buf
+                    .extend_from_slice(BAD_GATEWAY);
+                Ok(())
+            }
+        }
+    }
+    .map_err(ProxyStateError::from)?;
+
+    // 3. Parse frame
+    let buf = server_conn.buf.split();
+    let frame = server_conn
+        .parse_frame(buf)
+        .map_err(ProxyStateError::from)?;
+    server_conn.add_frame(frame);
+
+    // 4. Not logged, send
+    if !logged {
+        server_conn.frame_to_payload();
+        let server_state = async_run(ProxyState::Send(server_conn)).await?;
+        let server_conn = OneOneResponse::<E, T>::try_from(server_state)?;
+        trace!("responded");
+        return Ok(ProxyState::End(server_conn));
+    }
+
+    // 5. Send history
+    let mut history = server_conn.get_history();
+    if mock.is_some() {
+        history.set_mocked();
+    } else {
+        history.set_synthesized();
+    }
+    let history = CommanderToHistory::try_from(history)
+        .map_err(ProxyStateError::from)?;
+    server_conn
+        .get_sender()
+        .send(history)
+        .await
+        .map_err(ProxyStateError::from)?;
+
+    // 6. Log and send
+    let ProxyState::Analyze(server_conn) = write_log(server_conn).await?
+    else {
+        unreachable!()
+    };
+    let server_state = async_run(ProxyState::Send(server_conn)).await?;
+    let server_conn = OneOneResponse::<E, T>::try_from(server_state)?;
+    trace!("responded");
+    Ok(ProxyState::End(server_conn))
+}
+
+/* Description:
+ *      Read the response of the matched mock rule into buf.
+ *
+ * Steps:
+ *      1. Mock::Response, read the raw http response file.
+ *
+ *      2. Mock::Local, read the file and build a 200 response with
+ *         Content-Type from the file extension. If the file can't be read,
+ *         build a 404 response.
+ *
+ * Error:
+ *      FileErrorInfo   [1]
+ */
+
+async fn read_mock(
+    mock: &Mock,
+    buf: &mut BytesMut,
+) -> Result<(), FileErrorInfo> {
+    match mock {
+        Mock::Response(path) => {
+            let mut file = File::open(path).await.map_err(|e| {
+                FileErrorInfo::from((path, FileEvent::Open, e))
+            })?;
+            read_file(&mut file, buf).await
+        }
+        Mock::Local(path) => {
+            match tokio::fs::read(path).await {
+                Ok(body) => {
+                    let header = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+                        local_content_type(path),
+                        body.len()
+                    );
+                    buf.extend_from_slice(header.as_bytes());
+                    buf.extend_from_slice(&body);
+                }
+                Err(e) => {
+                    trace!("local| {}| {}", path.display(), e);
+                    buf.extend_from_slice(NOT_FOUND);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// Content-Type of the file served from local directory
+fn local_content_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" | "map" => "application/json",
+        "xml" => "application/xml",
+        "txt" => "text/plain",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_local_content_type() {
+        assert_eq!(
+            local_content_type(Path::new("/a/app.JS")),
+            "text/javascript"
+        );
+        assert_eq!(
+            local_content_type(Path::new("/a/index.html")),
+            "text/html"
+        );
+        assert_eq!(
+            local_content_type(Path::new("/a/blob")),
+            "application/octet-stream"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_mock_local_not_found() {
+        let mock =
+            Mock::Local(PathBuf::from("/zxc_mock_not_found/index.html"));
+        let mut buf = BytesMut::new();
+        read_mock(&mock, &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(buf, NOT_FOUND);
+    }
+}
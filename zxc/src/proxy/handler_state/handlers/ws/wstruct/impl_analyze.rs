@@ -0,0 +1,15 @@
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::WsStruct;
+use crate::history::analyzer::ToAnalyzer;
+use crate::proxy::handler_state::transition::analyze::Analyze;
+
+// Ws messages are not analyzed
+impl<T, E> Analyze for WsStruct<T, E> {
+    #[inline(always)]
+    fn analyze_msg(
+        &self,
+    ) -> Option<(&UnboundedSender<ToAnalyzer>, ToAnalyzer)> {
+        None
+    }
+}
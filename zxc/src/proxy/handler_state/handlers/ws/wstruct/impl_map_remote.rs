@@ -0,0 +1,19 @@
+use super::WsStruct;
+use crate::commander::communicate::response::convert::WrongMessage;
+use crate::commander::{CommanderRequest, CommanderResponse};
+use crate::proxy::handler_state::transition::map_remote::MapRemote;
+
+// Ws messages are never remapped, update is a no-op
+impl<T, E> MapRemote for WsStruct<T, E> {
+    #[inline(always)]
+    fn get_remap_request(&self) -> Option<CommanderRequest> {
+        None
+    }
+
+    fn update_remap(
+        &mut self,
+        _: CommanderResponse,
+    ) -> Result<(), WrongMessage> {
+        Ok(())
+    }
+}
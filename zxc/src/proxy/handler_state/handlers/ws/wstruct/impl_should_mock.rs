@@ -0,0 +1,19 @@
+use super::WsStruct;
+use crate::commander::communicate::response::convert::WrongMessage;
+use crate::commander::{CommanderRequest, CommanderResponse};
+use crate::proxy::handler_state::transition::should_mock::ShouldMock;
+
+// Ws messages are never mocked, update is a no-op
+impl<T, E> ShouldMock for WsStruct<T, E> {
+    #[inline(always)]
+    fn get_mock_request(&self) -> Option<CommanderRequest> {
+        None
+    }
+
+    fn update_mock(
+        &mut self,
+        _: CommanderResponse,
+    ) -> Result<(), WrongMessage> {
+        Ok(())
+    }
+}
@@ -20,17 +20,20 @@ use super::*;
 use crate::commander::{CommanderRequest, CommanderResponse};
 use crate::proxy::handler_state::role::{GetRole, Role};
 mod impl_add_raw;
+mod impl_analyze;
 mod impl_bytes_to_frame;
 mod impl_can_log;
 mod impl_frame_to_payload;
 mod impl_get_history;
 mod impl_intercept;
 mod impl_log;
+mod impl_map_remote;
 mod impl_read_write;
 mod impl_rewrite;
 mod impl_send_history;
 mod impl_should_intercept;
 mod impl_should_log;
+mod impl_should_mock;
 mod impl_should_rewrite;
 mod impl_update_log_extension;
 mod impl_update_resume_info;
@@ -7,6 +7,7 @@ use std::fmt::Display;
 
 use bytes::BytesMut;
 use role::GetRole;
+use transition::analyze::{Analyze, analyze};
 use transition::can_communicate::CanCommunicate;
 use transition::drop_msg::DropMsg;
 use transition::frame_to_payload::FrameToPayload;
@@ -27,10 +28,12 @@ pub mod additional_handler_info;
 use self::error::ProxyStateError;
 use self::read_write::ReadWrite;
 use self::transition::intercept::*;
+use self::transition::map_remote::{MapRemote, map_remote};
 use self::transition::resume_intercept::resume_intercept;
 use self::transition::rewrite::rewrite_log;
 use self::transition::should_intercept::*;
 use self::transition::should_log::*;
+use self::transition::should_mock::{ShouldMock, should_mock};
 use super::server_info::json::ServerInfoJson;
 use crate::async_step::AsyncStep;
 use crate::commander::Protocol;
@@ -41,9 +44,12 @@ use crate::interceptor::message::from_ui::resume_info::ResumeInfo;
 // In order
 pub enum ProxyState<T> {
     Receive(T),
+    ShouldMock(T), // http req only
+    MapRemote(T),  // http req only
     ShouldLog(T),
     WriteHistory(T),
     Log(T),
+    Analyze(T), // http res only
     ShouldIntercept(T),
     Intercept(T),
     ResumeIntercept(T),
@@ -51,6 +57,7 @@ pub enum ProxyState<T> {
     UpdateFrame(T, BytesMut, ResumeInfo),
     ReWrite(T, ResumeInfo),
     NewConnection(T, ServerInfoJson), // http only
+    Respond(T),                       // http req only
     Send(T),
     Drop(T),
     End(T),
@@ -82,10 +89,13 @@ where
         + ReadWrite<State = Self>
         + CanCommunicate
         + GetRole
+        + ShouldMock
+        + MapRemote
         + CanLog
         + ShouldLog
         + UpdateLogExt
         + Log
+        + Analyze
         + GetHistory
         + SendHistory
         + ShouldIntercept
@@ -109,38 +119,46 @@ where
         Self: Sized,
     {
         match self {
-            // 1. Read -> ShouldLog | ServerClose
+            // 1. Read -> ShouldMock | ServerClose
             Self::Receive(conn) => conn.read().await.map_err(Into::into),
-            // 2. ShouldLog -> WriteHistory | Send
+            // 2. ShouldMock -> MapRemote
+            Self::ShouldMock(conn) => should_mock(conn).await,
+            // 3. MapRemote -> ShouldLog
+            Self::MapRemote(conn) => map_remote(conn).await,
+            // 4. ShouldLog -> WriteHistory | Send
             Self::ShouldLog(conn) => should_log(conn).await,
-            // 3. WriteHistory -> Log
+            // 5. WriteHistory -> Log
             Self::WriteHistory(conn) => write_history(conn).await,
-            // 4. Log -> ShouldIntercept
+            // 6. Log -> Analyze
             Self::Log(conn) => write_log(conn).await,
-            // 5. ShouldIntercept -> Intercept | Send
+            // 7. Analyze -> ShouldIntercept
+            Self::Analyze(conn) => analyze(conn).await,
+            // 8. ShouldIntercept -> Intercept | Send
             Self::ShouldIntercept(conn) => should_intercept(conn).await,
-            // 6. Intercept -> ResumeIntercept | Drop
+            // 9. Intercept -> ResumeIntercept | Drop
             Self::Intercept(conn) => intercept(conn).await,
-            // 7. Drop -> End
+            // 10. Drop -> Receive (ws) | Error (http)
             Self::Drop(conn) => {
                 T::continue_on_drop()?;
                 Ok(Self::Receive(conn))
             }
-            // 7. ResumeIntercept -> ReadModFile | NewConnection | Send
+            // 11. ResumeIntercept -> ReadModFile | NewConnection | Respond |
+            //     Send
             Self::ResumeIntercept(conn) => resume_intercept(conn).await,
-            // 8. ReadModFile -> UpdateFrame | Send
+            // 12. ReadModFile -> UpdateFrame | Send
             Self::ReadModFile(conn, resume_info) => {
                 read_mod_file(conn, resume_info).await
             }
-            // 9. UpdateFrame -> ReWrite | Send
+            // 13. UpdateFrame -> ReWrite | Send
             Self::UpdateFrame(conn, buf, resume_info) => {
                 update_frame_state(conn, buf, resume_info)
             }
-            // 10. ReWrite -> Send
+            // 14. ReWrite -> Send
             Self::ReWrite(conn, resume_info) => {
                 rewrite_log(conn, resume_info).await
             }
-            // 11. Send -> End | ReadFail | Read (full duplex)
+            // 15. Send -> End | Respond | NewConnection | ReadFail |
+            //     Read (full duplex)
             Self::Send(conn) => conn.write().await.map_err(Into::into),
             _ => Ok(self),
         }
@@ -151,6 +169,7 @@ where
         matches!(self, Self::End(_))
             || matches!(self, Self::ServerClose(_, _))
             || matches!(self, Self::NewConnection(_, _))
+            || matches!(self, Self::Respond(_))
     }
 }
 
@@ -161,15 +180,19 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let (conn, val) = match self {
             Self::Receive(conn) => (conn, "receive"),
+            Self::ShouldMock(conn) => (conn, "should_mock"),
+            Self::MapRemote(conn) => (conn, "map_remote"),
             Self::ShouldLog(conn) => (conn, "should_log"),
             Self::WriteHistory(conn) => (conn, "write_history"),
             Self::Log(conn) => (conn, "log"),
+            Self::Analyze(conn) => (conn, "analyze"),
             Self::ShouldIntercept(conn) => (conn, "should_intercept"),
             Self::Intercept(conn) => (conn, "intercept"),
             Self::ResumeIntercept(conn) => (conn, "resume_intercept"),
             Self::ReadModFile(conn, _) => (conn, "read_mod_file"),
             Self::UpdateFrame(conn, ..) => (conn, "update_frame"),
             Self::ReWrite(conn, _) => (conn, "rewrite"),
+            Self::Respond(conn) => (conn, "respond"),
             Self::Send(conn) => (conn, "send"),
             Self::SwitchProtocol(conn, _) => (conn, "switch_protocol"),
             _ => return Err(std::fmt::Error),
@@ -0,0 +1,51 @@
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::trace;
+
+use crate::history::analyzer::ToAnalyzer;
+use crate::proxy::handler_state::{ProxyState, ProxyStateError};
+
+/* Description:
+ *      Trait to get the passive analysis request of the logged message.
+ *
+ *      http response only
+ */
+
+pub trait Analyze {
+    // Analyzer sender and message, None if not analyzed
+    fn analyze_msg(
+        &self,
+    ) -> Option<(&UnboundedSender<ToAnalyzer>, ToAnalyzer)>;
+}
+
+/* Description:
+ *      Transition function to request passive analysis of the logged
+ *      response from the analyzer task.
+ *
+ * Transition:
+ *      Analyze -> ShouldIntercept
+ *
+ * Steps:
+ *      1. Get ToAnalyzer::Response(log_id) by calling analyze_msg(). None
+ *         for request and ws.
+ *
+ *      2. If Some, send to the unbounded analyzer queue, so that the flow is
+ *         not blocked and no response is skipped. Cookie jar is updated by
+ *         the analyzer.
+ *
+ * Returns:
+ *      Ok(ProxyState::ShouldIntercept)
+ *
+ * Error:
+ *      ProxyStateError::AnalyzerSend   [2]
+ */
+
+pub async fn analyze<T>(conn: T) -> Result<ProxyState<T>, ProxyStateError>
+where
+    T: Analyze,
+{
+    if let Some((sender, msg)) = conn.analyze_msg() {
+        sender.send(msg)?;
+    }
+    trace!("Y");
+    Ok(ProxyState::ShouldIntercept(conn))
+}
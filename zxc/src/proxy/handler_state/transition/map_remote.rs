@@ -0,0 +1,67 @@
+use tracing::trace;
+
+use super::can_communicate::CanCommunicate;
+use crate::commander::communicate::response::convert::WrongMessage;
+use crate::commander::{CommanderRequest, CommanderResponse};
+use crate::proxy::handler_state::{ProxyState, ProxyStateError};
+
+const MAP_REMOTE: &str = "MapRemote";
+
+/* Description:
+ *      Trait to redirect request to another server.
+ *
+ *      http request only
+ */
+
+pub trait MapRemote {
+    fn get_remap_request(&self) -> Option<CommanderRequest>;
+
+    fn update_remap(
+        &mut self,
+        response: CommanderResponse,
+    ) -> Result<(), WrongMessage>;
+}
+
+/* Description:
+ *      Transition function to check if request matches any remote rule.
+ *
+ * Transition:
+ *      MapRemote -> ShouldLog
+ *
+ * Steps:
+ *      1. Get CommanderRequest::MapRemote(id, scheme, host, uri) by calling
+ *         get_remap_request(). None for response and mocked request.
+ *
+ *      2. If request is some, send the query to the commander and receive
+ *         the response.
+ *
+ *      3. Pass the response to update_remap() to rewrite the request and
+ *         server info.
+ *
+ * Returns:
+ *      Ok(ProxyState::ShouldLog)
+ *
+ * Error:
+ *      ProxyStateError::CommanderRequest       [2]
+ *      ProxyStateError::CommanderResponse      [2]
+ *      ProxyStateError::WrongMessage           [3]
+ */
+
+pub async fn map_remote<T>(
+    mut conn: T,
+) -> Result<ProxyState<T>, ProxyStateError>
+where
+    T: MapRemote + CanCommunicate,
+{
+    if let Some(req) = conn.get_remap_request() {
+        conn.sender().send(req).await?;
+        let response = conn
+            .receiver()
+            .recv()
+            .await
+            .ok_or(ProxyStateError::CommanderResponse(MAP_REMOTE))?;
+        conn.update_remap(response)?;
+    }
+    trace!("Y");
+    Ok(ProxyState::ShouldLog(conn))
+}
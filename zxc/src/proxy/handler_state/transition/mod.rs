@@ -1,13 +1,16 @@
+pub mod analyze;
 pub mod can_communicate;
 pub mod drop_msg;
 pub mod frame_to_payload;
 pub mod intercept;
+pub mod map_remote;
 pub mod read_modified_file;
 pub mod reconnect;
 pub mod resume_intercept;
 pub mod rewrite;
 pub mod should_intercept;
 pub mod should_log;
+pub mod should_mock;
 pub mod update_frame;
 pub mod write_history;
 pub mod write_log;
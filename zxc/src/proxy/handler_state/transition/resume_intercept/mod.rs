@@ -12,24 +12,28 @@ use super::can_communicate::CanCommunicate;
  *      Transition function to resume interception.
  *
  * Transition:
- *      ResumeIntercept -> ReadModFile | NewConnection | Send
+ *      ResumeIntercept -> ReadModFile | NewConnection | Respond | Send
  *
  * Steps:
  *      1. Receive the result from commander_response.receiver
  *
- *      2. If the result is Some, update the resume info, whether
+ *      2. If the result is drop, return ProxyState::Drop. If the result is
+ *         respond, return ProxyState::Respond (http req only), response is
+ *         read from the .res file written by ui.
+ *
+ *      3. If the result is Some, update the resume info, whether
  *         response needs to be intercepted (http req only)
  *
- *      3. If the file is modified, return ProxyState::ReadModFile.
+ *      4. If the file is modified, return ProxyState::ReadModFile.
  *
- *      4. Else if the file is not modified and resume_info.req_info is Some,
+ *      5. Else if the file is not modified and resume_info.req_info is Some,
  *          return ProxyState::NewConnection (http req only)
  *
- *      5. Default, return ProxyState::Send
+ *      6. Default, return ProxyState::Send
  *
  * Error:
  *      ProxyStateError::CommanderResponse      [1]
- *      ProxyStateError::WrongCommand           [2]
+ *      ProxyStateError::WrongCommand           [3]
  */
 
 pub async fn resume_intercept<T>(
@@ -46,7 +50,11 @@ where
     if response.is_drop_msg() {
         return Ok(ProxyState::Drop(conn));
     }
-    // 2. If the result is Some, update the interception info, (http only)
+    if response.is_respond_msg() {
+        trace!("respond");
+        return Ok(ProxyState::Respond(conn));
+    }
+    // 3. If the result is Some, update the interception info, (http only)
     if let Some(resume_info) = Option::<ResumeInfo>::try_from(response)? {
         conn.update_resume_info(&resume_info);
         if resume_info.modified() {
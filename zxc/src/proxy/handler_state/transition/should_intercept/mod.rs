@@ -15,7 +15,7 @@ pub use trait_should_intercept::*;
  * Steps:
  *      If should_intercept() returns
  *          1. Some(true), return Intercept
- *          2. None (ws response only), call query_commander_should_intercept()
+ *          2. None (response only), call query_commander_should_intercept()
  *             and return Intercept if true
  *
  *      Default to Send
@@ -24,9 +24,10 @@ pub use trait_should_intercept::*;
  *      Ok(ProxyState::Intercept | ProxyState::Send)
  *
  * Errors:
- *      ws response only
+ *      response only
  *          ProxyStateError::CommanderRequest
- *          ProxyStateError::WsShouldIntercept
+ *          ProxyStateError::CommanderResponse  (http)
+ *          ProxyStateError::WsShouldIntercept  (ws)
  */
 
 pub async fn should_intercept<T>(
@@ -41,7 +42,7 @@ where
             true
         }
         None => {
-            trace!("wres");
+            trace!("query response");
             conn.query_commander_should_intercept()
                 .await?
         }
@@ -1,13 +1,15 @@
 use crate::proxy::handler_state::error::ProxyStateError;
 
 /* Description:
- *      Trait to check with commander whether ws response should be intercepted.
+ *      Trait to check with commander whether ws/http response should be
+ *      intercepted.
  *
- *      Ws implementation only.
+ *      Ws and http response implementation only.
  *
  * Error:
  *      ProxyStateError::CommanderRequest
- *      ProxyStateError::WsShouldIntercept
+ *      ProxyStateError::CommanderResponse  (http)
+ *      ProxyStateError::WsShouldIntercept  (ws)
  */
 
 pub trait QueryCommanderShouldIntercept {
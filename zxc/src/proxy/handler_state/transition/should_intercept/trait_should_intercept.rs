@@ -3,8 +3,11 @@
  *
  *      For http,
  *          1. request, Some(true)
- *          2. response, oneonestruct.need_response, set in update_resume_info
- *             state's need_response var set by interceptor ui.
+ *          2. response, Some(true) if oneonestruct.need_response, set in
+ *             update_resume_info state's need_response var set by interceptor
+ *             ui. Some(false) if response interception was inactive when
+ *             the request was logged. Else None, query commander for
+ *             response rules.
  *
  *      For ws,
  *          1. request, Some(true).
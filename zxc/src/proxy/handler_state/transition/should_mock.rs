@@ -0,0 +1,66 @@
+use tracing::trace;
+
+use super::can_communicate::CanCommunicate;
+use crate::commander::communicate::response::convert::WrongMessage;
+use crate::commander::{CommanderRequest, CommanderResponse};
+use crate::proxy::handler_state::{ProxyState, ProxyStateError};
+
+const SHOULD_MOCK: &str = "ShouldMock";
+
+/* Description:
+ *      Trait to check if request should be mocked.
+ *
+ *      http request only
+ */
+
+pub trait ShouldMock {
+    fn get_mock_request(&self) -> Option<CommanderRequest>;
+
+    fn update_mock(
+        &mut self,
+        response: CommanderResponse,
+    ) -> Result<(), WrongMessage>;
+}
+
+/* Description:
+ *      Transition function to check if request matches any mock rule.
+ *
+ * Transition:
+ *      ShouldMock -> MapRemote
+ *
+ * Steps:
+ *      1. Get CommanderRequest::ShouldMock(id, method, host, path) by
+ *         calling get_mock_request(). None for response.
+ *
+ *      2. If request is some, send the query to the commander and receive
+ *         the response.
+ *
+ *      3. Pass the response to update_mock() to store the matched mock.
+ *
+ * Returns:
+ *      Ok(ProxyState::MapRemote)
+ *
+ * Error:
+ *      ProxyStateError::CommanderRequest       [2]
+ *      ProxyStateError::CommanderResponse      [2]
+ *      ProxyStateError::WrongMessage           [3]
+ */
+
+pub async fn should_mock<T>(
+    mut conn: T,
+) -> Result<ProxyState<T>, ProxyStateError>
+where
+    T: ShouldMock + CanCommunicate,
+{
+    if let Some(req) = conn.get_mock_request() {
+        conn.sender().send(req).await?;
+        let response = conn
+            .receiver()
+            .recv()
+            .await
+            .ok_or(ProxyStateError::CommanderResponse(SHOULD_MOCK))?;
+        conn.update_mock(response)?;
+    }
+    trace!("Y");
+    Ok(ProxyState::MapRemote(conn))
+}
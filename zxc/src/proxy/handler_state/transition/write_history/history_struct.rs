@@ -1,6 +1,8 @@
 use std::borrow::Cow;
 use std::fmt::Display;
+use std::net::IpAddr;
 
+use mime::ContentType;
 use serde::{Deserialize, Serialize};
 
 use crate::history::message::from_commander::CommanderToHistory;
@@ -24,6 +26,20 @@ impl HistoryEnum<'_> {
             Self::WebSocket(..) => None,
         }
     }
+
+    // Mark http response as synthesized, i.e. not received from server
+    pub fn set_synthesized(&mut self) {
+        if let Self::Response(resp) = self {
+            resp.synthesized = Some(true);
+        }
+    }
+
+    // Mark http response as mocked, i.e. served from a mock rule
+    pub fn set_mocked(&mut self) {
+        if let Self::Response(resp) = self {
+            resp.mocked = Some(true);
+        }
+    }
 }
 
 impl TryFrom<HistoryEnum<'_>> for CommanderToHistory {
@@ -45,6 +61,9 @@ impl TryFrom<HistoryEnum<'_>> for CommanderToHistory {
 // Struct to represent the history data of the http request.
 // {"Request":{"id":1,"method":"GET","http":bool,"host":"www.google.com","
 // uri":"/robots.txt"}}
+//
+// original is set when the request was redirected by a remote rule, host and
+// uri are the rewritten target.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RequestHistory<'a> {
     id: usize,
@@ -53,6 +72,8 @@ pub struct RequestHistory<'a> {
     http: Option<bool>,
     host: String,
     uri: Cow<'a, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original: Option<String>,
 }
 
 impl<'a> RequestHistory<'a> {
@@ -69,17 +90,56 @@ impl<'a> RequestHistory<'a> {
             method,
             host,
             uri,
+            original: None,
         }
     }
+
+    pub fn set_original(&mut self, original: String) {
+        self.original = Some(original);
+    }
 }
 
 // Struct to represent the history data of the http response.
 // {"Response":{"id":0,"status":"200","length":2000,"mime":"img"}}
+//
+// synthesized is set when the response was written in interceptor instead of
+// being received from server. mocked is set when the response was served
+// from a mock rule. length is the decoded body size.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ResponseHistory<'a> {
     id: usize,
     status: Cow<'a, str>,
     length: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    synthesized: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mocked: Option<bool>,
+    #[serde(flatten)]
+    meta: ResponseMeta,
+}
+
+// Struct to represent the metadata of the http response.
+// {"mime":"txt","encoding":"gzip","encoded_length":512,"ttfb":20,
+// "duration":35,"ip":"93.184.216.34","tls":"TLSv1.3"}
+//
+// encoding and encoded_length are set when the body was content encoded.
+// ttfb and duration are in milliseconds from the time the request was sent.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ResponseMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime: Option<ContentType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoded_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttfb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<IpAddr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<String>,
 }
 
 impl<'a> ResponseHistory<'a> {
@@ -92,8 +152,15 @@ impl<'a> ResponseHistory<'a> {
             id,
             status,
             length,
+            synthesized: None,
+            mocked: None,
+            meta: ResponseMeta::default(),
         }
     }
+
+    pub fn set_meta(&mut self, meta: ResponseMeta) {
+        self.meta = meta;
+    }
 }
 
 // Struct to represent the history data of the ws.
@@ -181,6 +248,71 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_request_history_original() {
+        let mut req_history = RequestHistory::new(
+            1,
+            std::borrow::Cow::Borrowed("GET"),
+            Scheme::Http,
+            "localhost:8443".to_owned(),
+            "/v3/users".to_owned().into(),
+        );
+        req_history.set_original("https://api.example.com/v2/users".into());
+        let his = HistoryEnum::Request(req_history);
+        let out = serde_json::to_string(&his).unwrap();
+        assert_eq!(
+            out,
+            r#"{"Request":{"id":1,"method":"GET","http":true,"host":"localhost:8443","uri":"/v3/users","original":"https://api.example.com/v2/users"}}"#
+        );
+    }
+
+    #[test]
+    fn test_response_history_synthesized() {
+        let res_history =
+            ResponseHistory::new(0, String::from_utf8_lossy(b"500"), 5);
+        let mut his = HistoryEnum::Response(res_history);
+        his.set_synthesized();
+        let out = serde_json::to_string(&his).unwrap();
+        assert_eq!(
+            out,
+            r#"{"Response":{"id":0,"status":"500","length":5,"synthesized":true}}"#
+        )
+    }
+
+    #[test]
+    fn test_response_history_mocked() {
+        let res_history =
+            ResponseHistory::new(0, String::from_utf8_lossy(b"200"), 5);
+        let mut his = HistoryEnum::Response(res_history);
+        his.set_mocked();
+        let out = serde_json::to_string(&his).unwrap();
+        assert_eq!(
+            out,
+            r#"{"Response":{"id":0,"status":"200","length":5,"mocked":true}}"#
+        )
+    }
+
+    #[test]
+    fn test_response_history_meta() {
+        let mut res_history =
+            ResponseHistory::new(0, String::from_utf8_lossy(b"200"), 20);
+        res_history.set_meta(ResponseMeta {
+            mime: Some(ContentType::Text),
+            encoding: Some("gzip".to_string()),
+            encoded_length: Some(10),
+            ttfb: Some(12),
+            duration: Some(15),
+            ip: Some("127.0.0.1".parse().unwrap()),
+            tls: Some("TLSv1.3".to_string()),
+        });
+        let his = HistoryEnum::Response(res_history);
+        let out = serde_json::to_string(&his).unwrap();
+        let verify = r#"{"Response":{"id":0,"status":"200","length":20,"mime":"txt","encoding":"gzip","encoded_length":10,"ttfb":12,"duration":15,"ip":"127.0.0.1","tls":"TLSv1.3"}}"#;
+        assert_eq!(out, verify);
+        let his: HistoryEnum = serde_json::from_str(verify).unwrap();
+        assert_eq!(his.id(), Some(0));
+    }
+
     #[test]
     fn test_ws_history_binary() {
         let ws_history = WsHistory::new(0, &Role::Client, true, 100);
@@ -14,7 +14,7 @@ use update_log_extension::UpdateLogExt;
  *      Transition function to write the http/ws request/response to a file.
  *
  * Transition:
- *      WriteLog -> ShouldIntercept
+ *      WriteLog -> Analyze
  *
  * Steps:
  *      1. Update path to write to.
@@ -38,5 +38,5 @@ where
     let file = create_and_write_file(conn.path(), conn.log_data()).await?;
     conn.attach_file(file);
     trace!("Y");
-    Ok(ProxyState::ShouldIntercept(conn))
+    Ok(ProxyState::Analyze(conn))
 }
@@ -5,7 +5,7 @@ use super::scheme::Scheme;
 
 // Sent and received from ui
 #[cfg_attr(test, derive(Default))]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ServerInfoJson {
     pub host: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -8,6 +8,7 @@ use address::Address;
 use address::error::AddressError;
 use scheme::Scheme;
 pub mod json;
+pub mod upstream;
 
 // struct to store server info
 #[derive(Debug)]
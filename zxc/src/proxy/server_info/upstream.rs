@@ -0,0 +1,73 @@
+use std::net::IpAddr;
+
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::ProtocolVersion;
+
+use crate::proxy::states::{ClientTlsStream, ZStream};
+
+// Upstream server connection info, recorded in response history
+#[derive(Debug, Default)]
+pub struct Upstream {
+    pub ip: Option<IpAddr>,
+    pub tls: Option<&'static str>,
+}
+
+/* Description:
+ *      Trait to get upstream info from the server connection.
+ *      Implemented for Tcp and Tls server streams, and ZStream for mocked
+ *      connections without a server.
+ */
+
+pub trait GetUpstream {
+    fn upstream(&self) -> Upstream;
+}
+
+impl GetUpstream for TcpStream {
+    fn upstream(&self) -> Upstream {
+        Upstream {
+            ip: self
+                .peer_addr()
+                .ok()
+                .map(|addr| addr.ip()),
+            tls: None,
+        }
+    }
+}
+
+impl GetUpstream for ClientTlsStream<TcpStream> {
+    fn upstream(&self) -> Upstream {
+        let (tcp, conn) = self.get_ref();
+        Upstream {
+            ip: tcp.upstream().ip,
+            tls: conn.protocol_version().map(tls_version),
+        }
+    }
+}
+
+impl GetUpstream for ZStream {
+    fn upstream(&self) -> Upstream {
+        Upstream::default()
+    }
+}
+
+// Negotiated tls version as string
+fn tls_version(version: ProtocolVersion) -> &'static str {
+    match version {
+        ProtocolVersion::TLSv1_3 => "TLSv1.3",
+        ProtocolVersion::TLSv1_2 => "TLSv1.2",
+        ProtocolVersion::TLSv1_1 => "TLSv1.1",
+        ProtocolVersion::TLSv1_0 => "TLSv1.0",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_version() {
+        assert_eq!(tls_version(ProtocolVersion::TLSv1_3), "TLSv1.3");
+        assert_eq!(tls_version(ProtocolVersion::SSLv3), "unknown");
+    }
+}
@@ -0,0 +1,55 @@
+use tokio_rustls::StartHandshake;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::server::TlsStream as ServerTlsStream;
+
+use super::*;
+use crate::proxy::states::StateError;
+
+/* Description:
+ *      Completes client side handshake of a mocked host with the certificate
+ *      generated for the host, without server certificate.
+ *
+ * Steps:
+ *      1. Get sni from client_hello and ServerName by passing sni to
+ *         server_info.address.parse_sni(). Store it in server_info.
+ *
+ *      2. Complete the handshake by calling into_stream() with
+ *         server_config as arg on client stream.
+ *
+ * Error:
+ *      StateError::InvalidDns      [1]
+ *      StateError::ClientEncrypt   [2]
+ */
+
+impl<T> Connection<StartHandshake<T>, ZStream>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin, // Stream
+{
+    pub async fn mock_handshake(
+        self,
+        server_info: &mut ServerInfo,
+        server_config: Arc<ServerConfig>,
+    ) -> Result<Connection<ServerTlsStream<T>, ZStream>, StateError> {
+        // 1. Server Name
+        let server_name = server_info
+            .address()
+            .parse_sni(self.reader.client_hello().server_name())?
+            .to_owned();
+        server_info.set_sni(server_name);
+
+        // 2. Complete Handshake
+        let stream = self
+            .reader
+            .into_stream(server_config)
+            .await
+            .map_err(StateError::ClientEncrypt)?;
+        Ok(Connection {
+            id: self.id,
+            commander: self.commander,
+            reader: stream,
+            writer: self.writer,
+            buf: self.buf,
+            frame: self.frame,
+        })
+    }
+}
@@ -1,6 +1,7 @@
 mod client_handshake;
 mod complete_handshake;
 mod encrypt_server;
+mod mock_handshake;
 pub use encrypt_server::{ServerEncryptError, server_encrypt};
 
 use super::*;
@@ -1,8 +1,11 @@
+use std::io::{self, ErrorKind};
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use bytes::BytesMut;
 use oneone::{OneOne, Request};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
 use tokio::sync::mpsc::Sender;
 
 use super::{ServerInfo, Tcp};
@@ -12,8 +15,52 @@ mod convert;
 pub mod encrypt;
 
 // Zero sized struct to denote no stream
+#[derive(Debug)]
 pub struct ZStream;
 
+/* Description:
+ *      Server stream of a mocked connection, for which the server connection
+ *      is not established. Reads return EOF and writes fail with
+ *      NotConnected.
+ *
+ *      Request that is not mocked is sent on a new connection
+ *      [ OneOneStruct::defer_connection() ]
+ */
+
+impl AsyncRead for ZStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        _: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for ZStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        _: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Err(ErrorKind::NotConnected.into()))
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
 /* Description:
  *      Initial Connection struct.
  *
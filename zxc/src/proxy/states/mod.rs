@@ -1,5 +1,6 @@
 use std::fmt::{Debug, Display, Formatter};
 use std::marker::Unpin;
+use std::sync::Arc;
 
 use connection::encrypt::server_encrypt;
 use oneone::Request;
@@ -10,11 +11,13 @@ use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::oneshot;
 use tokio_rustls::StartHandshake;
 pub use tokio_rustls::client::TlsStream as ClientTlsStream;
+use tokio_rustls::rustls::ServerConfig;
 pub use tokio_rustls::server::TlsStream as ServerTlsStream;
 use tracing::trace;
 
 use crate::async_step::AsyncStep;
 use crate::commander::{CommanderResponse, Protocol};
+use crate::config::Mock;
 use crate::io::socket::establish_connection;
 use crate::io::write::write_and_flush;
 use crate::proxy::server_info::ServerInfo;
@@ -32,6 +35,7 @@ use super::handler_state::handlers::{handle_http, read_http};
 
 pub const PROXY_ESTABLISHED: &[u8; 39] =
     b"HTTP/1.1 200 Connection established\r\n\r\n";
+const SHOULD_MOCK: &str = "ShouldMock";
 
 // type alias
 pub type Tcp = TcpStream;
@@ -40,8 +44,17 @@ pub enum ConnectionState<T> {
     ReadInitialClientData(Connection<T, ZStream>),
     DetermineEncryption(Connection<T, ZStream>),
     DetermineServer(Connection<T, ZStream>, bool),
-    EstablishServerConnection(Connection<T, ZStream>, ServerInfo),
-    ShouldProxy(Connection<T, Tcp>, ServerInfo),
+    ShouldProxy(Connection<T, ZStream>, ServerInfo),
+    ShouldMock(
+        Connection<T, ZStream>,
+        Receiver<CommanderResponse>,
+        ServerInfo,
+    ),
+    EstablishServerConnection(
+        Connection<T, ZStream>,
+        Option<Receiver<CommanderResponse>>,
+        ServerInfo,
+    ),
     Relay(Connection<T, Tcp>, ServerInfo),
     ClientHandShake(
         Connection<T, Tcp>,
@@ -70,13 +83,42 @@ pub enum ConnectionState<T> {
         ServerInfo,
         Protocol,
     ),
+    // ----- Mocked, server not connected -----
+    MockHandShake(
+        Connection<T, ZStream>,
+        Receiver<CommanderResponse>,
+        ServerInfo,
+        Arc<ServerConfig>,
+    ),
+    HandleMockTls(
+        Connection<ServerTlsStream<T>, ZStream>,
+        Receiver<CommanderResponse>,
+        ServerInfo,
+    ),
+    HandleMockTcp(
+        Connection<T, ZStream>,
+        Receiver<CommanderResponse>,
+        ServerInfo,
+        Mock,
+    ),
+    // ----- New Connection -----
     EstablishTlsTcp(
-        Connection<ServerTlsStream<T>, ClientTlsStream<Tcp>>,
+        Connection<ServerTlsStream<T>, ZStream>,
         AdditionalHandlerInfo,
     ),
     HandleTlsTcp(Connection<ServerTlsStream<T>, Tcp>, AdditionalHandlerInfo),
-    EstablishTcpTls(Connection<T, Tcp>, AdditionalHandlerInfo),
+    EstablishTcpTls(Connection<T, ZStream>, AdditionalHandlerInfo),
     HandleTcpTls(Connection<T, ClientTlsStream<Tcp>>, AdditionalHandlerInfo),
+    EstablishTcpTcp(Connection<T, ZStream>, AdditionalHandlerInfo),
+    HandleTcpTcp(Connection<T, Tcp>, AdditionalHandlerInfo),
+    EstablishTlsTls(
+        Connection<ServerTlsStream<T>, ZStream>,
+        AdditionalHandlerInfo,
+    ),
+    HandleTlsTls(
+        Connection<ServerTlsStream<T>, ClientTlsStream<Tcp>>,
+        AdditionalHandlerInfo,
+    ),
     End,
 }
 
@@ -142,7 +184,7 @@ where
             }
 
             /* Transition:
-             *      DetermineServer -> EstablishServerConnection
+             *      DetermineServer -> ShouldProxy
              *
              * Steps:
              *      call get_address() with request.infoline and tls to get
@@ -163,30 +205,11 @@ where
                 )?;
                 let server_info = ServerInfo::new(addr, tls, None);
                 trace!("{}", server_info);
-                Ok(Self::EstablishServerConnection(conn, server_info))
-            }
-
-            /* Transition:
-             *      EstablishServerConnection -> ShouldProxy
-             *
-             * Steps:
-             *      1. call establish_connection() with conn.address()
-             *      2. on success, build connection with tcp
-             *      [ From trait implemented in connection/convert.rs ]
-             *
-             * Errors:
-             *      StateError::ServerConnect
-             */
-            Self::EstablishServerConnection(conn, server_info) => {
-                let stream =
-                    establish_connection(server_info.address()).await?;
-                trace!("Y");
-                let conn = Connection::from((conn, stream));
                 Ok(Self::ShouldProxy(conn, server_info))
             }
 
             /* Transition:
-             *      ShouldProxy ->  Relay | ClientHandShake | SwitchTcp
+             *      ShouldProxy ->  ShouldMock | EstablishServerConnection
              *
              * Steps:
              *      1. Build one_shot channel to query commander whether the
@@ -195,10 +218,8 @@ where
              *      server addr as string
              *      3. Send Request to Commander and Receive Response
              *      Option<mpsc::Receiver<CommanderReponse>> from Commander
-             *      4. If Some,
-             *          - if tls    => ClientHandShake
-             *          - else      => SwitchTcp
-             *      5. Else,    => Relay
+             *      4. If Some  => ShouldMock
+             *      5. Else     => EstablishServerConnection, to relay
              *
              * Errors:
              *      StateError::CommanderSend   [3]
@@ -216,24 +237,140 @@ where
                 match rx.await? {
                     Some(recvr) => {
                         trace!("Y");
-                        if server_info.is_tls() {
-                            Ok(Self::ClientHandShake(conn, recvr, server_info))
-                        } else {
-                            Ok(Self::HandleTcp(
-                                conn,
-                                recvr,
-                                server_info,
-                                Protocol::OneOne,
-                            ))
-                        }
+                        Ok(Self::ShouldMock(conn, recvr, server_info))
                     }
                     _ => {
                         trace!("N");
-                        Ok(Self::Relay(conn, server_info))
+                        Ok(Self::EstablishServerConnection(
+                            conn,
+                            None,
+                            server_info,
+                        ))
                     }
                 }
             }
 
+            /* Description:
+             *      Check mock rules before connecting to the server.
+             *
+             * Transition:
+             *      ShouldMock ->   MockHandShake | HandleMockTcp |
+             *                      EstablishServerConnection
+             *
+             * Steps:
+             *      1. If tls, the request is not received yet. Query
+             *         commander with CommanderRequest::MockCert(id, host,
+             *         server name). If host matches any mock rule, commander
+             *         responds with a certificate generated for the server
+             *         name => MockHandShake
+             *
+             *      2. If tcp, query commander with
+             *         CommanderRequest::ShouldMock(id, method, host, path)
+             *         for the received request. If mocked => HandleMockTcp
+             *
+             *      3. Else     => EstablishServerConnection
+             *
+             * Errors:
+             *      StateError::InvalidDns      [1]
+             *      StateError::CommanderSend   [1] [2]
+             *      StateError::CommanderRecv   [1] [2]
+             *      StateError::WrongMessage    [1] [2]
+             */
+            Self::ShouldMock(conn, mut recvr, server_info) => {
+                let host = server_info.address_to_string();
+                // 1. Tls
+                if server_info.is_tls() {
+                    let server_name = server_info
+                        .address()
+                        .parse_sni(None)?
+                        .to_str()
+                        .to_string();
+                    let req =
+                        CommanderRequest::MockCert(conn.id, host, server_name);
+                    conn.commander.send(req).await?;
+                    let res = recvr
+                        .recv()
+                        .await
+                        .ok_or(StateError::CommanderRecv(SHOULD_MOCK))?;
+                    if let Some(config) =
+                        Option::<Arc<ServerConfig>>::try_from(res)?
+                    {
+                        trace!("Y");
+                        return Ok(Self::MockHandShake(
+                            conn,
+                            recvr,
+                            server_info,
+                            config,
+                        ));
+                    }
+                } else {
+                    // 2. Tcp, safe to unwrap frame
+                    let frame = conn.frame.as_ref().unwrap();
+                    let req = CommanderRequest::ShouldMock(
+                        conn.id,
+                        frame.method_as_string().to_string(),
+                        host,
+                        frame.uri_as_string().to_string(),
+                    );
+                    conn.commander.send(req).await?;
+                    let res = recvr
+                        .recv()
+                        .await
+                        .ok_or(StateError::CommanderRecv(SHOULD_MOCK))?;
+                    if let Some(mock) = Option::<Mock>::try_from(res)? {
+                        trace!("Y");
+                        return Ok(Self::HandleMockTcp(
+                            conn,
+                            recvr,
+                            server_info,
+                            mock,
+                        ));
+                    }
+                }
+                // 3. Not mocked
+                trace!("N");
+                Ok(Self::EstablishServerConnection(
+                    conn,
+                    Some(recvr),
+                    server_info,
+                ))
+            }
+
+            /* Transition:
+             *      EstablishServerConnection -> Relay | ClientHandShake |
+             *                                   HandleTcp
+             *
+             * Steps:
+             *      1. call establish_connection() with conn.address()
+             *      2. on success, build connection with tcp
+             *      [ From trait implemented in connection/convert.rs ]
+             *      3. If receiver is
+             *          - None      => Relay
+             *          - tls       => ClientHandShake
+             *          - else      => HandleTcp
+             *
+             * Errors:
+             *      StateError::ServerConnect
+             */
+            Self::EstablishServerConnection(conn, recvr, server_info) => {
+                let stream =
+                    establish_connection(server_info.address()).await?;
+                trace!("Y");
+                let conn = Connection::from((conn, stream));
+                match recvr {
+                    None => Ok(Self::Relay(conn, server_info)),
+                    Some(recvr) if server_info.is_tls() => {
+                        Ok(Self::ClientHandShake(conn, recvr, server_info))
+                    }
+                    Some(recvr) => Ok(Self::HandleTcp(
+                        conn,
+                        recvr,
+                        server_info,
+                        Protocol::OneOne,
+                    )),
+                }
+            }
+
             /* Transition:
              *      Relay   ->  End
              *
@@ -354,8 +491,8 @@ where
              * Steps:
              *      1. build one_one_request handler from conn
              *      [ From trait in oneonestruct/convert/from_connection ]
-             *      2. build ProxyState::ShouldLog, Since request is already
-             *      received
+             *      2. build ProxyState::MapRemote, Since request is already
+             *      received and checked for mock in ShouldMock
              *      3. call handle_http with ProxyState
              */
             Self::HandleTcp(conn, recvr, server_info, _protocol) => {
@@ -364,7 +501,85 @@ where
                     recvr,
                     server_info,
                 ));
-                let client_state = ProxyState::ShouldLog(client);
+                let client_state = ProxyState::MapRemote(client);
+                handle_http(client_state).await
+            }
+
+            /* Transition:
+             *      MockHandShake -> HandleMockTls
+             *
+             * Steps:
+             *      1. write PROXY_ESTABLISHED to client.
+             *      2. call perform_handshake() on conn to receive client_hello
+             *      3. call mock_handshake() to complete the handshake with
+             *         the generated certificate.
+             *
+             * Errors:
+             *      StateError::ClientWrite         [1]
+             *      StateError::ClientHandshake     [2]
+             *      StateError::InvalidDns          [3]
+             *      StateError::ClientEncrypt       [3]
+             */
+            Self::MockHandShake(mut conn, recvr, mut server_info, config) => {
+                write_and_flush(&mut conn.reader, PROXY_ESTABLISHED)
+                    .await
+                    .map_err(StateError::ClientWrite)?;
+                let conn = conn
+                    .perform_handshake()
+                    .await?
+                    .mock_handshake(&mut server_info, config)
+                    .await?;
+                trace!("Y");
+                Ok(Self::HandleMockTls(conn, recvr, server_info))
+            }
+
+            /* Description:
+             *      Handles tls client of a mocked host, without server
+             *      connection.
+             *
+             * Transition:
+             *      HandleMockTls ->  EstablishTlsTls | EstablishTlsTcp | End
+             *
+             * Steps:
+             *      1. Build one_one_request handler from conn
+             *      2. Take the frame, i.e. CONNECT request
+             *      3. Defer the server connection, request that is not
+             *         mocked needs a new connection.
+             *      4. Build ProxyState::Receive and call handle_http
+             */
+            Self::HandleMockTls(conn, recvr, server_info) => {
+                let mut client = OneOneStruct::<_, _, Request>::from((
+                    conn,
+                    recvr,
+                    server_info,
+                ));
+                client.frame.take();
+                client.defer_connection();
+                let client_state = ProxyState::Receive(client);
+                handle_http(client_state).await
+            }
+
+            /* Description:
+             *      Handles mocked tcp request, without server connection.
+             *
+             * Transition:
+             *      HandleMockTcp ->  EstablishTcpTcp | EstablishTcpTls | End
+             *
+             * Steps:
+             *      1. Build one_one_request handler from conn
+             *      2. Set the mock matched in ShouldMock and defer the server
+             *         connection, as there is no server stream.
+             *      3. Build ProxyState::MapRemote and call handle_http
+             */
+            Self::HandleMockTcp(conn, recvr, server_info, mock) => {
+                let mut client = OneOneStruct::<_, _, Request>::from((
+                    conn,
+                    recvr,
+                    server_info,
+                ));
+                client.set_mock(mock);
+                client.defer_connection();
+                let client_state = ProxyState::MapRemote(client);
                 handle_http(client_state).await
             }
 
@@ -444,6 +659,55 @@ where
                 Ok(Self::HandleTcpTls(conn, addinfo))
             }
 
+            /* Description:
+             *      Establish new tcp server connection for mocked tcp
+             *      connection.
+             *
+             * Transition:
+             *      EstablishTcpTcp -> HandleTcpTcp
+             *
+             * Errors:
+             *      StateError::ServerConnect
+             */
+            Self::EstablishTcpTcp(conn, addinfo) => {
+                let tcp = establish_connection(addinfo.address()).await?;
+                let conn = Connection::from((conn, tcp));
+                trace!("Y");
+                Ok(Self::HandleTcpTcp(conn, addinfo))
+            }
+
+            /* Description:
+             *      Establish new tls server connection for mocked tls
+             *      connection.
+             *
+             * Transition:
+             *      EstablishTlsTls -> HandleTlsTls
+             *
+             * Steps:
+             *      1. Establish tcp connection to conn.address()
+             *      2. call server_encrypt() to encrypt the connection
+             *      3. Build connection with tls
+             *
+             * Errors:
+             *      StateError::ServerConnect
+             *      StateError::ServerEncrypt
+             */
+            Self::EstablishTlsTls(mut conn, mut addinfo) => {
+                let tcp = establish_connection(addinfo.address()).await?;
+                let sni = addinfo.sni().to_owned();
+                let tls = server_encrypt(
+                    conn.id,
+                    &mut conn.commander,
+                    &mut addinfo.receiver,
+                    sni,
+                    tcp,
+                )
+                .await?;
+                let conn = Connection::from((conn, tls));
+                trace!("Y");
+                Ok(Self::HandleTlsTls(conn, addinfo))
+            }
+
             /* Description:
              *      Handles tcp to tls
              *
@@ -463,6 +727,32 @@ where
                 let client_state = ProxyState::Send(oneone);
                 handle_http(client_state).await
             }
+
+            /* Description:
+             *      Handles tcp to tcp and tls to tls of a mocked connection,
+             *      for the request that is not mocked.
+             *
+             * Transition:
+             *      HandleTcpTcp | HandleTlsTls ->  End
+             *
+             * Steps:
+             *      1. build one_one_request handler from conn and
+             *      AdditionalHandlerInfo
+             *      2. build ProxyState::Send
+             *      3. call handle_http with ProxyState
+             */
+            Self::HandleTcpTcp(conn, addinfo) => {
+                let oneone =
+                    OneOneStruct::<_, _, Request>::from((conn, addinfo));
+                let client_state = ProxyState::Send(oneone);
+                handle_http(client_state).await
+            }
+            Self::HandleTlsTls(conn, addinfo) => {
+                let oneone =
+                    OneOneStruct::<_, _, Request>::from((conn, addinfo));
+                let client_state = ProxyState::Send(oneone);
+                handle_http(client_state).await
+            }
             Self::End => Ok(Self::End),
         }
     }
@@ -479,16 +769,22 @@ impl<T> Display for ConnectionState<T> {
             Self::ReadInitialClientData(_) => "initial_read",
             Self::DetermineEncryption(_) => "encryption",
             Self::DetermineServer(..) => "server",
-            Self::EstablishServerConnection(..) => "server conn",
             Self::ShouldProxy(..) => "should_proxy",
+            Self::ShouldMock(..) => "should_mock",
+            Self::EstablishServerConnection(..) => "server conn",
             Self::Relay(..) => "relay",
             Self::ClientHandShake(..) => "client_handshake",
             Self::EncryptServer(..) => "server_encrypt",
             Self::CompleteHandshake(..) => "client_encrypt",
+            Self::MockHandShake(..) => "mock_handshake",
             Self::HandleTls(_, _, info, _)
             | Self::HandleTcp(_, _, info, _) => &info.to_string(),
+            Self::HandleMockTls(_, _, info)
+            | Self::HandleMockTcp(_, _, info, _) => &format!("mock| {}", info),
             Self::HandleTlsTcp(_, addinfo)
-            | Self::HandleTcpTls(_, addinfo) => {
+            | Self::HandleTcpTls(_, addinfo)
+            | Self::HandleTcpTcp(_, addinfo)
+            | Self::HandleTlsTls(_, addinfo) => {
                 &addinfo.server_info.to_string()
             }
             Self::EstablishTlsTcp(_, addinfo) => {
@@ -497,6 +793,12 @@ impl<T> Display for ConnectionState<T> {
             Self::EstablishTcpTls(_, addinfo) => {
                 &format!("establish_tcp_tls| {}", addinfo.server_info)
             }
+            Self::EstablishTcpTcp(_, addinfo) => {
+                &format!("establish_tcp_tcp| {}", addinfo.server_info)
+            }
+            Self::EstablishTlsTls(_, addinfo) => {
+                &format!("establish_tls_tls| {}", addinfo.server_info)
+            }
             Self::End => "",
         };
         write!(f, "{}", s)
@@ -11,6 +11,7 @@ impl From<(RepeaterConn<ZStream>, TcpStream)> for RepeaterConn<TcpStream> {
             stream,
             server_info: conn.server_info,
             update: conn.update,
+            timing: conn.timing,
         }
     }
 }
@@ -5,6 +5,7 @@ use crate::proxy::server_info::address::error::AddressError;
 use crate::proxy::states::ZStream;
 use crate::repeater::conn::RepeaterConn;
 use crate::repeater::msg_from_ui::SendInfo;
+use crate::repeater::timing::Timing;
 
 // Convert SendInfo -> RepeaterConn<ZStream>
 impl TryFrom<SendInfo> for RepeaterConn<ZStream> {
@@ -20,6 +21,7 @@ impl TryFrom<SendInfo> for RepeaterConn<ZStream> {
             stream: ZStream,
             path,
             update,
+            timing: Timing::default(),
         })
     }
 }
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Instant;
 
 use tokio::net::TcpStream;
 use tokio_rustls::TlsConnector;
@@ -6,13 +7,14 @@ use tokio_rustls::client::TlsStream;
 
 use super::RepeaterConn;
 use crate::repeater::error::RepeaterError;
+use crate::repeater::timing::micros;
 
 /* Description:
  *      convert RepeaterConn<TcpStream> -> RepeaterConn<TlsStream<TcpStream>>
  *
  * Steps:
  *      1. Get SNI from ServerInfo
- *      2. Encrypt connection with TlsConnector
+ *      2. Encrypt connection with TlsConnector, and record handshake time
  *
  * Error:
  *      RepeaterError::Encrypt
@@ -24,15 +26,19 @@ impl RepeaterConn<TcpStream> {
         connector: Arc<TlsConnector>,
     ) -> Result<RepeaterConn<TlsStream<TcpStream>>, RepeaterError> {
         let sni = self.server_info.sni().to_owned();
+        let start = Instant::now();
         let stream = connector
             .connect(sni, self.stream)
             .await
             .map_err(RepeaterError::Encrypt)?;
+        let mut timing = self.timing;
+        timing.tls = micros(start, Instant::now());
         Ok(RepeaterConn {
             server_info: self.server_info,
             path: self.path,
             stream,
             update: self.update,
+            timing,
         })
     }
 }
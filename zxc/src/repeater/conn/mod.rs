@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use crate::proxy::server_info::ServerInfo;
 use crate::proxy::server_info::address::Address;
+use crate::repeater::timing::Timing;
 mod convert;
 mod encrypt;
 
@@ -10,6 +11,7 @@ pub struct RepeaterConn<T> {
     pub path: PathBuf,
     pub stream: T,
     pub update: bool,
+    pub timing: Timing,
     server_info: ServerInfo,
 }
 
@@ -4,13 +4,20 @@ use std::path::PathBuf;
 use thiserror::Error;
 use tokio::sync::mpsc::error::SendError;
 
+use super::race::RaceError;
+use super::raw::RawError;
+use super::redirect::RedirectError;
+use super::versions::VersionError;
 use super::ws::builder::RWsBuildError;
 use super::ws::message::RepeaterWsMsg;
 use crate::commander::codec::error::CodecError;
+use crate::commander::codegen::error::CodeGenError;
+use crate::commander::compare::error::CompareError;
 use crate::forward_info::ForwardInfo;
 use crate::io::inc_dir::DirError;
 use crate::io::socket::ConnectError;
 use crate::io::unix_sock::error::UnixSockError;
+use crate::jar::JarError;
 use crate::proxy::handler_state::error::ProxyStateError;
 use crate::proxy::handler_state::handlers::scode::StatusCodeError;
 use crate::proxy::server_info::address::error::AddressError;
@@ -39,6 +46,10 @@ pub enum RepeaterError {
     // ----- Codec -----
     #[error("{0}")]
     Codec(#[from] CodecError),
+    #[error("copy as| {0}")]
+    CodeGen(#[from] CodeGenError),
+    #[error("compare| {0}")]
+    Compare(#[from] CompareError),
 
     // ----- Initial State Machine -----
     // Invalid Dns
@@ -82,6 +93,26 @@ pub enum RepeaterError {
     HistoryFile(io::Error),
     #[error("ws id not found| {0}")]
     WsIdNotFound(usize),
+
+    // ----- Race -----
+    #[error("race| {0}")]
+    Race(#[from] RaceError),
+
+    // ----- Raw -----
+    #[error("raw| {0}")]
+    Raw(#[from] RawError),
+
+    // ----- Redirect -----
+    #[error("redirect| {0}")]
+    Redirect(#[from] RedirectError),
+
+    // ----- Versions -----
+    #[error("version| {0}")]
+    Version(#[from] VersionError),
+
+    // ----- Jar -----
+    #[error("jar| {0}")]
+    Jar(#[from] JarError),
 }
 
 impl IsUIError for RepeaterError {
@@ -8,6 +8,7 @@ use crate::io::inc_dir::{DirError, incremental};
 
 pub const REPEATER_HTTP_FILENAME_REQ: &str = "rep.req";
 pub const REPEATER_HTTP_FILENAME_RES: &str = "rep.res";
+pub const REPEATER_HTTP_FILENAME_TIMING: &str = "rep.timing.json";
 pub const REPEATER_WS_SCRATCH_FILENAME: &str = "scratch.wreq";
 
 pub const REPEATER_HTTP_BUILD_INFO: (usize, &str, &str) =
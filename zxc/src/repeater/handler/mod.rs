@@ -1,16 +1,19 @@
 use std::fmt::Debug;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 
 use bytes::BytesMut;
 use serde_json::{Value, json};
-use tokio::net::UnixStream;
+use tokio::net::{TcpStream, UnixStream};
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio_rustls::TlsConnector;
-use tracing::trace;
+use tracing::{error, trace};
 use zxc_derive::{Buffer, CloseAction, FlushStorage, NotifyCommander};
 
 use crate::commander::codec::perform_codec_op;
+use crate::commander::codegen::perform_copy_as;
+use crate::commander::compare::perform_compare;
 use crate::io::unix_sock::error::UnixSockError;
 mod impl_from_commander;
 mod impl_handle_commander_msg;
@@ -18,14 +21,27 @@ mod impl_handle_ui;
 
 use super::conn::RepeaterConn;
 use super::error::RepeaterError;
+use super::file_builder::REPEATER_HTTP_FILENAME_RES;
 use super::http::handle_http;
 use super::msg_from_ui::{Operation, RepeaterMsg, SendInfo};
+use super::race::{RaceMode, race_last_byte, race_pipeline, race_results};
+use super::raw::{send_raw, split_responses, write_raw};
+use super::redirect::{
+    Hop, NextHop, RedirectError, RedirectInfo, hop_path, next_hop, send_hop
+};
 use super::states::establish_state::RepeaterConnState;
+use super::timing::{Timing, micros, write_timing};
+use super::versions::{list_versions, restore_version, save_version};
 use super::ws::repeater_ws_handle::RepeaterWsHandle;
 use super::ws::spawn_repeater_ws;
 use crate::CAPACITY_2MB;
 use crate::async_step::AsyncStep;
+use crate::file_types::{EXT_REQ, EXT_RES};
 use crate::forward_info::ForwardInfo;
+use crate::jar::{
+    JarRequest, SharedJar, perform_jar_op, update_from_response
+};
+use crate::proxy::server_info::json::ServerInfoJson;
 use crate::proxy::states::ZStream;
 use crate::repeater::ws::message::RepeaterWsMsg;
 use crate::run::boundary::{
@@ -41,6 +57,7 @@ pub struct RepeaterHandler {
     ws_index: usize,
     from_commander: Receiver<ForwardInfo>,
     to_commander: Sender<ForwardInfo>,
+    jar: SharedJar,
 }
 
 impl RepeaterHandler {
@@ -49,6 +66,7 @@ impl RepeaterHandler {
         tls_connector: Arc<TlsConnector>,
         from_commander: Receiver<ForwardInfo>,
         to_commander: Sender<ForwardInfo>,
+        jar: SharedJar,
     ) -> RepeaterHandler {
         RepeaterHandler {
             buf: BytesMut::with_capacity(CAPACITY_2MB),
@@ -57,6 +75,7 @@ impl RepeaterHandler {
             ws_index: 1,
             from_commander,
             to_commander,
+            jar,
         }
     }
 
@@ -96,6 +115,150 @@ impl RepeaterHandler {
         }
     }
 
+    /* Description:
+     *      Follow redirects of the response to Send. A failed hop does not
+     *      fail the Send, it is added to the chain with the error and the
+     *      chain is not followed further.
+     *
+     * Steps:
+     *      1. First hop is the response to Send, logged in rep.res.
+     *
+     *      2. Upto max_hops, while next_hop() returns a request to the
+     *         location,
+     *          a. If use_jar, merge jar cookies for the location into the
+     *             request.
+     *          b. Send the request [ send_next_hop() ], on error add a
+     *             failed hop and stop.
+     *          c. Update jar from the response
+     */
+
+    async fn follow_redirects(
+        &self,
+        file: &Path,
+        mut server_info: ServerInfoJson,
+        response: Option<BytesMut>,
+        time: u128,
+        redirect: &RedirectInfo,
+        use_jar: bool,
+    ) -> Vec<Hop> {
+        let Some(mut res) = response else {
+            return Vec::new();
+        };
+        // 1. First hop
+        let mut hops = vec![Hop::new(
+            &server_info.host,
+            file.with_file_name(REPEATER_HTTP_FILENAME_RES),
+            &res,
+            time,
+        )];
+        let mut req = match tokio::fs::read(file).await {
+            Ok(req) => req,
+            Err(e) => {
+                error!("hop| read| {}", e);
+                hops[0].error = Some(RedirectError::File(e).to_string());
+                return hops;
+            }
+        };
+        // 2. Hops
+        for hop in 1..=redirect.max_hops() {
+            let Some(mut next) =
+                next_hop(&req, &res, &server_info, redirect.keep_credentials)
+            else {
+                break;
+            };
+            // a. Jar
+            if use_jar {
+                let jar = JarRequest {
+                    jar: self.jar.clone(),
+                    host: next.server_info.host.clone(),
+                    tls: next.server_info.is_tls(),
+                };
+                match jar.merge_raw(&next.request) {
+                    Ok(data) => next.request = data.to_vec(),
+                    Err(e) => error!("hop jar| {}| {}", hop, e),
+                }
+            }
+            // b. Send
+            let (res_path, data, time) = match self
+                .send_next_hop(file, hop, &next)
+                .await
+            {
+                Ok(result) => result,
+                Err(e) => {
+                    error!("hop| {}| {}", hop, e);
+                    hops.push(Hop::failed(
+                        &next.server_info.host,
+                        e.to_string(),
+                    ));
+                    break;
+                }
+            };
+            // c. Jar
+            update_from_response(
+                &self.jar,
+                &next.server_info.host,
+                &next.request,
+                &data,
+            )
+            .await;
+            trace!("hop| {}| {}", hop, next.server_info.host);
+            hops.push(Hop::new(&next.server_info.host, res_path, &data, time));
+            req = next.request;
+            res = data;
+            server_info = next.server_info;
+        }
+        hops
+    }
+
+    /* Steps:
+     *      1. Write request to rep.hop-N.req
+     *      2. Establish connection to the location and send_hop()
+     *      3. Write response to rep.hop-N.res
+     *
+     * Returns:
+     *      (response path, response, time in ms)
+     *
+     * Error:
+     *      RepeaterError::Redirect     [1] [2] [3]
+     *      RepeaterError               [2]
+     */
+
+    async fn send_next_hop(
+        &self,
+        file: &Path,
+        hop: usize,
+        next: &NextHop,
+    ) -> Result<(PathBuf, BytesMut, u128), RepeaterError> {
+        // 1. Request
+        let req_path = hop_path(file, hop, EXT_REQ);
+        tokio::fs::write(&req_path, &next.request)
+            .await
+            .map_err(RedirectError::File)?;
+        // 2. Send
+        let info = SendInfo {
+            file: req_path,
+            update: Some(false),
+            server_info: next.server_info.clone(),
+            redirect: None,
+            use_jar: None,
+        };
+        let (data, time) = match self.establish_conn(info).await? {
+            RepeaterConnState::HandleTcp(mut conn) => {
+                send_hop(&mut conn.stream, &next.request).await?
+            }
+            RepeaterConnState::HandleTls(mut conn) => {
+                send_hop(&mut conn.stream, &next.request).await?
+            }
+            _ => unreachable!(),
+        };
+        // 3. Response
+        let res_path = hop_path(file, hop, EXT_RES);
+        tokio::fs::write(&res_path, &data)
+            .await
+            .map_err(RedirectError::File)?;
+        Ok((res_path, data, time))
+    }
+
     pub async fn get_response(
         &mut self,
         msg: RepeaterMsg,
@@ -144,6 +307,22 @@ impl RepeaterHandler {
                 Some(result)
             }
 
+            /* Associated Values:
+             *      info    : CopyAsInfo
+             *
+             * Error:
+             *      RepeaterError::CodeGen
+             */
+            Operation::CopyAs(info) => Some(perform_copy_as(&info).await?),
+
+            /* Associated Values:
+             *      info    : CompareInfo
+             *
+             * Error:
+             *      RepeaterError::Compare
+             */
+            Operation::Compare(info) => Some(perform_compare(&info).await?),
+
             /* Associated Values:
              *      info    : SendInfo
              *
@@ -151,29 +330,49 @@ impl RepeaterHandler {
              *      1. start timer
              *      2. start initial RepeaterState machine
              *      3. If RepeaterState is HandleTcp/HandleTls call
-             *         handle_http(), and get the response.
+             *         handle_http(), with JarRequest if use_jar is set, and
+             *         get the response.
              *      4. calculate the response length
-             *      5. end timer and calculate the response time
-             *      6. build output json in format {"size": size, "time": time}
+             *      5. end timer and calculate the response time, set total
+             *         of timing breakdown and write it to rep.timing.json
+             *      6. save sent request, response and timing as next version
+             *         of the tab by calling save_version()
+             *      7. update jar from the response
+             *      8. build output json in format
+             *              {"size": size, "time": time, "version": version,
+             *               "timing": Timing}
+             *      9. If redirect is set, call follow_redirects() and add the
+             *         chain as "redirects" to output. A failed hop has an
+             *         "error" and ends the chain, output is still returned.
              *
              * Error:
              *      RepeaterError
              */
-            Operation::Send(info) => {
+            Operation::Send(mut info) => {
                 let start_time = Instant::now();
+                let file = info.file.clone();
+                let redirect = info.redirect.take();
+                let server_info = info.server_info.clone();
+                let use_jar = info.use_jar.unwrap_or(false);
+                let jar = use_jar.then(|| JarRequest {
+                    jar: self.jar.clone(),
+                    host: server_info.host.clone(),
+                    tls: server_info.is_tls(),
+                });
 
-                let response_data = match self.establish_conn(info).await? {
-                    RepeaterConnState::HandleTcp(conn) => {
-                        let mut hconn = handle_http(conn).await?;
-                        hconn.get_payload()
-                    }
-                    RepeaterConnState::HandleTls(conn) => {
-                        let mut hconn = handle_http(conn).await?;
-                        hconn.get_payload()
-                    }
+                let (response_data, mut timing): (_, Timing) =
+                    match self.establish_conn(info).await? {
+                        RepeaterConnState::HandleTcp(conn) => {
+                            let mut hconn = handle_http(conn, jar).await?;
+                            (hconn.get_payload(), hconn.timing())
+                        }
+                        RepeaterConnState::HandleTls(conn) => {
+                            let mut hconn = handle_http(conn, jar).await?;
+                            (hconn.get_payload(), hconn.timing())
+                        }
 
-                    _ => unreachable!(),
-                };
+                        _ => unreachable!(),
+                    };
 
                 let size = response_data
                     .as_ref()
@@ -189,7 +388,214 @@ impl RepeaterHandler {
                 trace!("size| {}", size);
                 trace!("time| {}", time);
 
-                Some(json!({"size" : size, "time": time}))
+                timing.total = micros(start_time, end_time);
+                if let Err(e) = write_timing(&file, &timing).await {
+                    error!("write timing| {}", e);
+                }
+
+                let version = save_version(
+                    &file,
+                    response_data.as_deref(),
+                    time,
+                    timing,
+                )
+                .await?;
+
+                if let Some(res) = response_data.as_ref()
+                    && let Ok(req) = tokio::fs::read(&file).await
+                {
+                    update_from_response(
+                        &self.jar,
+                        &server_info.host,
+                        &req,
+                        res,
+                    )
+                    .await;
+                }
+
+                let mut output = json!({"size" : size, "time": time, "version": version.version, "timing": timing});
+                if let Some(redirect) = redirect {
+                    let hops = self
+                        .follow_redirects(
+                            &file,
+                            server_info,
+                            response_data,
+                            time,
+                            &redirect,
+                            use_jar,
+                        )
+                        .await;
+                    output["redirects"] = json!(hops);
+                }
+                Some(output)
+            }
+
+            /* Associated Values:
+             *      info    : RaceInfo
+             *
+             * Steps:
+             *      1. Read and update the requests by calling payloads().
+             *
+             *      2. Establish a connection per request for LastByte, or a
+             *         single connection for Pipeline. Disable Nagle, so that
+             *         the last byte is not held back till the head is acked.
+             *
+             *      3. Send the requests by calling race_last_byte() or
+             *         race_pipeline().
+             *
+             *      4. Write responses and build results by calling
+             *         race_results()
+             *
+             * Error:
+             *      RepeaterError::Race
+             *      RepeaterError       [2]
+             */
+            Operation::Race(info) => {
+                // 1. Payloads
+                let payloads = info.payloads().await?;
+                // 2. Connections
+                let conns = match info.mode {
+                    RaceMode::LastByte => payloads.len(),
+                    RaceMode::Pipeline => 1,
+                };
+                let mut tcp = Vec::with_capacity(conns);
+                let mut tls = Vec::with_capacity(conns);
+                for _ in 0..conns {
+                    match self
+                        .establish_conn(info.send_info())
+                        .await?
+                    {
+                        RepeaterConnState::HandleTcp(conn) => {
+                            set_nodelay(&conn.stream);
+                            tcp.push(conn.stream)
+                        }
+                        RepeaterConnState::HandleTls(conn) => {
+                            set_nodelay(conn.stream.get_ref().0);
+                            tls.push(conn.stream)
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                // 3. Send
+                let reads = match (info.mode, tcp.pop(), tls.pop()) {
+                    (RaceMode::Pipeline, Some(stream), _) => {
+                        race_pipeline(stream, &payloads).await
+                    }
+                    (RaceMode::Pipeline, _, Some(stream)) => {
+                        race_pipeline(stream, &payloads).await
+                    }
+                    (RaceMode::LastByte, Some(stream), _) => {
+                        tcp.push(stream);
+                        race_last_byte(tcp, &payloads).await
+                    }
+                    (RaceMode::LastByte, _, Some(stream)) => {
+                        tls.push(stream);
+                        race_last_byte(tls, &payloads).await
+                    }
+                    _ => unreachable!(),
+                };
+                // 4. Results
+                let dir = info.files[0]
+                    .parent()
+                    .unwrap_or(Path::new("."));
+                let results = race_results(dir, &payloads, reads).await?;
+                Some(json!({"result": results}))
+            }
+
+            /* Associated Values:
+             *      info    : RawInfo
+             *
+             * Steps:
+             *      1. Read the request file verbatim.
+             *
+             *      2. Establish connection and send the bytes as is by
+             *         calling send_raw().
+             *
+             *      3. Write the raw byte stream to rep.raw.res and split it
+             *         into responses by calling split_responses().
+             *
+             *      4. Build output json in format
+             *              {"size": size, "time": time, "closed": bool,
+             *               "file": rep.raw.res, "responses": [RawResponse]}
+             *
+             * Error:
+             *      RepeaterError::Raw
+             *      RepeaterError       [2]
+             */
+            Operation::RawSend(info) => {
+                // 1. Payload
+                let payload = info.payload().await?;
+                // 2. Send
+                let read = match self
+                    .establish_conn(info.send_info())
+                    .await?
+                {
+                    RepeaterConnState::HandleTcp(conn) => {
+                        send_raw(conn.stream, &payload, info.idle()).await?
+                    }
+                    RepeaterConnState::HandleTls(conn) => {
+                        send_raw(conn.stream, &payload, info.idle()).await?
+                    }
+                    _ => unreachable!(),
+                };
+                // 3. Split
+                let file = write_raw(&info.file, &read.data).await?;
+                let responses = split_responses(&read.data);
+                trace!("raw| {}| {}", read.data.len(), responses.len());
+                // 4. Output
+                Some(json!({
+                    "size": read.data.len(),
+                    "time": read.time,
+                    "closed": read.closed,
+                    "file": file,
+                    "responses": responses
+                }))
+            }
+
+            /* Associated Values:
+             *      file    : PathBuf
+             *
+             * Error:
+             *      RepeaterError::Version
+             */
+            Operation::Versions(file) => {
+                Some(json!({"result": list_versions(&file).await?}))
+            }
+
+            /* Associated Values:
+             *      info    : VersionDiffInfo
+             *
+             * Error:
+             *      RepeaterError::Version
+             *      RepeaterError::Compare
+             */
+            Operation::VersionDiff(info) => {
+                Some(perform_compare(&info.to_compare_info()?).await?)
+            }
+
+            /* Associated Values:
+             *      info    : RestoreInfo
+             *
+             * Error:
+             *      RepeaterError::Version
+             */
+            Operation::Restore(info) => {
+                restore_version(&info).await?;
+                Some(json!({"restored": info.version}))
+            }
+
+            /* Associated Values:
+             *      op      : JarOp
+             *
+             * Steps:
+             *      Perform op on the session jar, and return the cookies in
+             *      format {"result": [cookie]}
+             *
+             * Error:
+             *      RepeaterError::Jar
+             */
+            Operation::Jar(op) => {
+                Some(json!({"result": perform_jar_op(&self.jar, op).await?}))
             }
 
             /* Associated Values:
@@ -271,6 +677,13 @@ impl RepeaterHandler {
     }
 }
 
+// Disable Nagle on the race connection, logged on error
+fn set_nodelay(stream: &TcpStream) {
+    if let Err(e) = stream.set_nodelay(true) {
+        error!("race nodelay| {}", e);
+    }
+}
+
 impl Debug for RepeaterHandler {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "repeater")
@@ -6,12 +6,15 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use super::states::rstate::RepeaterState;
 use crate::async_step::async_run;
 use crate::io::file::{FileErrorInfo, FileEvent};
+use crate::jar::JarRequest;
 use crate::proxy::handler_state::error::ProxyStateError;
 use crate::repeater::conn::RepeaterConn;
 use crate::repeater::http::Roneone;
 
+// If jar is set, cookies are merged into the updated request
 pub async fn handle_http<T>(
     conn: RepeaterConn<T>,
+    jar: Option<JarRequest>,
 ) -> Result<Roneone<T>, ProxyStateError>
 where
     T: AsyncWriteExt + AsyncReadExt + Unpin,
@@ -22,7 +25,8 @@ where
         .open(&conn.path)
         .await
         .map_err(|e| FileErrorInfo::from((&conn.path, FileEvent::Open, e)))?;
-    let one: Roneone<T> = Roneone::from((conn, file));
+    let mut one: Roneone<T> = Roneone::from((conn, file));
+    one.set_jar(jar);
     let state = RepeaterState::ReadFromFile(one);
     match async_run(state).await? {
         RepeaterState::End(hconn) => Ok(hconn),
@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use bytes::BytesMut;
 use tokio::fs::File;
 
@@ -16,6 +18,9 @@ impl<T> From<(RepeaterConn<T>, File)> for Roneone<T> {
             file,
             payload: None,
             update: conn.update,
+            jar: None,
+            timing: conn.timing,
+            sent: Instant::now(),
         }
     }
 }
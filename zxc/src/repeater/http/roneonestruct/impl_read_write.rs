@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use oneone::Response;
 use protocol_traits::Frame;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -6,9 +8,10 @@ use tracing::trace;
 use super::Roneone;
 use crate::io::write::write_and_flush;
 use crate::proxy::handler_state::handlers::oneonestruct::OneOneRWError;
-use crate::proxy::handler_state::handlers::read_http;
+use crate::proxy::handler_state::handlers::read_http_meta;
 use crate::proxy::handler_state::read_write::ReadWrite;
 use crate::repeater::states::rstate::RepeaterState;
+use crate::repeater::timing::micros;
 
 impl<T> ReadWrite for Roneone<T>
 where
@@ -20,26 +23,39 @@ where
     /* Transition:
      *      Receive -> WriteResponse
      *
+     * Steps:
+     *      Read response with ReadMeta, and record time to first byte from
+     *      request sent and download time.
+     *
      * Error:
      *      OneOneRWError::Read
      */
 
     async fn read(mut self) -> Result<RepeaterState<Self>, OneOneRWError> {
-        let frame =
-            read_http::<T, Response>(&mut self.stream, &mut self.buf).await?;
+        let (frame, meta) =
+            read_http_meta::<T, Response>(&mut self.stream, &mut self.buf)
+                .await?;
+        self.timing.first_byte = micros(self.sent, meta.first_byte);
+        self.timing.download = micros(meta.first_byte, meta.end);
         self.payload = Some(frame.into_data());
         trace!("Y");
         Ok(RepeaterState::WriteResponse(self))
     }
 
     /* Transition:
-     *      WriteResponse -> Receive
+     *      Send -> Receive
+     *
+     * Steps:
+     *      Write request and record write time.
      */
 
     async fn write(mut self) -> Result<RepeaterState<Self>, OneOneRWError> {
+        let start = Instant::now();
         write_and_flush(&mut self.stream, self.payload.as_ref().unwrap())
             .await
             .map_err(OneOneRWError::Write)?;
+        self.sent = Instant::now();
+        self.timing.write = micros(start, self.sent);
         trace!("Y");
         Ok(RepeaterState::Receive(self))
     }
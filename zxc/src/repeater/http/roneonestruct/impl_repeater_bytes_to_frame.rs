@@ -13,7 +13,11 @@ impl<T> RepeaterBytesToFrame for Roneone<T> {
         &mut self,
         buf: BytesMut,
     ) -> Result<Self::Frame, ProxyUpdateFrameError> {
-        Ok(OneOne::<Request>::update(buf)?)
+        let mut frame = OneOne::<Request>::update(buf)?;
+        if let Some(jar) = self.jar.as_ref() {
+            jar.merge(&mut frame);
+        }
+        Ok(frame)
     }
 
     fn frame_to_payload(&mut self, frame: Self::Frame) {
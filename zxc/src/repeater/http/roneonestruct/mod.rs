@@ -1,10 +1,13 @@
 use std::path::PathBuf;
+use std::time::Instant;
 
 use bytes::BytesMut;
 use tokio::fs::File;
 use zxc_derive::RepeaterReadFile;
 
+use crate::jar::JarRequest;
 use crate::repeater::states::transition::read_from_file::RepeaterReadFile;
+use crate::repeater::timing::Timing;
 
 mod conversion;
 mod impl_add_raw;
@@ -23,10 +26,21 @@ pub struct Roneone<T> {
     file: File,
     payload: Option<BytesMut>,
     update: bool,
+    jar: Option<JarRequest>,
+    timing: Timing,
+    sent: Instant,
 }
 
 impl<T> Roneone<T> {
     pub fn get_payload(&mut self) -> Option<BytesMut> {
         self.payload.take()
     }
+
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+
+    pub fn set_jar(&mut self, jar: Option<JarRequest>) {
+        self.jar = jar;
+    }
 }
@@ -4,7 +4,12 @@ mod file_builder;
 mod handler;
 mod http;
 mod msg_from_ui;
+mod race;
+mod raw;
+mod redirect;
 mod states;
+mod timing;
+mod versions;
 mod ws;
 
 pub use handler::RepeaterHandler;
@@ -2,8 +2,15 @@ use std::path::PathBuf;
 
 use serde::Deserialize;
 
+use super::race::RaceInfo;
+use super::raw::RawInfo;
+use super::redirect::RedirectInfo;
+use super::versions::{RestoreInfo, VersionDiffInfo};
 use crate::commander::codec::Codec;
+use crate::commander::codegen::CopyAsInfo;
+use crate::commander::compare::CompareInfo;
 use crate::forward_info::{ForwardInfo, Module};
+use crate::jar::JarOp;
 use crate::proxy::server_info::json::ServerInfoJson;
 
 /* Message received from Repeater UI
@@ -12,9 +19,27 @@ use crate::proxy::server_info::json::ServerInfoJson;
  *     [0,{"Encode" : {"Codec": Base64, "data": "aGVsbG8gd29ybGQ="}}]
  *     [0,{"Decode" : {"Codec": Base64, "data": "aGVsbG8gd29ybGQ="}}]
  *     [0,{"Send" : {"file": "1.req", "update": true, "server_info": {
- *      "scheme": "https", "host": "www.google.com", "update": true, "sni": "www.google.com"}]
+ *      "scheme": "https", "host": "www.google.com", "update": true, "sni": "www.google.com"},
+ *      "redirect": {"max_hops": 5, "keep_credentials": false}, "use_jar": true}]
  *     [0,{"WsEstablish": "1.req"}}]
  *     [0,{"WsSend": "1"}]
+ *     [0,{"CopyAs" : {"file": "1.req", "lang": "Curl", "server_info": {
+ *      "host": "www.google.com"}}}]
+ *     [0,{"Compare" : {"left": "1.res", "right": "2.res", "mode": "Line"}}]
+ *     [0,{"Versions" : "./history/1/r-1/rep.req"}]
+ *     [0,{"VersionDiff" : {"file": "./history/1/r-1/rep.req", "left": 1,
+ *      "right": 3}}]
+ *     [0,{"Restore" : {"file": "./history/1/r-1/rep.req", "version": 2}}]
+ *     [0,{"Race" : {"files": ["./history/1/r-1/rep.req"], "count": 20,
+ *      "mode": "LastByte", "server_info": {"host": "www.google.com"}}}]
+ *     [0,{"RawSend" : {"file": "./history/1/r-1/rep.req", "idle_ms": 2000,
+ *      "server_info": {"host": "www.google.com"}}}]
+ *     [0,{"Jar" : "View"}]
+ *     [0,{"Jar" : {"Set": {"name": "sid", "value": "1", "domain":
+ *      "www.google.com"}}}]
+ *     [0,{"Jar" : {"Remove": {"domain": "www.google.com", "name": "sid",
+ *      "path": "/"}}}]
+ *     [0,{"Jar" : "Clear"}]
  */
 
 #[derive(Debug, Deserialize)]
@@ -42,8 +67,16 @@ pub enum Operation {
         codec: Codec,
         data: String,
     },
+    CopyAs(CopyAsInfo),
+    Compare(CompareInfo),
     // http
     Send(SendInfo),
+    Versions(PathBuf),
+    VersionDiff(VersionDiffInfo),
+    Restore(RestoreInfo),
+    Race(RaceInfo),
+    RawSend(RawInfo),
+    Jar(JarOp),
     // ws
     WsEstablish(SendInfo),
     WsSend(usize),
@@ -52,12 +85,21 @@ pub enum Operation {
     Close,
 }
 
-// http request send info
+/* Description:
+ *      http request send info, redirects are followed if redirect is set.
+ *      If use_jar is set, cookies in the session jar are merged into the
+ *      request and into each redirect hop request. Since merging needs the
+ *      parsed request, it is skipped for the request if update is false.
+ */
 #[derive(Debug, Deserialize)]
 pub struct SendInfo {
     pub file: PathBuf,
     pub update: Option<bool>,
     pub server_info: ServerInfoJson,
+    #[serde(default)]
+    pub redirect: Option<RedirectInfo>,
+    #[serde(default)]
+    pub use_jar: Option<bool>,
 }
 
 //#[cfg(test)]
@@ -0,0 +1,471 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use buffer::{Cursor, Event};
+use bytes::BytesMut;
+use oneone::{OneOne, OneOneState, Request, Response, UpdateHttp};
+use protocol_traits::{Frame, Step};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Barrier;
+use tracing::trace;
+
+use super::msg_from_ui::SendInfo;
+use super::versions::status_code;
+use crate::io::socket::fill_buffer;
+use crate::io::write::write_and_flush;
+use crate::proxy::handler_state::handlers::oneonestruct::OneOneRWError;
+use crate::proxy::handler_state::handlers::read_http;
+use crate::proxy::handler_state::transition::update_frame::error::ProxyUpdateFrameError;
+use crate::proxy::server_info::json::ServerInfoJson;
+
+const MAX_REQUESTS: usize = 64;
+const RACE_BUF: usize = 64 * 1024;
+
+#[derive(Debug, Error)]
+pub enum RaceError {
+    #[error("no requests")]
+    NoRequests,
+    #[error("too many requests| {0} > {MAX_REQUESTS}")]
+    TooMany(usize),
+    #[error("read| {0}| {1}")]
+    Read(PathBuf, io::Error),
+    #[error("empty| {0}")]
+    Empty(PathBuf),
+    #[error("update| {0}")]
+    Update(#[from] ProxyUpdateFrameError),
+    #[error("write response| {0}")]
+    WriteResponse(io::Error),
+}
+
+/* Description:
+ *      How the requests are synchronized.
+ *
+ *      LastByte    : Connection per request. All but the last byte of each
+ *                    request is sent, then the last bytes are sent at once.
+ *      Pipeline    : All requests are sent in a single write on one
+ *                    connection, responses are read in order.
+ */
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum RaceMode {
+    #[default]
+    LastByte,
+    Pipeline,
+}
+
+/* Description:
+ *      Race info from repeater ui. Each file is sent count times.
+ *
+ * Format:
+ *      [0, {"Race": {"files": ["./history/1/r-1/rep.req"], "count": 20,
+ *          "mode": "LastByte", "server_info": {"host": "a.com"}}}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub struct RaceInfo {
+    pub files: Vec<PathBuf>,
+    #[serde(default)]
+    pub count: Option<usize>,
+    #[serde(default)]
+    pub mode: RaceMode,
+    pub update: Option<bool>,
+    pub server_info: ServerInfoJson,
+}
+
+impl RaceInfo {
+    // SendInfo to establish a connection
+    pub fn send_info(&self) -> SendInfo {
+        SendInfo {
+            file: self.files[0].clone(),
+            update: self.update,
+            server_info: self.server_info.clone(),
+            redirect: None,
+            use_jar: None,
+        }
+    }
+
+    /* Steps:
+     *      1. Repeat each file count times, check 0 < total <= MAX_REQUESTS.
+     *
+     *      2. Read each file, if update (default true), update the frame
+     *         similar to Send [ OneOne::<Request>::update() ].
+     *
+     * Error:
+     *      RaceError::NoRequests   [1]
+     *      RaceError::TooMany      [1]
+     *      RaceError::Read         [2]
+     *      RaceError::Empty        [2]
+     *      RaceError::Update       [2]
+     */
+
+    pub async fn payloads(&self) -> Result<Vec<RacePayload>, RaceError> {
+        // 1. Files
+        let count = self.count.unwrap_or(1);
+        let total = self.files.len() * count;
+        if total == 0 {
+            return Err(RaceError::NoRequests);
+        } else if total > MAX_REQUESTS {
+            return Err(RaceError::TooMany(total));
+        }
+        // 2. Read
+        let mut payloads = Vec::with_capacity(total);
+        for file in self.files.iter() {
+            let data = tokio::fs::read(file)
+                .await
+                .map_err(|e| RaceError::Read(file.clone(), e))?;
+            if data.is_empty() {
+                return Err(RaceError::Empty(file.clone()));
+            }
+            let mut data = BytesMut::from(&data[..]);
+            if self.update.unwrap_or(true) {
+                data = OneOne::<Request>::update(data)
+                    .map_err(ProxyUpdateFrameError::from)?
+                    .into_data();
+            }
+            for _ in 0..count {
+                payloads.push(RacePayload {
+                    file: file.clone(),
+                    data: data.clone(),
+                });
+            }
+        }
+        Ok(payloads)
+    }
+}
+
+pub struct RacePayload {
+    file: PathBuf,
+    data: BytesMut,
+}
+
+// Response read in race along with the time the request was completed
+pub struct RaceRead {
+    sent: Instant,
+    end: Instant,
+    data: BytesMut,
+}
+
+/* Steps:
+ *      1. For each connection, spawn a task which
+ *          a. Writes all but the last byte of the request.
+ *          b. Waits for all tasks on the barrier, even on error.
+ *          c. Writes the last byte and reads the response.
+ *
+ *      2. Collect results in order of the payloads.
+ */
+
+pub async fn race_last_byte<T>(
+    streams: Vec<T>,
+    payloads: &[RacePayload],
+) -> Vec<Result<RaceRead, String>>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static,
+{
+    let barrier = Arc::new(Barrier::new(streams.len()));
+    // 1. Spawn
+    let handles: Vec<_> = streams
+        .into_iter()
+        .zip(
+            payloads
+                .iter()
+                .map(|payload| payload.data.clone()),
+        )
+        .map(|(mut stream, data)| {
+            let barrier = barrier.clone();
+            tokio::spawn(async move {
+                // payload is not empty
+                let (head, last) = data.split_at(data.len() - 1);
+                // a. Head
+                let written = write_and_flush(&mut stream, head).await;
+                // b. Sync
+                barrier.wait().await;
+                written.map_err(OneOneRWError::Write)?;
+                // c. Last byte
+                write_and_flush(&mut stream, last)
+                    .await
+                    .map_err(OneOneRWError::Write)?;
+                let sent = Instant::now();
+                let mut buf = BytesMut::with_capacity(RACE_BUF);
+                let frame =
+                    read_http::<T, Response>(&mut stream, &mut buf).await?;
+                Ok::<_, OneOneRWError>(RaceRead {
+                    sent,
+                    end: Instant::now(),
+                    data: frame.into_data(),
+                })
+            })
+        })
+        .collect();
+    // 2. Collect
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        results.push(match handle.await {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        });
+    }
+    results
+}
+
+/* Steps:
+ *      1. Write all requests in a single write.
+ *
+ *      2. Read responses in order, after an error the remaining responses
+ *         fail with the same error.
+ */
+
+pub async fn race_pipeline<T>(
+    mut stream: T,
+    payloads: &[RacePayload],
+) -> Vec<Result<RaceRead, String>>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    // 1. Write
+    let data: Vec<u8> = payloads
+        .iter()
+        .flat_map(|payload| payload.data.iter().copied())
+        .collect();
+    if let Err(e) = write_and_flush(&mut stream, &data).await {
+        let e = OneOneRWError::Write(e).to_string();
+        return payloads
+            .iter()
+            .map(|_| Err(e.clone()))
+            .collect();
+    }
+    let sent = Instant::now();
+    trace!("pipelined| {}", payloads.len());
+
+    // 2. Read
+    let mut buf = BytesMut::with_capacity(RACE_BUF);
+    let mut results = Vec::with_capacity(payloads.len());
+    let mut failed = None;
+    for _ in payloads {
+        if let Some(e) = failed.as_ref() {
+            results.push(Err(String::clone(e)));
+            continue;
+        }
+        match read_pipelined(&mut stream, &mut buf).await {
+            Ok(frame) => results.push(Ok(RaceRead {
+                sent,
+                end: Instant::now(),
+                data: frame.into_data(),
+            })),
+            Err(e) => {
+                let e = e.to_string();
+                results.push(Err(e.clone()));
+                failed = Some(e);
+            }
+        }
+    }
+    results
+}
+
+/* Description:
+ *      Read a response when the previous response may have left the next
+ *      one in buf. Buffered data is parsed before reading from stream, as
+ *      read_http() always reads first.
+ */
+
+async fn read_pipelined<T>(
+    stream: &mut T,
+    buf: &mut BytesMut,
+) -> Result<OneOne<Response>, OneOneRWError>
+where
+    T: AsyncReadExt + Unpin,
+{
+    let mut state = OneOneState::<Response>::new();
+    let mut cbuf = Cursor::new(buf);
+    if !cbuf.as_ref().is_empty() {
+        state = state.next(Event::Read(&mut cbuf))?;
+    }
+    while !state.is_ended() {
+        let event = fill_buffer(stream, &mut cbuf)
+            .await
+            .map_err(OneOneRWError::Read)?;
+        state = state.next(event)?;
+    }
+    Ok(state.into_frame()?)
+}
+
+/* Description:
+ *      Result of a request in race, response is written to race.N.res in
+ *      the directory of the first file.
+ *
+ *      time    : ms from the request being completed to the response
+ *      offset  : us from the earliest completed request, spread of the
+ *                synchronized sends
+ */
+
+#[derive(Debug, Serialize)]
+pub struct RaceResult {
+    index: usize,
+    file: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response: Option<PathBuf>,
+    size: usize,
+    time: u128,
+    offset: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/* Steps:
+ *      1. Get earliest send time.
+ *      2. For each result, write response to race.N.res and build
+ *         RaceResult.
+ *
+ * Error:
+ *      RaceError::WriteResponse    [2]
+ */
+
+pub async fn race_results(
+    dir: &Path,
+    payloads: &[RacePayload],
+    reads: Vec<Result<RaceRead, String>>,
+) -> Result<Vec<RaceResult>, RaceError> {
+    // 1. Earliest
+    let first = reads
+        .iter()
+        .filter_map(|read| read.as_ref().ok())
+        .map(|read| read.sent)
+        .min();
+    // 2. Build
+    let mut results = Vec::with_capacity(reads.len());
+    for (index, (payload, read)) in payloads.iter().zip(reads).enumerate() {
+        let index = index + 1;
+        let mut result = RaceResult {
+            index,
+            file: payload.file.clone(),
+            status: None,
+            response: None,
+            size: 0,
+            time: 0,
+            offset: 0,
+            error: None,
+        };
+        match read {
+            Ok(read) => {
+                let path = dir.join(format!("race.{index}.res"));
+                tokio::fs::write(&path, &read.data)
+                    .await
+                    .map_err(RaceError::WriteResponse)?;
+                result.status = status_code(&read.data);
+                result.response = Some(path);
+                result.size = read.data.len();
+                result.time = read
+                    .end
+                    .duration_since(read.sent)
+                    .as_millis();
+                result.offset = first.map_or(0, |first| {
+                    read.sent
+                        .duration_since(first)
+                        .as_micros()
+                });
+            }
+            Err(e) => result.error = Some(e),
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::duplex;
+
+    use super::*;
+
+    fn payloads(n: usize) -> Vec<RacePayload> {
+        (0..n)
+            .map(|_| RacePayload {
+                file: PathBuf::from("rep.req"),
+                data: BytesMut::from("GET / HTTP/1.1\r\n\r\n"),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_race_last_byte() {
+        let mut servers = Vec::new();
+        let mut clients = Vec::new();
+        for i in 0..3 {
+            let (client, mut server) = duplex(1024);
+            clients.push(client);
+            servers.push(tokio::spawn(async move {
+                let mut buf = BytesMut::new();
+                while !buf.ends_with(b"\r\n\r\n") {
+                    server.read_buf(&mut buf).await.unwrap();
+                }
+                let res = format!(
+                    "HTTP/1.1 20{i} OK\r\nContent-Length: 1\r\n\r\n{i}"
+                );
+                server
+                    .write_all(res.as_bytes())
+                    .await
+                    .unwrap();
+            }));
+        }
+        let payloads = payloads(3);
+        let reads = race_last_byte(clients, &payloads).await;
+        let dir = PathBuf::from("/tmp/zxc_race_last_byte");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let results = race_results(&dir, &payloads, reads)
+            .await
+            .unwrap();
+        let status: Vec<_> = results
+            .iter()
+            .map(|result| result.status.as_deref().unwrap())
+            .collect();
+        assert_eq!(status, ["200", "201", "202"]);
+        assert_eq!(
+            std::fs::read(dir.join("race.2.res")).unwrap(),
+            b"HTTP/1.1 201 OK\r\nContent-Length: 1\r\n\r\n1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_race_pipeline() {
+        let (client, mut server) = duplex(4096);
+        let server = tokio::spawn(async move {
+            let mut buf = BytesMut::new();
+            while buf
+                .windows(4)
+                .filter(|window| *window == b"\r\n\r\n")
+                .count()
+                < 2
+            {
+                server.read_buf(&mut buf).await.unwrap();
+            }
+            // both responses in a single write
+            server
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1\r\n\r\naHTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            drop(server);
+        });
+        let payloads = payloads(3);
+        let reads = race_pipeline(client, &payloads).await;
+        server.await.unwrap();
+        assert_eq!(
+            reads[0]
+                .as_ref()
+                .ok()
+                .map(|read| &read.data[..]),
+            Some(&b"HTTP/1.1 200 OK\r\nContent-Length: 1\r\n\r\na"[..])
+        );
+        assert!(
+            reads[1]
+                .as_ref()
+                .is_ok_and(|read| read.data.starts_with(b"HTTP/1.1 404"))
+        );
+        assert!(reads[2].is_err());
+    }
+}
@@ -0,0 +1,393 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::timeout;
+
+use super::msg_from_ui::SendInfo;
+use super::versions::status_code;
+use crate::proxy::server_info::json::ServerInfoJson;
+
+const DEFAULT_IDLE_MS: u64 = 2000;
+const MAX_IDLE_MS: u64 = 30000;
+const MAX_RAW_SIZE: usize = 16 * 1024 * 1024;
+const RAW_BUF: usize = 64 * 1024;
+pub const REPEATER_RAW_RES: &str = "rep.raw.res";
+
+#[derive(Debug, Error)]
+pub enum RawError {
+    #[error("read request| {0}")]
+    File(io::Error),
+    #[error("empty| {0}")]
+    Empty(PathBuf),
+    #[error("write| {0}")]
+    Write(io::Error),
+    #[error("read| {0}")]
+    Read(io::Error),
+    #[error("write response| {0}")]
+    WriteResponse(io::Error),
+}
+
+/* Description:
+ *      Raw send info from repeater ui. File is sent as is, without parsing
+ *      or updating the request, so that malformed requests (CL.TE, TE.CL,
+ *      obs-fold, bare LF) reach the server byte for byte.
+ *
+ *      Response is read till the server closes the connection, no data is
+ *      received for idle_ms (default 2000, max 30000) or 16MB is read.
+ *
+ * Format:
+ *      [0, {"RawSend": {"file": "./history/1/r-1/rep.req", "idle_ms": 2000,
+ *          "server_info": {"host": "a.com"}}}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub struct RawInfo {
+    pub file: PathBuf,
+    #[serde(default)]
+    pub idle_ms: Option<u64>,
+    pub server_info: ServerInfoJson,
+}
+
+impl RawInfo {
+    // SendInfo to establish a connection
+    pub fn send_info(&self) -> SendInfo {
+        SendInfo {
+            file: self.file.clone(),
+            update: Some(false),
+            server_info: self.server_info.clone(),
+            redirect: None,
+            use_jar: None,
+        }
+    }
+
+    pub fn idle(&self) -> Duration {
+        Duration::from_millis(
+            self.idle_ms
+                .unwrap_or(DEFAULT_IDLE_MS)
+                .min(MAX_IDLE_MS),
+        )
+    }
+
+    // Request bytes of the file, verbatim
+    pub async fn payload(&self) -> Result<Vec<u8>, RawError> {
+        let data = tokio::fs::read(&self.file)
+            .await
+            .map_err(RawError::File)?;
+        if data.is_empty() {
+            return Err(RawError::Empty(self.file.clone()));
+        }
+        Ok(data)
+    }
+}
+
+// Raw byte stream read from the server
+pub struct RawRead {
+    pub data: Vec<u8>,
+    pub time: u128,
+    pub closed: bool,
+}
+
+/* Steps:
+ *      1. Write the payload and flush.
+ *
+ *      2. Read till EOF, idle timeout or MAX_RAW_SIZE. Read error after
+ *         some data is received (e.g. reset) ends the read.
+ *
+ * Error:
+ *      RawError::Write     [1]
+ *      RawError::Read      [2] if no data was read
+ */
+
+pub async fn send_raw<T>(
+    mut stream: T,
+    payload: &[u8],
+    idle: Duration,
+) -> Result<RawRead, RawError>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    // 1. Write
+    let start = Instant::now();
+    stream
+        .write_all(payload)
+        .await
+        .map_err(RawError::Write)?;
+    stream
+        .flush()
+        .await
+        .map_err(RawError::Write)?;
+    // 2. Read
+    let mut data = Vec::with_capacity(RAW_BUF);
+    let mut buf = vec![0; RAW_BUF];
+    let mut closed = false;
+    while data.len() < MAX_RAW_SIZE {
+        match timeout(idle, stream.read(&mut buf)).await {
+            Err(_) => break,
+            Ok(Ok(0)) => {
+                closed = true;
+                break;
+            }
+            Ok(Ok(n)) => data.extend_from_slice(&buf[..n]),
+            Ok(Err(e)) if data.is_empty() => return Err(RawError::Read(e)),
+            Ok(Err(_)) => {
+                closed = true;
+                break;
+            }
+        }
+    }
+    Ok(RawRead {
+        data,
+        time: start.elapsed().as_millis(),
+        closed,
+    })
+}
+
+/* Description:
+ *      Response found in the raw byte stream.
+ *
+ *      offset, size    : position in rep.raw.res
+ *      complete        : false if the stream ended before the framed end,
+ *                        or the response has no framing (read till close)
+ */
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RawResponse {
+    pub offset: usize,
+    pub size: usize,
+    pub status: Option<String>,
+    pub complete: bool,
+}
+
+// Position after the header block, CRLFCRLF or bare LFLF
+fn header_end(data: &[u8]) -> Option<usize> {
+    (0..data.len()).find_map(|pos| {
+        if data[pos..].starts_with(b"\r\n\r\n") {
+            Some(pos + 4)
+        } else if data[pos..].starts_with(b"\n\n") {
+            Some(pos + 2)
+        } else if data[pos..].starts_with(b"\n\r\n") {
+            Some(pos + 3)
+        } else {
+            None
+        }
+    })
+}
+
+// Value of the first header with key, ignoring case
+fn header<'a>(head: &'a str, key: &str) -> Option<&'a str> {
+    head.lines().skip(1).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case(key)
+            .then(|| value.trim())
+    })
+}
+
+// End of a chunked body starting at data[0], None if incomplete
+fn chunked_end(data: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    loop {
+        let line_end = pos
+            + data[pos..]
+                .iter()
+                .position(|b| *b == b'\n')?;
+        let line = String::from_utf8_lossy(&data[pos..line_end]);
+        let size = line.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        pos = line_end + 1;
+        if size == 0 {
+            // trailers till empty line
+            loop {
+                let end = pos
+                    + data[pos..]
+                        .iter()
+                        .position(|b| *b == b'\n')?;
+                let empty = data[pos..end]
+                    .iter()
+                    .all(|b| *b == b'\r');
+                pos = end + 1;
+                if empty {
+                    return Some(pos);
+                }
+            }
+        }
+        pos = pos.checked_add(size)?;
+        if data.len() < pos {
+            return None;
+        }
+        // chunk CRLF
+        if data[pos..].starts_with(b"\r\n") {
+            pos += 2;
+        } else if data[pos..].starts_with(b"\n") {
+            pos += 1;
+        }
+    }
+}
+
+/* Steps:
+ *      1. Skip leading whitespace, a response starts with "HTTP/".
+ *
+ *      2. Find end of headers, if missing the rest is an incomplete response.
+ *
+ *      3. Body length,
+ *          a. 1xx, 204, 304    => no body
+ *          b. chunked          => parse chunks [ chunked_end() ]
+ *          c. Content-Length   => length
+ *          d. else             => till end of stream
+ *
+ *      4. Repeat from the end of the response.
+ *
+ *      Best effort, HEAD responses with Content-Length are split wrongly.
+ */
+
+pub fn split_responses(data: &[u8]) -> Vec<RawResponse> {
+    let mut responses = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        // 1. Start
+        let Some(skip) = data[pos..]
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+        else {
+            break;
+        };
+        let offset = pos + skip;
+        let rest = &data[offset..];
+        let status = status_code(rest);
+        // 2. Headers
+        let Some(head_len) =
+            header_end(rest).filter(|_| rest.starts_with(b"HTTP/"))
+        else {
+            responses.push(RawResponse {
+                offset,
+                size: rest.len(),
+                status: rest
+                    .starts_with(b"HTTP/")
+                    .then_some(status)
+                    .flatten(),
+                complete: false,
+            });
+            break;
+        };
+        let head = String::from_utf8_lossy(&rest[..head_len]);
+        let body = &rest[head_len..];
+        // 3. Body
+        let no_body = status.as_deref().is_some_and(|status| {
+            status.starts_with('1') || status == "204" || status == "304"
+        });
+        let chunked =
+            header(&head, "transfer-encoding").is_some_and(|value| {
+                value
+                    .to_ascii_lowercase()
+                    .contains("chunked")
+            });
+        let body_len = if no_body {
+            Some(0)
+        } else if chunked {
+            chunked_end(body)
+        } else if let Some(len) = header(&head, "content-length") {
+            len.parse::<usize>()
+                .ok()
+                .filter(|len| *len <= body.len())
+        } else {
+            None
+        };
+        let (size, complete) = match body_len {
+            Some(len) => (head_len + len, true),
+            None => (rest.len(), false),
+        };
+        responses.push(RawResponse {
+            offset,
+            size,
+            status,
+            complete,
+        });
+        // 4. Next
+        pos = offset + size;
+    }
+    responses
+}
+
+// Write raw stream to rep.raw.res, alongside the request
+pub async fn write_raw(file: &Path, data: &[u8]) -> Result<PathBuf, RawError> {
+    let path = file.with_file_name(REPEATER_RAW_RES);
+    tokio::fs::write(&path, data)
+        .await
+        .map_err(RawError::WriteResponse)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::duplex;
+
+    use super::*;
+
+    #[test]
+    fn test_split_responses_pipelined() {
+        let data = b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhelloHTTP/1.1 404 Not Found\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\nHTTP/1.1 400 Bad\nContent-Length: 10\n\nshort";
+        let result = split_responses(data);
+        let verify = vec![
+            (0, 25, "100", true),
+            (25, 43, "200", true),
+            (68, 67, "404", true),
+            (135, 42, "400", false),
+        ];
+        assert_eq!(
+            result
+                .iter()
+                .map(|res| (
+                    res.offset,
+                    res.size,
+                    res.status.as_deref().unwrap(),
+                    res.complete
+                ))
+                .collect::<Vec<_>>(),
+            verify
+        );
+        assert!(data[68..135].starts_with(b"HTTP/1.1 404"));
+        assert!(data[135..].ends_with(b"short"));
+    }
+
+    #[test]
+    fn test_split_responses_garbage() {
+        let result = split_responses(b"\r\ngarbage");
+        assert_eq!(
+            result,
+            vec![RawResponse {
+                offset: 2,
+                size: 7,
+                status: None,
+                complete: false,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_raw_verbatim() {
+        let (client, mut server) = duplex(1024);
+        let payload = b"POST / HTTP/1.1\nHost: a\nContent-Length: 3\nTransfer-Encoding: chunked\n\n0\r\n\r\nG";
+        let handle = tokio::spawn(async move {
+            let mut buf = vec![0; payload.len()];
+            server
+                .read_exact(&mut buf)
+                .await
+                .unwrap();
+            server
+                .write_all(b"HTTP/1.1 200 OK\r\n\r\n")
+                .await
+                .unwrap();
+            buf
+        });
+        let read = send_raw(client, payload, Duration::from_millis(200))
+            .await
+            .unwrap();
+        assert_eq!(handle.await.unwrap(), payload);
+        assert_eq!(read.data, b"HTTP/1.1 200 OK\r\n\r\n");
+        assert!(read.closed);
+    }
+}
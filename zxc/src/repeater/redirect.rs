@@ -0,0 +1,383 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use bytes::BytesMut;
+use oneone::Response;
+use protocol_traits::Frame;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use super::versions::status_code;
+use crate::history::http::{HttpMessage, header_value};
+use crate::io::write::write_and_flush;
+use crate::proxy::handler_state::handlers::oneonestruct::OneOneRWError;
+use crate::proxy::handler_state::handlers::read_http;
+use crate::proxy::server_info::json::ServerInfoJson;
+
+const DEFAULT_MAX_HOPS: usize = 5;
+const MAX_HOPS: usize = 20;
+const HOP_BUF: usize = 64 * 1024;
+const REDIRECT_CODES: [&str; 5] = ["301", "302", "303", "307", "308"];
+// Dropped on cross origin hops, unless keep_credentials
+const CREDENTIAL_HEADERS: [&str; 3] =
+    ["cookie", "authorization", "proxy-authorization"];
+// Dropped when the body is dropped
+const BODY_HEADERS: [&str; 3] =
+    ["content-length", "content-type", "transfer-encoding"];
+
+#[derive(Debug, Error)]
+pub enum RedirectError {
+    #[error("file| {0}")]
+    File(io::Error),
+    #[error("hop| {0}")]
+    Hop(#[from] OneOneRWError),
+}
+
+/* Description:
+ *      Follow redirects in Send.
+ *
+ * Format:
+ *      "redirect": {"max_hops": 5, "keep_credentials": false}
+ */
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RedirectInfo {
+    #[serde(default)]
+    max_hops: Option<usize>,
+    #[serde(default)]
+    pub keep_credentials: bool,
+}
+
+impl RedirectInfo {
+    pub fn max_hops(&self) -> usize {
+        self.max_hops
+            .unwrap_or(DEFAULT_MAX_HOPS)
+            .min(MAX_HOPS)
+    }
+}
+
+// Entry in the redirect chain, error is set if the hop failed and the
+// chain was not followed further.
+#[derive(Debug, Serialize)]
+pub struct Hop {
+    pub host: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<PathBuf>,
+    pub size: usize,
+    pub time: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Hop {
+    pub fn new(
+        host: &str,
+        response: PathBuf,
+        data: &[u8],
+        time: u128,
+    ) -> Self {
+        let res = HttpMessage::parse(data);
+        Self {
+            host: host.to_string(),
+            status: status_code(data),
+            location: header_value(&res.headers, "location")
+                .map(str::to_string),
+            response: Some(response),
+            size: data.len(),
+            time,
+            error: None,
+        }
+    }
+
+    pub fn failed(host: &str, error: String) -> Self {
+        Self {
+            host: host.to_string(),
+            status: None,
+            location: None,
+            response: None,
+            size: 0,
+            time: 0,
+            error: Some(error),
+        }
+    }
+}
+
+// Request to the location of a redirect
+#[cfg_attr(test, derive(Debug))]
+pub struct NextHop {
+    pub request: Vec<u8>,
+    pub server_info: ServerInfoJson,
+}
+
+// scheme and authority without default port, for origin comparison
+fn origin(http: bool, host: &str) -> (bool, String) {
+    let host = host.to_ascii_lowercase();
+    let default = if http {
+        ":80"
+    } else {
+        ":443"
+    };
+    let host = host
+        .strip_suffix(default)
+        .map(str::to_string)
+        .unwrap_or(host);
+    (http, host)
+}
+
+/* Steps:
+ *      1. If status is a redirect with Location header, continue.
+ *
+ *      2. Resolve location against the current server,
+ *          http(s)://h/p   => scheme and host of location
+ *          //h/p           => scheme of current
+ *          /p              => current server
+ *          p               => relative to directory of request path
+ *
+ *      3. Method and body,
+ *          303 (non HEAD) or 301/302 with POST => GET without body
+ *          else keep method and body
+ *
+ *      4. Headers,
+ *          a. Host set to new host.
+ *          b. Body headers dropped if body is dropped.
+ *          c. Credential headers dropped on cross origin hop if not
+ *             keep_credentials.
+ *
+ *      5. Build request, sni is kept only for same origin.
+ */
+
+pub fn next_hop(
+    req: &[u8],
+    res: &[u8],
+    current: &ServerInfoJson,
+    keep_credentials: bool,
+) -> Option<NextHop> {
+    // 1. Redirect
+    let res = HttpMessage::parse(res);
+    if !REDIRECT_CODES.contains(&res.second.as_str()) {
+        return None;
+    }
+    let location = header_value(&res.headers, "location")?.trim();
+    let location = location
+        .split('#')
+        .next()
+        .unwrap_or(location);
+    let req = HttpMessage::parse(req);
+
+    // 2. Resolve
+    let http = current.http.is_some();
+    let (http, host, uri) =
+        if let Some((scheme, rest)) = location.split_once("://") {
+            let http = match scheme.to_ascii_lowercase().as_str() {
+                "http" => true,
+                "https" => false,
+                _ => return None,
+            };
+            let (host, uri) = split_authority(rest);
+            (http, host.to_string(), uri.to_string())
+        } else if let Some(rest) = location.strip_prefix("//") {
+            let (host, uri) = split_authority(rest);
+            (http, host.to_string(), uri.to_string())
+        } else if location.starts_with('/') {
+            (http, current.host.clone(), location.to_string())
+        } else {
+            let path = req
+                .second
+                .split('?')
+                .next()
+                .unwrap_or_default();
+            let dir = path
+                .rfind('/')
+                .map_or("/", |i| &path[..=i]);
+            (http, current.host.clone(), format!("{dir}{location}"))
+        };
+    if host.is_empty() {
+        return None;
+    }
+    let same_origin =
+        origin(http, &host) == origin(current.http.is_some(), &current.host);
+
+    // 3. Method
+    let status = res.second.as_str();
+    let to_get = (status == "303" && req.first != "HEAD")
+        || (matches!(status, "301" | "302") && req.first == "POST");
+    let method = if to_get {
+        "GET"
+    } else {
+        req.first.as_str()
+    };
+
+    // 4. Headers
+    let mut request = format!("{method} {uri} {}\r\n", req.third).into_bytes();
+    for header in req.headers.iter() {
+        let name = header.name.to_ascii_lowercase();
+        let value = if name == "host" {
+            host.as_str()
+        } else if (to_get && BODY_HEADERS.contains(&name.as_str()))
+            || (!same_origin
+                && !keep_credentials
+                && CREDENTIAL_HEADERS.contains(&name.as_str()))
+        {
+            continue;
+        } else {
+            header.value.as_str()
+        };
+        request.extend_from_slice(
+            format!("{}: {}\r\n", header.name, value).as_bytes(),
+        );
+    }
+    request.extend_from_slice(b"\r\n");
+    if !to_get {
+        request.extend_from_slice(&req.body);
+    }
+
+    // 5. Server info
+    let sni = if same_origin {
+        current.sni.clone()
+    } else {
+        None
+    };
+    Some(NextHop {
+        request,
+        server_info: ServerInfoJson {
+            host,
+            http: http.then_some(true),
+            sni,
+        },
+    })
+}
+
+// Split authority and origin form uri
+fn split_authority(rest: &str) -> (&str, &str) {
+    match rest.find(['/', '?']) {
+        Some(pos) if rest[pos..].starts_with('?') => (&rest[..pos], "/"),
+        Some(pos) => (&rest[..pos], &rest[pos..]),
+        None => (rest, "/"),
+    }
+}
+
+// ./history/1/r-1/rep.req -> ./history/1/r-1/rep.hop-{hop}.{ext}
+pub fn hop_path(file: &Path, hop: usize, ext: &str) -> PathBuf {
+    let stem = file
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    file.with_file_name(format!("{stem}.hop-{hop}.{ext}"))
+}
+
+/* Description:
+ *      Send hop request on the stream and read the response.
+ *
+ * Returns:
+ *      (response, time in ms)
+ */
+
+pub async fn send_hop<T>(
+    stream: &mut T,
+    request: &[u8],
+) -> Result<(BytesMut, u128), RedirectError>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    let start = Instant::now();
+    write_and_flush(stream, request)
+        .await
+        .map_err(OneOneRWError::Write)?;
+    let mut buf = BytesMut::with_capacity(HOP_BUF);
+    let frame = read_http::<T, Response>(stream, &mut buf).await?;
+    Ok((frame.into_data(), start.elapsed().as_millis()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POST: &[u8] = b"POST /a/login HTTP/1.1\r\nHost: a.com\r\nCookie: s=1\r\nAuthorization: Basic eA==\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: 3\r\n\r\nx=1";
+
+    fn server(host: &str) -> ServerInfoJson {
+        ServerInfoJson {
+            host: host.to_string(),
+            http: None,
+            sni: None,
+        }
+    }
+
+    fn hop(res: &str, keep: bool) -> Option<(String, ServerInfoJson)> {
+        next_hop(POST, res.as_bytes(), &server("a.com"), keep).map(|next| {
+            (String::from_utf8(next.request).unwrap(), next.server_info)
+        })
+    }
+
+    #[test]
+    fn test_next_hop_same_origin_to_get() {
+        let (req, info) =
+            hop("HTTP/1.1 302 Found\r\nLocation: home?x=1#top\r\n\r\n", false)
+                .unwrap();
+        assert_eq!(
+            req,
+            "GET /a/home?x=1 HTTP/1.1\r\nHost: a.com\r\nCookie: s=1\r\nAuthorization: Basic eA==\r\n\r\n"
+        );
+        assert_eq!(info.host, "a.com");
+        assert!(info.http.is_none());
+    }
+
+    #[test]
+    fn test_next_hop_cross_origin_keep_body() {
+        let (req, info) = hop(
+            "HTTP/1.1 307 Temporary Redirect\r\nLocation: http://b.com:8080/in\r\n\r\n",
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            req,
+            "POST /in HTTP/1.1\r\nHost: b.com:8080\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: 3\r\n\r\nx=1"
+        );
+        assert_eq!(info.host, "b.com:8080");
+        assert_eq!(info.http, Some(true));
+
+        let (req, _) = hop(
+            "HTTP/1.1 308 Permanent Redirect\r\nLocation: //c.com\r\n\r\n",
+            true,
+        )
+        .unwrap();
+        assert!(
+            req.starts_with(
+                "POST / HTTP/1.1\r\nHost: c.com\r\nCookie: s=1\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_next_hop_none() {
+        assert!(
+            hop("HTTP/1.1 200 OK\r\nLocation: /x\r\n\r\n", false).is_none()
+        );
+        assert!(hop("HTTP/1.1 302 Found\r\n\r\n", false).is_none());
+        assert!(
+            hop("HTTP/1.1 302 Found\r\nLocation: ftp://a.com/\r\n\r\n", false)
+                .is_none()
+        );
+        assert_eq!(origin(false, "A.com:443"), origin(false, "a.com"));
+    }
+
+    #[test]
+    fn test_next_hop_relative_without_slash() {
+        let next = next_hop(
+            b"OPTIONS * HTTP/1.1\r\nHost: a.com\r\n\r\n",
+            b"HTTP/1.1 302 Found\r\nLocation: home\r\n\r\n",
+            &server("a.com"),
+            false,
+        )
+        .unwrap();
+        assert!(
+            next.request
+                .starts_with(b"OPTIONS /home HTTP/1.1\r\n")
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 use std::sync::Arc;
+use std::time::Instant;
 
 use tokio::net::TcpStream;
 use tokio_rustls::TlsConnector;
@@ -7,10 +8,11 @@ use tokio_rustls::client::TlsStream;
 use tracing::trace;
 
 use crate::async_step::AsyncStep;
-use crate::io::socket::establish_connection;
+use crate::io::socket::{connect_resolved, resolve};
 use crate::proxy::states::ZStream;
 use crate::repeater::conn::RepeaterConn;
 use crate::repeater::error::RepeaterError;
+use crate::repeater::timing::micros;
 
 // Initial Repeater States, upto connection establishment + encryption
 pub enum RepeaterConnState {
@@ -28,7 +30,8 @@ impl AsyncStep for RepeaterConnState {
     async fn next(self) -> Result<Self, Self::Error> {
         match self {
             /* Description:
-             *      Establish connection with server
+             *      Establish connection with server. Resolve and connect are
+             *      timed separately.
              *
              * Transition:
              *      if Tls -> NeedConnector
@@ -37,8 +40,13 @@ impl AsyncStep for RepeaterConnState {
              * Error:
              *      RepeaterError::Connection
              */
-            Self::EstablishServerConn(rconn) => {
-                let stream = establish_connection(rconn.address()).await?;
+            Self::EstablishServerConn(mut rconn) => {
+                let start = Instant::now();
+                let addrs = resolve(rconn.address()).await?;
+                let resolved = Instant::now();
+                let stream = connect_resolved(rconn.address(), &addrs).await?;
+                rconn.timing.dns = micros(start, resolved);
+                rconn.timing.connect = micros(resolved, Instant::now());
                 let rconn = RepeaterConn::<TcpStream>::from((rconn, stream));
                 trace!("Y| {}", rconn.address());
                 if rconn.tls() {
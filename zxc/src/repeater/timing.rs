@@ -0,0 +1,50 @@
+use std::path::Path;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use super::file_builder::REPEATER_HTTP_FILENAME_TIMING;
+
+/* Description:
+ *      Timing breakdown of a repeater http request, in microseconds.
+ *
+ *      dns         : resolve host, 0 for ip address
+ *      connect     : tcp connect
+ *      tls         : tls handshake, 0 for http
+ *      write       : write request
+ *      first_byte  : request written to first byte of response
+ *      download    : first byte to end of response
+ *      total       : start of send to end of response
+ *
+ * Format:
+ *      {"dns":1250,"connect":20300,"tls":41000,"write":80,
+ *       "first_byte":95000,"download":1200,"total":158830}
+ */
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Timing {
+    pub dns: u128,
+    pub connect: u128,
+    pub tls: u128,
+    pub write: u128,
+    pub first_byte: u128,
+    pub download: u128,
+    pub total: u128,
+}
+
+// Microseconds elapsed from start to end
+pub fn micros(start: Instant, end: Instant) -> u128 {
+    end.saturating_duration_since(start)
+        .as_micros()
+}
+
+// Write timing as json to rep.timing.json, alongside rep.res
+pub async fn write_timing(
+    file: &Path,
+    timing: &Timing,
+) -> Result<(), std::io::Error> {
+    // safe to unwrap, only integers
+    let data = serde_json::to_vec(timing).unwrap();
+    tokio::fs::write(file.with_file_name(REPEATER_HTTP_FILENAME_TIMING), data)
+        .await
+}
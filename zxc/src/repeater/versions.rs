@@ -0,0 +1,301 @@
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::fs::{OpenOptions, copy, read_to_string};
+use tokio::io::AsyncWriteExt;
+
+use super::timing::Timing;
+use crate::commander::compare::{CompareInfo, DiffMode};
+use crate::file_types::{EXT_REQ, EXT_RES};
+use crate::io::inc_dir::incremental;
+
+pub const REPEATER_VERSIONS: &str = "versions.jsonl";
+
+#[derive(Debug, Error)]
+pub enum VersionError {
+    #[error("io| {0}")]
+    Io(#[from] io::Error),
+    #[error("parse| {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("not found| {0}")]
+    NotFound(usize),
+}
+
+/* Description:
+ *      Entry of a sent request in versions.jsonl of the repeater tab, with
+ *      the timing breakdown of the send. Timing is missing in entries
+ *      written before it was recorded.
+ *
+ * Format:
+ *      {"version":1,"status":"200","size":1024,"time":35,
+ *       "timing":{"dns":1250,"connect":20300,"tls":41000,"write":80,
+ *       "first_byte":95000,"download":1200,"total":158830}}
+ */
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Version {
+    pub version: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    pub size: usize,
+    pub time: u128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<Timing>,
+}
+
+/* Description:
+ *      Diff two versions of a repeater tab, responses by default.
+ *
+ * Format:
+ *      [0, {"VersionDiff": {"file": "./history/1/r-1/rep.req", "left": 1,
+ *          "right": 3, "request": false, "mode": "Word"}}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub struct VersionDiffInfo {
+    pub file: PathBuf,
+    pub left: usize,
+    pub right: usize,
+    #[serde(default)]
+    pub request: bool,
+    #[serde(default)]
+    pub ignore_header_order: bool,
+    #[serde(default)]
+    pub mode: DiffMode,
+}
+
+impl VersionDiffInfo {
+    // Paths of the versions to compare
+    pub fn to_compare_info(&self) -> Result<CompareInfo, VersionError> {
+        let ext = if self.request {
+            EXT_REQ
+        } else {
+            EXT_RES
+        };
+        let [left, right] = [self.left, self.right].map(|version| {
+            let path = version_path(&self.file, version, ext);
+            if path.exists() {
+                Ok(path)
+            } else {
+                Err(VersionError::NotFound(version))
+            }
+        });
+        Ok(CompareInfo {
+            left: left?,
+            right: right?,
+            ignore_header_order: self.ignore_header_order,
+            mode: self.mode,
+        })
+    }
+}
+
+/* Description:
+ *      Restore request of a version as the current request of the tab.
+ *
+ * Format:
+ *      [0, {"Restore": {"file": "./history/1/r-1/rep.req", "version": 2}}]
+ */
+
+#[derive(Debug, Deserialize)]
+pub struct RestoreInfo {
+    pub file: PathBuf,
+    pub version: usize,
+}
+
+// ./history/1/r-1/rep.req -> ./history/1/r-1/rep.{version}.{ext}
+fn version_path(file: &Path, version: usize, ext: &str) -> PathBuf {
+    let stem = file
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    file.with_file_name(format!("{stem}.{version}.{ext}"))
+}
+
+fn versions_file(file: &Path) -> PathBuf {
+    file.with_file_name(REPEATER_VERSIONS)
+}
+
+/* Steps:
+ *      1. Get next version by calling incremental() with "{stem}." prefix
+ *         on the tab directory.
+ *
+ *      2. Copy the sent request (rewritten if updated) to rep.N.req.
+ *
+ *      3. If response is present, write it to rep.N.res.
+ *
+ *      4. Append entry to versions.jsonl.
+ *
+ * Error:
+ *      VersionError::Io   [1] [2] [3] [4]
+ */
+
+pub async fn save_version(
+    file: &Path,
+    response: Option<&[u8]>,
+    time: u128,
+    timing: Timing,
+) -> Result<Version, VersionError> {
+    // 1. Next version
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let stem = file
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let version = incremental(dir, &format!("{stem}."), false).await?;
+
+    // 2. Request
+    copy(file, version_path(file, version, EXT_REQ)).await?;
+
+    // 3. Response
+    let response = response.filter(|data| !data.is_empty());
+    if let Some(data) = response {
+        tokio::fs::write(version_path(file, version, EXT_RES), data).await?;
+    }
+
+    // 4. Log
+    let entry = Version {
+        version,
+        status: response.and_then(status_code),
+        size: response.map_or(0, <[u8]>::len),
+        time,
+        timing: Some(timing),
+    };
+    let mut line = serde_json::to_vec(&entry)?;
+    line.push(b'\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(versions_file(file))
+        .await?
+        .write_all(&line)
+        .await?;
+    Ok(entry)
+}
+
+// Status code from the response line
+pub fn status_code(data: &[u8]) -> Option<String> {
+    let line = data.split(|b| *b == b'\n').next()?;
+    let status = String::from_utf8_lossy(line)
+        .split(' ')
+        .nth(1)?
+        .trim()
+        .to_string();
+    Some(status)
+}
+
+/* Description:
+ *      List versions of the tab from versions.jsonl, no file is no versions.
+ *
+ * Error:
+ *      VersionError::Io
+ *      VersionError::Parse
+ */
+
+pub async fn list_versions(file: &Path) -> Result<Vec<Version>, VersionError> {
+    let data = match read_to_string(versions_file(file)).await {
+        Ok(data) => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    data.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_str(line).map_err(Into::into))
+        .collect()
+}
+
+/* Steps:
+ *      1. Copy rep.N.req to rep.req.
+ *      2. If rep.N.res is present, copy to rep.res.
+ *
+ * Error:
+ *      VersionError::NotFound     [1]
+ *      VersionError::Io           [1] [2]
+ */
+
+pub async fn restore_version(info: &RestoreInfo) -> Result<(), VersionError> {
+    // 1. Request
+    let req = version_path(&info.file, info.version, EXT_REQ);
+    if !req.exists() {
+        return Err(VersionError::NotFound(info.version));
+    }
+    copy(&req, &info.file).await?;
+
+    // 2. Response
+    let res = version_path(&info.file, info.version, EXT_RES);
+    if res.exists() {
+        copy(&res, info.file.with_extension(EXT_RES)).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_versions_save_list_restore() {
+        let dir = PathBuf::from("/tmp/zxc_repeater_versions/r-1");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("rep.req");
+
+        fs::write(&file, "GET /1 HTTP/1.1\r\n\r\n").unwrap();
+        let res = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+        let timing = Timing {
+            total: 10_000,
+            ..Timing::default()
+        };
+        save_version(&file, Some(res), 10, timing)
+            .await
+            .unwrap();
+        fs::write(&file, "GET /2 HTTP/1.1\r\n\r\n").unwrap();
+        let entry = save_version(&file, None, 20, Timing::default())
+            .await
+            .unwrap();
+        assert_eq!(entry.version, 2);
+
+        let verify = vec![
+            Version {
+                version: 1,
+                status: Some("200".to_string()),
+                size: res.len(),
+                time: 10,
+                timing: Some(timing),
+            },
+            Version {
+                version: 2,
+                status: None,
+                size: 0,
+                time: 20,
+                timing: Some(Timing::default()),
+            },
+        ];
+        assert_eq!(list_versions(&file).await.unwrap(), verify);
+        assert!(!dir.join("rep.2.res").exists());
+
+        restore_version(&RestoreInfo {
+            file: file.clone(),
+            version: 1,
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "GET /1 HTTP/1.1\r\n\r\n"
+        );
+        assert_eq!(fs::read(dir.join("rep.res")).unwrap(), res);
+        assert!(matches!(
+            restore_version(&RestoreInfo {
+                file,
+                version: 3,
+            })
+            .await,
+            Err(VersionError::NotFound(3))
+        ));
+    }
+}
@@ -53,7 +53,7 @@ pub async fn spawn_repeater_ws<T>(
 where
     T: AsyncWriteExt + AsyncReadExt + Unpin + Send + 'static,
 {
-    let mut hconn = handle_http(conn).await?;
+    let mut hconn = handle_http(conn, None).await?;
     let response = hconn.get_payload().unwrap();
     // check status code
     match get_status_code(response)? {